@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TABLE users ADD COLUMN locale VARCHAR(16) NOT NULL DEFAULT 'en' AFTER role;
\ No newline at end of file
@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+ALTER TABLE users ADD COLUMN mfa_enabled BOOLEAN NOT NULL DEFAULT FALSE AFTER verified;
+
+CREATE TABLE user_mfa (
+    user_id CHAR(36) PRIMARY KEY NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    last_used_step BIGINT NULL DEFAULT NULL,
+    confirmed_at TIMESTAMP NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT user_mfa_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE TABLE mfa_recovery_codes (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    code_hash VARCHAR(255) NOT NULL,
+    used_at TIMESTAMP NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT mfa_recovery_codes_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX mfa_recovery_codes_user_idx ON mfa_recovery_codes (user_id);
\ No newline at end of file
@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+DROP INDEX users_status_expires_idx ON users;
+ALTER TABLE users DROP FOREIGN KEY users_status_changed_by_fk;
+ALTER TABLE users
+    DROP COLUMN status_expires_at,
+    DROP COLUMN status_changed_by,
+    DROP COLUMN status_reason,
+    DROP COLUMN status;
\ No newline at end of file
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+ALTER TABLE users
+    ADD COLUMN status ENUM('active', 'suspended', 'banned') NOT NULL DEFAULT 'active' AFTER role,
+    ADD COLUMN status_reason VARCHAR(500) NULL DEFAULT NULL AFTER status,
+    ADD COLUMN status_changed_by CHAR(36) NULL DEFAULT NULL AFTER status_reason,
+    ADD COLUMN status_expires_at TIMESTAMP NULL DEFAULT NULL AFTER status_changed_by,
+    ADD CONSTRAINT users_status_changed_by_fk FOREIGN KEY (status_changed_by) REFERENCES users (id) ON DELETE SET NULL;
+
+CREATE INDEX users_status_expires_idx ON users (status, status_expires_at);
\ No newline at end of file
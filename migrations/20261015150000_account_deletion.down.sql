@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS user_deletions;
+DROP INDEX users_deletion_scheduled_idx ON users;
+ALTER TABLE users
+    DROP COLUMN deletion_scheduled_at,
+    DROP COLUMN deletion_requested_at;
\ No newline at end of file
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+ALTER TABLE users
+    ADD COLUMN deletion_requested_at TIMESTAMP NULL DEFAULT NULL AFTER status_expires_at,
+    ADD COLUMN deletion_scheduled_at TIMESTAMP NULL DEFAULT NULL AFTER deletion_requested_at;
+
+CREATE INDEX users_deletion_scheduled_idx ON users (deletion_scheduled_at);
+
+-- No foreign key on purpose: rows outlive the user they describe.
+CREATE TABLE user_deletions (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    email_hash CHAR(64) NOT NULL,
+    requested_at TIMESTAMP NULL DEFAULT NULL,
+    purged_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX user_deletions_user_idx ON user_deletions (user_id);
+CREATE INDEX user_deletions_email_hash_idx ON user_deletions (email_hash);
\ No newline at end of file
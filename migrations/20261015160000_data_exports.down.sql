@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS data_exports;
+DROP TABLE IF EXISTS role_history;
\ No newline at end of file
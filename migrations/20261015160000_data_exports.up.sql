@@ -0,0 +1,31 @@
+-- Add up migration script here
+
+CREATE TABLE role_history (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    old_role ENUM('admin', 'moderator', 'user') NOT NULL,
+    new_role ENUM('admin', 'moderator', 'user') NOT NULL,
+    changed_by CHAR(36) NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT role_history_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    CONSTRAINT role_history_changed_by_fk FOREIGN KEY (changed_by) REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE INDEX role_history_user_idx ON role_history (user_id);
+
+CREATE TABLE data_exports (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    status ENUM('pending', 'processing', 'ready', 'failed') NOT NULL DEFAULT 'pending',
+    storage_key VARCHAR(255) NULL DEFAULT NULL,
+    token_hash VARCHAR(255) NULL DEFAULT NULL,
+    error VARCHAR(500) NULL DEFAULT NULL,
+    expires_at TIMESTAMP NULL DEFAULT NULL,
+    started_at TIMESTAMP NULL DEFAULT NULL,
+    completed_at TIMESTAMP NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT data_exports_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX data_exports_user_idx ON data_exports (user_id);
+CREATE INDEX data_exports_status_idx ON data_exports (status, expires_at);
\ No newline at end of file
@@ -0,0 +1,37 @@
+-- Add down migration script here
+
+ALTER TABLE role_history
+    ADD COLUMN old_role ENUM('admin', 'moderator', 'user') NOT NULL DEFAULT 'user' AFTER user_id,
+    ADD COLUMN new_role ENUM('admin', 'moderator', 'user') NOT NULL DEFAULT 'user' AFTER old_role;
+
+DELETE FROM role_history
+WHERE action = 'removed' OR role NOT IN ('admin', 'moderator', 'user');
+
+UPDATE role_history SET new_role = role;
+
+ALTER TABLE role_history
+    DROP COLUMN action,
+    DROP COLUMN role,
+    ALTER COLUMN old_role DROP DEFAULT,
+    ALTER COLUMN new_role DROP DEFAULT;
+
+ALTER TABLE users ADD COLUMN role ENUM('admin', 'moderator', 'user') NOT NULL DEFAULT 'user' AFTER password;
+
+-- Users holding several roles keep the most privileged one.
+UPDATE users u
+SET role = CASE
+    WHEN EXISTS (
+        SELECT 1 FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+        WHERE ur.user_id = u.id AND r.name = 'admin'
+    ) THEN 'admin'
+    WHEN EXISTS (
+        SELECT 1 FROM user_roles ur JOIN roles r ON r.id = ur.role_id
+        WHERE ur.user_id = u.id AND r.name = 'moderator'
+    ) THEN 'moderator'
+    ELSE 'user'
+END;
+
+DROP TABLE IF EXISTS user_roles;
+DROP TABLE IF EXISTS role_permissions;
+DROP TABLE IF EXISTS permissions;
+DROP TABLE IF EXISTS roles;
\ No newline at end of file
@@ -0,0 +1,85 @@
+-- Add up migration script here
+
+CREATE TABLE roles (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    name VARCHAR(50) NOT NULL UNIQUE,
+    description VARCHAR(255) NULL DEFAULT NULL,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
+
+CREATE TABLE permissions (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description VARCHAR(255) NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE role_permissions (
+    role_id CHAR(36) NOT NULL,
+    permission_id CHAR(36) NOT NULL,
+    PRIMARY KEY (role_id, permission_id),
+    CONSTRAINT role_permissions_role_fk FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+    CONSTRAINT role_permissions_permission_fk FOREIGN KEY (permission_id) REFERENCES permissions (id) ON DELETE CASCADE
+);
+
+CREATE TABLE user_roles (
+    user_id CHAR(36) NOT NULL,
+    role_id CHAR(36) NOT NULL,
+    assigned_by CHAR(36) NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, role_id),
+    CONSTRAINT user_roles_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+    CONSTRAINT user_roles_role_fk FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+    CONSTRAINT user_roles_assigned_by_fk FOREIGN KEY (assigned_by) REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE INDEX user_roles_role_idx ON user_roles (role_id);
+
+INSERT INTO roles (id, name, description, is_system) VALUES
+    (UUID(), 'admin', 'Full access to every account and role', TRUE),
+    (UUID(), 'moderator', 'Can look up other accounts', TRUE),
+    (UUID(), 'user', 'Given to every account on registration', TRUE);
+
+INSERT INTO permissions (id, name, description) VALUES
+    (UUID(), 'profile:read', 'Read the own profile'),
+    (UUID(), 'profile:write', 'Change the own profile, password and sessions'),
+    (UUID(), 'users:read', 'List and view other accounts'),
+    (UUID(), 'users:write', 'Change, suspend and assign roles to other accounts'),
+    (UUID(), 'roles:read', 'List roles and permissions'),
+    (UUID(), 'roles:write', 'Create, change and delete roles');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r
+JOIN permissions p ON
+    (r.name = 'user' AND p.name IN ('profile:read', 'profile:write'))
+    OR (r.name = 'moderator' AND p.name IN ('profile:read', 'profile:write', 'users:read'))
+    OR r.name = 'admin';
+
+INSERT INTO user_roles (user_id, role_id)
+SELECT u.id, r.id
+FROM users u
+JOIN roles r ON r.name = u.role;
+
+ALTER TABLE users DROP COLUMN role;
+
+-- Role history now records single assignments instead of a swap.
+ALTER TABLE role_history
+    ADD COLUMN role VARCHAR(50) NULL DEFAULT NULL AFTER user_id,
+    ADD COLUMN action ENUM('assigned', 'removed') NULL DEFAULT NULL AFTER role;
+
+INSERT INTO role_history (id, user_id, role, action, changed_by, created_at)
+SELECT UUID(), user_id, old_role, 'removed', changed_by, created_at
+FROM role_history;
+
+UPDATE role_history
+SET role = new_role, action = 'assigned'
+WHERE action IS NULL;
+
+ALTER TABLE role_history
+    DROP COLUMN old_role,
+    DROP COLUMN new_role,
+    MODIFY COLUMN role VARCHAR(50) NOT NULL,
+    MODIFY COLUMN action ENUM('assigned', 'removed') NOT NULL;
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+ALTER TABLE sessions DROP FOREIGN KEY sessions_active_organization_fk;
+ALTER TABLE sessions DROP COLUMN active_organization_id;
+
+DROP TABLE IF EXISTS organization_invitations;
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
\ No newline at end of file
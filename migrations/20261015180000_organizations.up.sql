@@ -0,0 +1,45 @@
+-- Add up migration script here
+
+CREATE TABLE organizations (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    created_by CHAR(36) NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    CONSTRAINT organizations_created_by_fk FOREIGN KEY (created_by) REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE TABLE organization_members (
+    organization_id CHAR(36) NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    role ENUM('owner', 'admin', 'member') NOT NULL DEFAULT 'member',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (organization_id, user_id),
+    CONSTRAINT organization_members_organization_fk FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE,
+    CONSTRAINT organization_members_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX organization_members_user_idx ON organization_members (user_id);
+
+CREATE TABLE organization_invitations (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    organization_id CHAR(36) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    role ENUM('owner', 'admin', 'member') NOT NULL DEFAULT 'member',
+    token_hash VARCHAR(255) NOT NULL UNIQUE,
+    invited_by CHAR(36) NULL DEFAULT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    accepted_at TIMESTAMP NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT organization_invitations_organization_fk FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE,
+    CONSTRAINT organization_invitations_invited_by_fk FOREIGN KEY (invited_by) REFERENCES users (id) ON DELETE SET NULL
+);
+
+CREATE INDEX organization_invitations_organization_idx ON organization_invitations (organization_id, email);
+
+-- The organization a session acts in, carried in the `org` claim.
+ALTER TABLE sessions
+    ADD COLUMN active_organization_id CHAR(36) NULL DEFAULT NULL AFTER user_agent,
+    ADD CONSTRAINT sessions_active_organization_fk FOREIGN KEY (active_organization_id) REFERENCES organizations (id) ON DELETE SET NULL;
\ No newline at end of file
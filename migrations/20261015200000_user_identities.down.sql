@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS user_identities;
+ALTER TABLE users DROP COLUMN has_password;
\ No newline at end of file
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+-- Accounts created through a sign-in provider get a random password nobody
+-- knows until they set one through the password reset.
+ALTER TABLE users ADD COLUMN has_password BOOLEAN NOT NULL DEFAULT TRUE AFTER password;
+
+CREATE TABLE user_identities (
+    id CHAR(36) PRIMARY KEY NOT NULL,
+    user_id CHAR(36) NOT NULL,
+    provider VARCHAR(50) NOT NULL,
+    -- The provider's `sub` claim, stable for the lifetime of the account.
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NULL DEFAULT NULL,
+    last_used_at TIMESTAMP NULL DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT user_identities_subject_key UNIQUE (provider, subject),
+    -- One account per provider, so unlinking by provider name is unambiguous.
+    CONSTRAINT user_identities_provider_key UNIQUE (user_id, provider),
+    CONSTRAINT user_identities_user_fk FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
\ No newline at end of file
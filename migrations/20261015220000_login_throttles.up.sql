@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Failed password logins per account (`email:<address>`) and per client
+-- (`ip:<address>`).
+CREATE TABLE login_throttles (
+    throttle_key VARCHAR(320) PRIMARY KEY NOT NULL,
+    failures INT NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Logins for the key are refused until then.
+    blocked_until TIMESTAMP NULL DEFAULT NULL
+);
\ No newline at end of file
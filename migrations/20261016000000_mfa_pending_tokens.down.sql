@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE user_mfa DROP COLUMN pending_token_id;
\ No newline at end of file
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Id of the one `mfa_pending` token that may still be exchanged, it is
+-- cleared by the first attempt to use it.
+ALTER TABLE user_mfa ADD COLUMN pending_token_id CHAR(36) NULL DEFAULT NULL AFTER last_used_step;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::services::api_key_service::ApiKeyWithScopes;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyDto {
+    pub id: String,
+    pub name: String,
+    /// First characters of the key, to tell keys apart.
+    pub prefix: String,
+    /// Permissions the key grants.
+    pub scopes: Vec<String>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<ApiKeyWithScopes> for ApiKeyDto {
+    fn from(value: ApiKeyWithScopes) -> Self {
+        ApiKeyDto {
+            id: value.api_key.id,
+            name: value.api_key.name,
+            prefix: value.api_key.key_prefix,
+            scopes: value.scopes,
+            expires_at: value.api_key.expires_at,
+            last_used_at: value.api_key.last_used_at,
+            created_at: value.api_key.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyListData {
+    #[serde(rename = "apiKeys")]
+    pub api_keys: Vec<ApiKeyDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyListResponseDto {
+    pub status: String,
+    pub data: ApiKeyListData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreatedApiKeyData {
+    #[serde(rename = "apiKey")]
+    pub api_key: ApiKeyDto,
+    /// The full key. It is only ever returned here, store it right away.
+    pub key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreatedApiKeyResponseDto {
+    pub status: String,
+    pub data: CreatedApiKeyData,
+}
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::data_export::{DataExportModel, DataExportStatus};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DataExportDto {
+    pub id: String,
+    pub status: DataExportStatus,
+    /// When the download link emailed for a ready export stops working.
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<DataExportModel> for DataExportDto {
+    fn from(export: DataExportModel) -> Self {
+        DataExportDto {
+            id: export.id,
+            status: export.status,
+            expires_at: export.expires_at,
+            completed_at: export.completed_at,
+            created_at: export.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DataExportResponseDto {
+    pub status: String,
+    pub data: DataExportData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DataExportData {
+    pub export: DataExportDto,
+}
@@ -6,3 +6,23 @@ pub struct Response {
     pub status: &'static str,
     pub message: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginationDto {
+    pub page: u32,
+    pub limit: u32,
+    pub total: i64,
+    #[serde(rename = "totalPages")]
+    pub total_pages: i64,
+}
+
+impl PaginationDto {
+    pub fn new(page: u32, limit: u32, total: i64) -> Self {
+        PaginationDto {
+            page,
+            limit,
+            total,
+            total_pages: (total + limit as i64 - 1) / limit as i64,
+        }
+    }
+}
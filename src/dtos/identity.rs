@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::user_identity::UserIdentityModel;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdentityDto {
+    pub id: String,
+    /// Name of the sign-in provider, e.g. `google`.
+    pub provider: String,
+    pub email: Option<String>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<UserIdentityModel> for IdentityDto {
+    fn from(value: UserIdentityModel) -> Self {
+        IdentityDto {
+            id: value.id,
+            provider: value.provider,
+            email: value.email,
+            last_used_at: value.last_used_at,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdentityData {
+    pub identity: IdentityDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdentityResponseDto {
+    pub status: String,
+    pub data: IdentityData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdentityListData {
+    pub identities: Vec<IdentityDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdentityListResponseDto {
+    pub status: String,
+    pub data: IdentityListData,
+}
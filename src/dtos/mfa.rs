@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MfaEnrollmentResponseDto {
+    pub status: String,
+    pub data: MfaEnrollmentData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MfaEnrollmentData {
+    pub secret: String,
+    #[serde(rename = "otpauthUri")]
+    pub otpauth_uri: String,
+    /// Base64 encoded PNG of `otpauthUri`.
+    #[serde(rename = "qrCode")]
+    pub qr_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodesResponseDto {
+    pub status: String,
+    pub data: RecoveryCodesData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecoveryCodesData {
+    #[serde(rename = "recoveryCodes")]
+    pub recovery_codes: Vec<String>,
+}
+
+/// Returned by login instead of tokens when a second factor is required.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MfaPendingResponseDto {
+    pub status: String,
+    pub data: MfaPendingData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MfaPendingData {
+    #[serde(rename = "mfaToken")]
+    pub mfa_token: String,
+}
@@ -1,2 +1,9 @@
+pub mod api_key;
+pub mod data_export;
 pub mod global;
+pub mod identity;
+pub mod mfa;
+pub mod organization;
+pub mod role;
+pub mod session;
 pub mod user;
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::organization::{
+    OrganizationInvitationModel, OrganizationMemberUserModel, OrganizationModel, OrganizationRole,
+    UserOrganizationModel,
+};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationDto {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    /// Role of the current user in the organization.
+    pub role: OrganizationRole,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl OrganizationDto {
+    pub fn from_model(organization: OrganizationModel, role: OrganizationRole) -> Self {
+        OrganizationDto {
+            id: organization.id,
+            name: organization.name,
+            slug: organization.slug,
+            role,
+            created_at: organization.created_at,
+        }
+    }
+}
+
+impl From<UserOrganizationModel> for OrganizationDto {
+    fn from(organization: UserOrganizationModel) -> Self {
+        OrganizationDto {
+            id: organization.id,
+            name: organization.name,
+            slug: organization.slug,
+            role: organization.role,
+            created_at: organization.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationData {
+    pub organization: OrganizationDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationResponseDto {
+    pub status: String,
+    pub data: OrganizationData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationListData {
+    pub organizations: Vec<OrganizationDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationListResponseDto {
+    pub status: String,
+    pub data: OrganizationListData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SwitchOrganizationData {
+    /// Access token carrying the organization in its `org` claim.
+    pub token: String,
+    pub organization: OrganizationDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SwitchOrganizationResponseDto {
+    pub status: String,
+    pub data: SwitchOrganizationData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberDto {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    pub name: String,
+    pub email: String,
+    pub role: OrganizationRole,
+    #[serde(rename = "joinedAt")]
+    pub joined_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<OrganizationMemberUserModel> for MemberDto {
+    fn from(member: OrganizationMemberUserModel) -> Self {
+        MemberDto {
+            user_id: member.user_id,
+            name: member.name,
+            email: member.email,
+            role: member.role,
+            joined_at: member.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberListData {
+    pub members: Vec<MemberDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberListResponseDto {
+    pub status: String,
+    pub data: MemberListData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationDto {
+    pub id: String,
+    pub email: String,
+    pub role: OrganizationRole,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<OrganizationInvitationModel> for InvitationDto {
+    fn from(invitation: OrganizationInvitationModel) -> Self {
+        InvitationDto {
+            id: invitation.id,
+            email: invitation.email,
+            role: invitation.role,
+            expires_at: invitation.expires_at,
+            created_at: invitation.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationData {
+    pub invitation: InvitationDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationResponseDto {
+    pub status: String,
+    pub data: InvitationData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationListData {
+    pub invitations: Vec<InvitationDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationListResponseDto {
+    pub status: String,
+    pub data: InvitationListData,
+}
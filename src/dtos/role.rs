@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{models::role::PermissionModel, services::role_service::RoleWithPermissions};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoleDto {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Built-in roles cannot be renamed or deleted.
+    pub system: bool,
+    /// Names of the permissions the role grants.
+    pub permissions: Vec<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<RoleWithPermissions> for RoleDto {
+    fn from(value: RoleWithPermissions) -> Self {
+        RoleDto {
+            id: value.role.id,
+            name: value.role.name,
+            description: value.role.description,
+            system: value.role.is_system != 0,
+            permissions: value.permissions,
+            created_at: value.role.created_at,
+            updated_at: value.role.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoleData {
+    pub role: RoleDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoleResponseDto {
+    pub status: String,
+    pub data: RoleData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoleListData {
+    pub roles: Vec<RoleDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoleListResponseDto {
+    pub status: String,
+    pub data: RoleListData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PermissionDto {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl From<PermissionModel> for PermissionDto {
+    fn from(permission: PermissionModel) -> Self {
+        PermissionDto {
+            name: permission.name,
+            description: permission.description,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PermissionListData {
+    pub permissions: Vec<PermissionDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PermissionListResponseDto {
+    pub status: String,
+    pub data: PermissionListData,
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::session::SessionModel;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionDto {
+    pub id: String,
+    #[serde(rename = "deviceName")]
+    pub device_name: Option<String>,
+    #[serde(rename = "ipAddress")]
+    pub ip_address: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    /// Whether this is the session making the request.
+    pub current: bool,
+    #[serde(rename = "lastSeenAt")]
+    pub last_seen_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl SessionDto {
+    pub fn from_model(session: SessionModel, current_session_id: &str) -> Self {
+        SessionDto {
+            current: session.id == current_session_id,
+            id: session.id,
+            device_name: session.device_name,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+            last_seen_at: session.last_seen_at,
+            created_at: session.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionListResponseDto {
+    pub status: String,
+    pub data: SessionListData,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionListData {
+    pub sessions: Vec<SessionDto>,
+}
@@ -1,34 +1,108 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use crate::models::user::{UserModel, UserRole};
+use crate::{
+    dtos::global::PaginationDto,
+    models::user::{UserModel, UserStatus},
+    utils::{
+        avatar,
+        storage::{self, Storage},
+    },
+};
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserDto {
     pub id: String,
     pub name: String,
     pub email: String,
-    pub role: UserRole,
+    /// Names of the roles the user holds.
+    pub roles: Vec<String>,
+    pub status: UserStatus,
+    #[serde(rename = "statusReason")]
+    pub status_reason: Option<String>,
+    #[serde(rename = "statusExpiresAt")]
+    pub status_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "deletionScheduledAt")]
+    pub deletion_scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub locale: String,
+    /// URL of the profile photo. May be signed and expire.
     pub photo: String,
+    /// Thumbnail URLs keyed by edge length in pixels. Empty unless the photo
+    /// was uploaded.
+    #[serde(rename = "photoThumbnails")]
+    pub photo_thumbnails: BTreeMap<u32, String>,
     pub verified: bool,
+    /// Whether the user can sign in with a password. Accounts created
+    /// through a sign-in provider set one through the password reset.
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    #[serde(rename = "mfaEnabled")]
+    pub mfa_enabled: bool,
     #[serde(rename = "createdAt")]
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-impl Into<UserModel> for UserDto {
-    fn into(self) -> UserModel {
+impl UserDto {
+    pub fn from_model(user: UserModel, roles: Vec<String>, storage: &dyn Storage) -> Self {
+        let photo_thumbnails = if avatar::is_avatar_key(&user.photo) {
+            avatar::THUMBNAIL_SIZES
+                .iter()
+                .map(|&size| {
+                    let key = avatar::thumbnail_key(&user.photo, size);
+                    (size, storage::resolve_url(storage, &key))
+                })
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        let status = user.effective_status();
+
+        UserDto {
+            photo: storage::resolve_url(storage, &user.photo),
+            photo_thumbnails,
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            roles,
+            status,
+            status_reason: user.status_reason,
+            status_expires_at: user.status_expires_at,
+            deletion_scheduled_at: user.deletion_scheduled_at,
+            locale: user.locale,
+            verified: user.verified != 0,
+            has_password: user.has_password != 0,
+            mfa_enabled: user.mfa_enabled != 0,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+impl From<UserDto> for UserModel {
+    fn from(user: UserDto) -> Self {
         UserModel {
-            id: self.id,
-            name: self.name,
-            email: self.email,
+            id: user.id,
+            name: user.name,
+            email: user.email,
             password: "".to_string(),
-            role: self.role,
-            photo: self.photo,
-            verified: if self.verified { 1 } else { 0 },
-            created_at: self.created_at,
-            updated_at: self.updated_at,
+            has_password: if user.has_password { 1 } else { 0 },
+            status: user.status,
+            status_reason: user.status_reason,
+            status_changed_by: None,
+            status_expires_at: user.status_expires_at,
+            deletion_requested_at: None,
+            deletion_scheduled_at: user.deletion_scheduled_at,
+            locale: user.locale,
+            photo: user.photo,
+            verified: if user.verified { 1 } else { 0 },
+            mfa_enabled: if user.mfa_enabled { 1 } else { 0 },
+            created_at: user.created_at,
+            updated_at: user.updated_at,
         }
     }
 }
@@ -44,6 +118,18 @@ pub struct UserData {
     pub user: UserDto,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserListResponseDto {
+    pub status: String,
+    pub data: UserListData,
+    pub pagination: PaginationDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserListData {
+    pub users: Vec<UserDto>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserLoginResponseDto {
     pub status: String,
@@ -53,4 +139,6 @@ pub struct UserLoginResponseDto {
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TokenData {
     pub token: String,
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
 }
@@ -0,0 +1,443 @@
+use actix_web::{web, HttpResponse};
+use validator::Validate;
+
+use crate::{
+    dtos::{
+        global::{PaginationDto, Response},
+        role::{
+            PermissionDto, PermissionListData, PermissionListResponseDto, RoleData, RoleDto,
+            RoleListData, RoleListResponseDto, RoleResponseDto,
+        },
+        user::{UserData, UserDto, UserListData, UserListResponseDto, UserResponseDto},
+    },
+    models::user::UserModel,
+    schemas::admin::{
+        AdminUpdateUserSchema, CreateRoleSchema, SetUserRolesSchema, SetUserStatusSchema,
+        UpdateRoleSchema, UserListQuery,
+    },
+    services::{
+        admin_service::AdminService,
+        role_service::{RoleService, RoleWithPermissions},
+    },
+    utils::{
+        error::AppError,
+        extractor::{AuthClaims, Authenticated},
+        scope,
+        validated_json::ValidatedJson,
+    },
+    AppState,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    tag = "Admin Endpoint",
+    params(UserListQuery),
+    responses(
+        (status=200, description= "One page of users matching the filters", body= UserListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_users_handler(
+    query: web::Query<UserListQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    query.validate()?;
+
+    let (users, total) = AdminService::new(data.db.clone())
+        .list_users(&query)
+        .await?;
+
+    let user_ids: Vec<String> = users.iter().map(|user| user.id.clone()).collect();
+    let mut roles = RoleService::new(data.db.clone())
+        .get_roles_of_users(&user_ids)
+        .await?;
+
+    let response_data = UserListResponseDto {
+        status: "success".to_string(),
+        data: UserListData {
+            users: users
+                .into_iter()
+                .map(|user| {
+                    let user_roles = roles.remove(&user.id).unwrap_or_default();
+                    UserDto::from_model(user, user_roles, data.storage.as_ref())
+                })
+                .collect(),
+        },
+        pagination: PaginationDto::new(query.page(), query.limit(), total),
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the user"),
+    ),
+    responses(
+        (status=200, description= "The user", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission", body= ErrorResponse),
+        (status=404, description= "User not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn get_user_handler(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user = AdminService::new(data.db.clone())
+        .get_user(&path.into_inner())
+        .await?;
+
+    user_response(user, &data).await
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/admin/users/{id}",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the user"),
+    ),
+    request_body(content = AdminUpdateUserSchema, description = "Fields to change, omitted fields are kept", example = json!({"name": "John Doe","email": "john@mail.com","verified": true})),
+    responses(
+        (status=200, description= "User updated successfully", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission", body= ErrorResponse),
+        (status=404, description= "User not found", body= ErrorResponse),
+        (status=409, description= "User with email already exists", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn update_user_handler(
+    claims: AuthClaims,
+    path: web::Path<String>,
+    body: ValidatedJson<AdminUpdateUserSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::USERS_WRITE)?;
+
+    let user = AdminService::new(data.db.clone())
+        .update_user(&path.into_inner(), &body)
+        .await?;
+
+    user_response(user, &data).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{id}/roles",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the user"),
+    ),
+    request_body(content = SetUserRolesSchema, description = "Every role the user should hold", example = json!({"roles": ["user", "moderator"]})),
+    responses(
+        (status=200, description= "Roles replaced successfully", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission, the admin's own account, or a role with permissions the admin lacks", body= ErrorResponse),
+        (status=404, description= "User not found", body= ErrorResponse),
+        (status=422, description= "Unknown role", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn set_roles_handler(
+    admin: Authenticated,
+    claims: AuthClaims,
+    path: web::Path<String>,
+    body: ValidatedJson<SetUserRolesSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::USERS_WRITE)?;
+
+    let user = AdminService::new(data.db.clone())
+        .set_roles(&admin, &claims.scope, &path.into_inner(), &body.roles)
+        .await?;
+
+    user_response(user, &data).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{id}/status",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the user"),
+    ),
+    request_body(content = SetUserStatusSchema, description = "Suspension or ban to apply", example = json!({"status": "suspended","reason": "Spamming other users","expiresAt": "2026-11-01T00:00:00Z"})),
+    responses(
+        (status=200, description= "Status applied and the user signed out everywhere", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission, or the admin's own account", body= ErrorResponse),
+        (status=404, description= "User not found", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn set_status_handler(
+    admin: Authenticated,
+    claims: AuthClaims,
+    path: web::Path<String>,
+    body: ValidatedJson<SetUserStatusSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::USERS_WRITE)?;
+
+    let user = AdminService::new(data.db.clone())
+        .set_status(&admin, &path.into_inner(), &body)
+        .await?;
+
+    user_response(user, &data).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{id}/status",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the user"),
+    ),
+    responses(
+        (status=200, description= "Account reactivated", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission", body= ErrorResponse),
+        (status=404, description= "User not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn clear_status_handler(
+    claims: AuthClaims,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::USERS_WRITE)?;
+
+    let user = AdminService::new(data.db.clone())
+        .clear_status(&path.into_inner())
+        .await?;
+
+    user_response(user, &data).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{id}/lockout",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the user"),
+    ),
+    responses(
+        (status=200, description= "Failed logins forgotten and the account unlocked", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the users:read or users:write permission", body= ErrorResponse),
+        (status=404, description= "User not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn unlock_user_handler(
+    claims: AuthClaims,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::USERS_WRITE)?;
+
+    let user = AdminService::new(data.db.clone())
+        .unlock(&path.into_inner())
+        .await?;
+
+    user_response(user, &data).await
+}
+
+async fn user_response(user: UserModel, data: &AppState) -> Result<HttpResponse, AppError> {
+    let roles = RoleService::new(data.db.clone())
+        .get_user_roles(&user.id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(UserResponseDto {
+        status: "success".to_string(),
+        data: UserData {
+            user: UserDto::from_model(user, roles, data.storage.as_ref()),
+        },
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/roles",
+    tag = "Admin Endpoint",
+    responses(
+        (status=200, description= "Every role with its permissions", body= RoleListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the roles:read permission", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_roles_handler(data: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let roles = RoleService::new(data.db.clone()).list_roles().await?;
+
+    Ok(HttpResponse::Ok().json(RoleListResponseDto {
+        status: "success".to_string(),
+        data: RoleListData {
+            roles: roles.into_iter().map(RoleDto::from).collect(),
+        },
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/roles/{id}",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the role"),
+    ),
+    responses(
+        (status=200, description= "The role", body= RoleResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the roles:read permission", body= ErrorResponse),
+        (status=404, description= "Role not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn get_role_handler(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let role = RoleService::new(data.db.clone())
+        .get_role(&path.into_inner())
+        .await?;
+
+    Ok(role_response(role))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/roles",
+    tag = "Admin Endpoint",
+    request_body(content = CreateRoleSchema, description = "Name and permissions of the new role", example = json!({"name": "support","description": "Looks up accounts for customer support","permissions": ["users:read"]})),
+    responses(
+        (status=201, description= "Role created successfully", body= RoleResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the roles:read or roles:write permission, or a permission the caller lacks", body= ErrorResponse),
+        (status=409, description= "A role with this name already exists", body= ErrorResponse),
+        (status=422, description= "Validation Errors or unknown permission", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn create_role_handler(
+    claims: AuthClaims,
+    body: ValidatedJson<CreateRoleSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::ROLES_WRITE)?;
+
+    let role = RoleService::new(data.db.clone())
+        .create_role(&body, &claims.scope)
+        .await?;
+
+    Ok(HttpResponse::Created().json(RoleResponseDto {
+        status: "success".to_string(),
+        data: RoleData { role: role.into() },
+    }))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/admin/roles/{id}",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the role"),
+    ),
+    request_body(content = UpdateRoleSchema, description = "Fields to change, permissions replace the current ones", example = json!({"permissions": ["users:read", "users:write"]})),
+    responses(
+        (status=200, description= "Role updated successfully", body= RoleResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the roles:read or roles:write permission, renaming a built-in role, or adding a permission the caller lacks", body= ErrorResponse),
+        (status=404, description= "Role not found", body= ErrorResponse),
+        (status=409, description= "A role with this name already exists", body= ErrorResponse),
+        (status=422, description= "Validation Errors or unknown permission", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn update_role_handler(
+    claims: AuthClaims,
+    path: web::Path<String>,
+    body: ValidatedJson<UpdateRoleSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::ROLES_WRITE)?;
+
+    let role = RoleService::new(data.db.clone())
+        .update_role(&path.into_inner(), &body, &claims.scope)
+        .await?;
+
+    Ok(role_response(role))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/roles/{id}",
+    tag = "Admin Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the role"),
+    ),
+    responses(
+        (status=200, description= "Role deleted and removed from every user", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the roles:read or roles:write permission, or a built-in role", body= ErrorResponse),
+        (status=404, description= "Role not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn delete_role_handler(
+    claims: AuthClaims,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    claims.require_scope(scope::ROLES_WRITE)?;
+
+    RoleService::new(data.db.clone())
+        .delete_role(&path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Role deleted successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/permissions",
+    tag = "Admin Endpoint",
+    responses(
+        (status=200, description= "Every permission a role can grant", body= PermissionListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Missing the roles:read permission", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_permissions_handler(data: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let permissions = RoleService::new(data.db.clone()).list_permissions().await?;
+
+    Ok(HttpResponse::Ok().json(PermissionListResponseDto {
+        status: "success".to_string(),
+        data: PermissionListData {
+            permissions: permissions.into_iter().map(PermissionDto::from).collect(),
+        },
+    }))
+}
+
+fn role_response(role: RoleWithPermissions) -> HttpResponse {
+    HttpResponse::Ok().json(RoleResponseDto {
+        status: "success".to_string(),
+        data: RoleData { role: role.into() },
+    })
+}
@@ -0,0 +1,105 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+    dtos::{
+        api_key::{
+            ApiKeyDto, ApiKeyListData, ApiKeyListResponseDto, CreatedApiKeyData,
+            CreatedApiKeyResponseDto,
+        },
+        global::Response,
+    },
+    schemas::user::CreateApiKeySchema,
+    services::api_key_service::ApiKeyService,
+    utils::{error::AppError, extractor::Authenticated, validated_json::ValidatedJson},
+    AppState,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/api-keys",
+    tag = "API Key Endpoint",
+    responses(
+        (status=200, description= "API keys of the authenticated user", body= ApiKeyListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Called with an API key", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_api_keys_handler(
+    user: Authenticated,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let api_keys = ApiKeyService::new(data.db.clone())
+        .list_api_keys(&user.id)
+        .await?;
+
+    let response_data = ApiKeyListResponseDto {
+        status: "success".to_string(),
+        data: ApiKeyListData {
+            api_keys: api_keys.into_iter().map(ApiKeyDto::from).collect(),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/api-keys",
+    tag = "API Key Endpoint",
+    request_body(content = CreateApiKeySchema, description = "Name, scopes and optional expiry of the key", example = json!({"name": "CI deploys","scopes": ["profile:read"],"expiresAt": "2027-01-01T00:00:00Z"})),
+    responses(
+        (status=201, description= "API key created, the key is only shown in this response", body= CreatedApiKeyResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Called with an API key or with a scope the user does not have", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn create_api_key_handler(
+    user: Authenticated,
+    body: ValidatedJson<CreateApiKeySchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (api_key, key) = ApiKeyService::new(data.db.clone())
+        .create_api_key(&user.id, &body)
+        .await?;
+
+    Ok(HttpResponse::Created().json(CreatedApiKeyResponseDto {
+        status: "success".to_string(),
+        data: CreatedApiKeyData {
+            api_key: ApiKeyDto::from(api_key),
+            key,
+        },
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/api-keys/{id}",
+    tag = "API Key Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the API key to revoke"),
+    ),
+    responses(
+        (status=200, description= "API key revoked successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Called with an API key", body= ErrorResponse),
+        (status=404, description= "API key not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn delete_api_key_handler(
+    user: Authenticated,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    ApiKeyService::new(data.db.clone())
+        .delete_api_key(&user.id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "API key revoked successfully".to_string(),
+    }))
+}
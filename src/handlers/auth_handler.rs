@@ -1,21 +1,146 @@
 use actix_web::{
-    cookie::time::Duration as ActixWebDuration, cookie::Cookie, web, HttpResponse, Responder,
+    cookie::time::Duration as ActixWebDuration, cookie::Cookie, http::StatusCode, web, HttpRequest,
+    HttpResponse,
 };
-use serde_json::json;
-use validator::Validate;
 
 use crate::{
     dtos::{
         global::Response,
-        user::{UserData, UserLoginResponseDto, UserResponseDto},
+        mfa::{MfaPendingData, MfaPendingResponseDto},
+        user::{TokenData, UserData, UserDto, UserLoginResponseDto, UserResponseDto},
     },
     models::user::UserModel,
-    schemas::auth::{LoginUserSchema, RegisterUserSchema},
-    services::{auth_service::AuthService, user_services::UserService},
-    utils::{password, token},
+    schemas::auth::{
+        ForgotPasswordSchema, LoginUserSchema, MagicLinkSchema, OidcLoginSchema,
+        RefreshTokenSchema, RegisterUserSchema, ResendVerificationSchema, ResetPasswordSchema,
+        VerifyEmailSchema, VerifyMagicLinkSchema, VerifyMfaSchema,
+    },
+    services::{
+        auth_service::AuthService,
+        identity_service::IdentityService,
+        login_throttle_service::{self, LoginThrottleService},
+        mail_service::MailService,
+        mfa_service::MfaService,
+        role_service::RoleService,
+        session_service::DeviceInfo,
+        token_service::{TokenPair, TokenService},
+        user_services::UserService,
+    },
+    utils::{
+        client_ip::client_ip,
+        config::Config,
+        error::AppError,
+        mailer, password,
+        token::{self, TokenPurpose},
+        validated_json::ValidatedJson,
+    },
     AppState,
 };
 
+const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+/// Minutes a user has to submit the second factor after the password.
+const MFA_PENDING_MAXAGE: i64 = 5;
+
+fn token_response(status: StatusCode, tokens: TokenPair, config: &Config) -> HttpResponse {
+    let TokenPair {
+        access_token: token,
+        refresh_token,
+    } = tokens;
+
+    let cookie = Cookie::build("token", token.to_owned())
+        .path("/")
+        .max_age(ActixWebDuration::new(60 * config.jwt_maxage, 0))
+        .http_only(true)
+        .finish();
+
+    // Only the auth scope ever needs to see the refresh token.
+    let refresh_cookie = Cookie::build(REFRESH_TOKEN_COOKIE, refresh_token.to_owned())
+        .path("/api/auth")
+        .max_age(ActixWebDuration::new(60 * config.refresh_token_maxage, 0))
+        .http_only(true)
+        .finish();
+
+    let token_response = UserLoginResponseDto {
+        status: "success".to_string(),
+        data: TokenData {
+            token,
+            refresh_token,
+        },
+    };
+
+    HttpResponse::build(status)
+        .cookie(cookie)
+        .cookie(refresh_cookie)
+        .json(token_response)
+}
+
+/// Finishes a sign-in whose first factor has been checked: asks for the
+/// second factor when the user has one, otherwise starts a session.
+async fn complete_login(
+    req: &HttpRequest,
+    user: &UserModel,
+    device_name: Option<String>,
+    data: &AppState,
+) -> Result<HttpResponse, AppError> {
+    UserService::ensure_active(user)?;
+
+    if user.mfa_enabled != 0 {
+        let token_id = MfaService::new(data.db.clone())
+            .start_challenge(&user.id)
+            .await?;
+        let mfa_token = token::create_purpose_token(
+            &user.id,
+            TokenPurpose::MfaPending,
+            Some(&token_id),
+            &data.jwt_keys,
+            MFA_PENDING_MAXAGE,
+        )
+        .map_err(AppError::internal)?;
+
+        return Ok(HttpResponse::Ok().json(MfaPendingResponseDto {
+            status: "mfa_pending".to_string(),
+            data: MfaPendingData { mfa_token },
+        }));
+    }
+
+    let device = DeviceInfo::from_request(req, device_name, &data.config);
+
+    let tokens = TokenService::new(data.db.clone())
+        .start_session(user, &device, &data.config, &data.jwt_keys)
+        .await?;
+
+    Ok(token_response(StatusCode::CREATED, tokens, &data.config))
+}
+
+/// Cookies that make the browser drop the access and refresh tokens.
+pub fn expired_auth_cookies() -> [Cookie<'static>; 2] {
+    let cookie = Cookie::build("token", "")
+        .path("/")
+        .max_age(ActixWebDuration::new(-1, 0))
+        .http_only(true)
+        .finish();
+
+    let refresh_cookie = Cookie::build(REFRESH_TOKEN_COOKIE, "")
+        .path("/api/auth")
+        .max_age(ActixWebDuration::new(-1, 0))
+        .http_only(true)
+        .finish();
+
+    [cookie, refresh_cookie]
+}
+
+fn refresh_token_from_request(
+    req: &HttpRequest,
+    body: Option<web::Json<RefreshTokenSchema>>,
+) -> Option<String> {
+    body.map(|body| body.into_inner().refresh_token)
+        .filter(|token| !token.is_empty())
+        .or_else(|| {
+            req.cookie(REFRESH_TOKEN_COOKIE)
+                .map(|c| c.value().to_string())
+        })
+}
+
 #[utoipa::path(
     post,
     path = "/api/auth/register",
@@ -23,51 +148,61 @@ use crate::{
     request_body(content = RegisterUserSchema, description = "Credentials to create account", example = json!({"email": "user1@mail.com","name": "User Name","password": "password123","passwordConfirm": "password123"})),
     responses(
         (status=201, description= "Account created successfully", body= UserResponseDto ),
-        (status=400, description= "Validation Errors", body= Response),
-        (status=409, description= "User with email already exists", body= Response),
-        (status=500, description= "Internal Server Error", body= Response ),
+        (status=409, description= "User with email already exists", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
     )
 )]
 pub async fn register_user_handler(
-    body: web::Json<RegisterUserSchema>,
+    req: HttpRequest,
+    body: ValidatedJson<RegisterUserSchema>,
     data: web::Data<AppState>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let auth_service = AuthService::new(data.db.clone());
 
     let user_id = uuid::Uuid::new_v4().to_string();
+    let locale: String = mailer::locale_from_request(&req)
+        .unwrap_or(data.mail_templates.default_locale().to_string())
+        .chars()
+        .take(16)
+        .collect();
 
-    if let Err(err) = auth_service.create_user(&user_id, body).await {
-        if err.contains("Duplicate entry") {
-            return HttpResponse::BadRequest().json(json!({
-                "status": "fail",
-                "message": "User with that email already exists"
-            }));
-        }
+    auth_service.create_user(&user_id, &body, &locale).await?;
 
-        return HttpResponse::InternalServerError().json(json!({
-            "status":"error",
-            "message": format!("{:?}", err)
-        }));
+    let user = UserService::new(data.db.clone())
+        .get_user(Some(&user_id), None, None)
+        .await?
+        .ok_or(AppError::internal(
+            "User missing right after registering it",
+        ))?;
+
+    // The account exists at this point; a failed email can be retried
+    // through the resend endpoint, so it must not fail the signup.
+    if let Err(e) = auth_service
+        .send_verification_email(
+            &user,
+            &data.config,
+            &data.jwt_keys,
+            &MailService::new(data.mailer.clone(), data.mail_templates.clone()),
+        )
+        .await
+    {
+        eprintln!("🔥 Failed to send verification email: {}", e);
     }
 
-    let user_service = UserService::new(data.db.clone());
+    let roles = RoleService::new(data.db.clone())
+        .get_user_roles(&user.id)
+        .await?;
 
-    match user_service.get_user(Some(&user_id), None, None).await {
-        Ok(user) => {
-            let user_response = UserResponseDto {
-                status: "success".to_string(),
-                data: UserData {
-                    user: UserModel::into(user.unwrap()),
-                },
-            };
+    let user_response = UserResponseDto {
+        status: "success".to_string(),
+        data: UserData {
+            user: UserDto::from_model(user, roles, data.storage.as_ref()),
+        },
+    };
 
-            HttpResponse::Ok().json(user_response)
-        }
-        Err(e) => HttpResponse::InternalServerError().json(json!({
-            "status": "error",
-            "message": format!("{:?}", e)
-        })),
-    }
+    Ok(HttpResponse::Ok().json(user_response))
 }
 
 #[utoipa::path(
@@ -77,107 +212,449 @@ pub async fn register_user_handler(
     request_body(content = LoginUserSchema, description = "Credentials to login", example = json!({"email": "user1@mail.com","password": "password123"})),
     responses(
         (status=201, description= "Account created successfully", body= UserLoginResponseDto ),
-        (status=400, description= "Validation Errors", body= Response),
-        (status=500, description= "User not found!", body= Response),
-        (status=401, description= "Email or password is wrong", body= Response),
+        (status=200, description= "Password accepted, submit the second factor to /api/auth/mfa/verify", body= MfaPendingResponseDto ),
+        (status=401, description= "Email or password is wrong, also while the account or client is throttled", body= ErrorResponse),
+        (status=403, description= "Account is suspended or banned", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
     )
 )]
 pub async fn login_user_handler(
+    req: HttpRequest,
     data: web::Data<AppState>,
-    body: web::Json<LoginUserSchema>,
-) -> impl Responder {
-    let _ = body.validate().map_err(|e| {
-        return HttpResponse::BadRequest().json(json!({
-            "status":"fail",
-            "message": e.to_string(),
-        }));
-    });
+    body: ValidatedJson<LoginUserSchema>,
+) -> Result<HttpResponse, AppError> {
+    let throttle_service = LoginThrottleService::new(data.db.clone());
+    let email_key = login_throttle_service::email_key(&body.email);
+    let ip_key = client_ip(&req, &data.config.trusted_proxies)
+        .map(|ip_address| login_throttle_service::ip_key(&ip_address.to_string()));
+
+    let throttle_keys: Vec<String> = [Some(email_key.clone()), ip_key.clone()]
+        .into_iter()
+        .flatten()
+        .collect();
+    let blocked = throttle_service.is_blocked(&throttle_keys).await?;
+
+    let user = UserService::new(data.db.clone())
+        .get_user(None, None, Some(&body.email))
+        .await?;
 
-    let user_service = UserService::new(data.db.clone());
-
-    match user_service.get_user(None, None, Some(&body.email)).await {
-        Ok(result) => {
-            let user = match result {
-                Some(user) => user,
-                None => {
-                    return HttpResponse::InternalServerError().json(json!({
-                        "status":"fail",
-                        "message":"User not found!",
-                    }))
-                }
-            };
-
-            let password_matches = password::compare(&body.password, &user.password)
-                .map_err(|_| {
-                    HttpResponse::Unauthorized().json(json!({
-                        "status":"fail",
-                        "message":"Email or password is wrong",
-                    }))
-                })
-                .unwrap();
-
-            if password_matches {
-                let token = token::create_token(
-                    &user.id.to_string(),
-                    data.config.jwt_secret.as_bytes(),
-                    data.config.jwt_maxage,
-                )
-                .map_err(|e| {
-                    HttpResponse::InternalServerError().json(json!({
-                        "status":"fail",
-                        "message": e.to_string(),
-                    }))
-                })
-                .unwrap();
-
-                let cookie = Cookie::build("token", token.to_owned())
-                    .path("/")
-                    .max_age(ActixWebDuration::new(60 * &data.config.jwt_maxage, 0))
-                    .http_only(true)
-                    .finish();
-
-                let token_response = UserLoginResponseDto {
-                    status: "success".to_string(),
-                    data: crate::dtos::user::TokenData { token },
-                };
-
-                return HttpResponse::Created()
-                    .cookie(cookie)
-                    .json(json!(token_response));
-            } else {
-                return HttpResponse::InternalServerError().json(json!({
-                    "status": "error",
-                    "message": "Email or password is wrong"
-                }));
+    // The hash is checked even for unknown and throttled accounts, so that
+    // neither answers faster than a wrong password.
+    let hashed_password = user
+        .as_ref()
+        .map_or(password::dummy_hash(), |user| user.password.as_str());
+    let password_matches = password::compare(&body.password, hashed_password).unwrap_or(false);
+
+    let user = match user {
+        Some(user) if password_matches && !blocked => user,
+        _ => {
+            // Attempts while blocked are refused unseen, so they neither
+            // reveal the password nor extend the block.
+            if !blocked {
+                throttle_service
+                    .record_failure(&email_key, ip_key.as_deref(), &data.config)
+                    .await?;
             }
+
+            return Err(AppError::WrongCredentials);
         }
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(json!({
-                "status":"fail",
-                "message": e.to_string(),
-            }))
+    };
+
+    throttle_service.clear(&email_key).await?;
+
+    complete_login(&req, &user, body.device_name.clone(), &data).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/oidc/{provider}",
+    tag = "Login Account Endpoint",
+    params(
+        ("provider" = String, Path, description = "Name of the sign-in provider, e.g. google or apple"),
+    ),
+    request_body(content = OidcLoginSchema, description = "ID token the app got from the provider", example = json!({"idToken": "eyJhbGciOiJSUzI1NiIsImtpZCI6IjEifQ...","nonce": "n-0S6_WzA2Mj"})),
+    responses(
+        (status=201, description= "Signed in, the account is created or linked by email on first use", body= UserLoginResponseDto ),
+        (status=200, description= "ID token accepted, submit the second factor to /api/auth/mfa/verify", body= MfaPendingResponseDto ),
+        (status=401, description= "ID token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Account is suspended or banned", body= ErrorResponse),
+        (status=404, description= "Sign-in provider not found", body= ErrorResponse),
+        (status=409, description= "An unverified account uses the email", body= ErrorResponse),
+        (status=422, description= "Validation Errors or the provider did not verify the email", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn oidc_login_handler(
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: ValidatedJson<OidcLoginSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let provider = path.into_inner();
+    let claims = data
+        .oidc
+        .verify(&provider, &body.id_token, body.nonce.as_deref())
+        .await?;
+
+    let locale: String = mailer::locale_from_request(&req)
+        .unwrap_or(data.mail_templates.default_locale().to_string())
+        .chars()
+        .take(16)
+        .collect();
+
+    let user = IdentityService::new(data.db.clone())
+        .sign_in(&provider, &claims, &locale)
+        .await?;
+
+    complete_login(&req, &user, body.device_name.clone(), &data).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/magic-link",
+    tag = "Login Account Endpoint",
+    request_body(content = MagicLinkSchema, description = "Email address to send the sign-in link and code to", example = json!({"email": "user1@mail.com"})),
+    responses(
+        (status=200, description= "Sign-in email sent if the account exists", body= Response ),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+    )
+)]
+pub async fn request_magic_link_handler(
+    body: ValidatedJson<MagicLinkSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let pool = data.db.clone();
+    let config = data.config.clone();
+    let mail_service = MailService::new(data.mailer.clone(), data.mail_templates.clone());
+    let email = body.into_inner().email;
+
+    // Handled off the request for the same reason as password resets.
+    actix_web::rt::spawn(async move {
+        if let Err(e) = AuthService::new(pool)
+            .request_magic_link(&email, &config, &mail_service)
+            .await
+        {
+            eprintln!("🔥 Failed to process sign-in link request: {}", e);
+        }
+    });
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "If an account with that email exists, a sign-in link and code have been sent"
+            .to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/magic-link/verify",
+    tag = "Login Account Endpoint",
+    request_body(content = VerifyMagicLinkSchema, description = "Token from the emailed link, or the email and the emailed code", example = json!({"email": "user1@mail.com","code": "123456"})),
+    responses(
+        (status=201, description= "Signed in successfully", body= UserLoginResponseDto ),
+        (status=200, description= "Link or code accepted, submit the second factor to /api/auth/mfa/verify", body= MfaPendingResponseDto ),
+        (status=401, description= "Link or code is invalid, used, expired or out of attempts", body= ErrorResponse),
+        (status=403, description= "Account is suspended or banned", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn verify_magic_link_handler(
+    req: HttpRequest,
+    body: ValidatedJson<VerifyMagicLinkSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let auth_service = AuthService::new(data.db.clone());
+
+    let user = match (&body.token, &body.email, &body.code) {
+        (Some(link_token), _, _) => auth_service.verify_magic_link(link_token).await?,
+        (None, Some(email), Some(code)) => {
+            // Every email comes with fresh attempts, so wrong codes also
+            // count against the account like wrong passwords do.
+            let throttle_service = LoginThrottleService::new(data.db.clone());
+            let email_key = login_throttle_service::email_key(email);
+            if throttle_service
+                .is_blocked(std::slice::from_ref(&email_key))
+                .await?
+            {
+                return Err(AppError::InvalidMagicLink);
+            }
+
+            let user = auth_service.verify_login_code(email, code).await;
+            if let Err(AppError::InvalidMagicLink) = user {
+                throttle_service
+                    .record_failure(&email_key, None, &data.config)
+                    .await?;
+            }
+            let user = user?;
+
+            throttle_service.clear(&email_key).await?;
+            user
         }
+        _ => return Err(AppError::MagicLinkNotProvided),
+    };
+
+    complete_login(&req, &user, body.device_name.clone(), &data).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/mfa/verify",
+    tag = "Login Account Endpoint",
+    request_body(content = VerifyMfaSchema, description = "Token from the login response and either a TOTP or a recovery code", example = json!({"mfaToken": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9...","code": "123456"})),
+    responses(
+        (status=201, description= "Second factor accepted", body= UserLoginResponseDto ),
+        (status=401, description= "MFA token or code is invalid, the token is burnt either way", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests or too many wrong codes for the account", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn verify_mfa_handler(
+    req: HttpRequest,
+    body: ValidatedJson<VerifyMfaSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let claims =
+        token::decode_purpose_token(&body.mfa_token, TokenPurpose::MfaPending, &data.jwt_keys)?;
+    let token_id = claims.jti.ok_or(AppError::InvalidToken)?;
+
+    let user = UserService::new(data.db.clone())
+        .get_user(Some(&claims.sub), None, None)
+        .await?
+        .ok_or(AppError::UserNoLongerExist)?;
+
+    // Wrong codes count against the account, not the token, so signing in
+    // again for a fresh token does not buy more guesses.
+    let throttle_service = LoginThrottleService::new(data.db.clone());
+    let mfa_key = login_throttle_service::mfa_key(&user.id);
+    if throttle_service
+        .is_blocked(std::slice::from_ref(&mfa_key))
+        .await?
+    {
+        return Err(AppError::TooManyRequests);
     }
+
+    let verified = MfaService::new(data.db.clone())
+        .verify(
+            &user,
+            &token_id,
+            body.code.as_deref(),
+            body.recovery_code.as_deref(),
+            &data.config.mfa_issuer,
+        )
+        .await;
+
+    if let Err(AppError::InvalidMfaCode) = verified {
+        throttle_service
+            .record_failure(&mfa_key, None, &data.config)
+            .await?;
+    }
+    verified?;
+
+    throttle_service.clear(&mfa_key).await?;
+
+    let device = DeviceInfo::from_request(&req, body.device_name.clone(), &data.config);
+    let tokens = TokenService::new(data.db.clone())
+        .start_session(&user, &device, &data.config, &data.jwt_keys)
+        .await?;
+
+    Ok(token_response(StatusCode::CREATED, tokens, &data.config))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "Refresh Token Endpoint",
+    request_body(content = RefreshTokenSchema, description = "Refresh token to rotate, falls back to the refresh_token cookie", example = json!({"refreshToken": "5f2b...e91c"})),
+    responses(
+        (status=200, description= "Tokens rotated successfully", body= UserLoginResponseDto ),
+        (status=401, description= "Refresh token is missing, invalid, expired or reused", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn refresh_token_handler(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: Option<web::Json<RefreshTokenSchema>>,
+) -> Result<HttpResponse, AppError> {
+    let raw_token =
+        refresh_token_from_request(&req, body).ok_or(AppError::RefreshTokenNotProvided)?;
+
+    let tokens = TokenService::new(data.db.clone())
+        .refresh_session(&raw_token, &data.config, &data.jwt_keys)
+        .await?;
+
+    Ok(token_response(StatusCode::OK, tokens, &data.config))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/verify-email",
+    tag = "Email Verification Endpoint",
+    request_body(content = VerifyEmailSchema, description = "Token from the verification email", example = json!({"token": "eyJ0eXAiOiJKV1QiLCJhbGciOiJIUzI1NiJ9..."})),
+    responses(
+        (status=200, description= "Email verified successfully", body= Response ),
+        (status=400, description= "Verification token is invalid or expired", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn verify_email_handler(
+    body: ValidatedJson<VerifyEmailSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    AuthService::new(data.db.clone())
+        .verify_email(&body.token, &data.jwt_keys)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Email verified successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/resend-verification",
+    tag = "Email Verification Endpoint",
+    request_body(content = ResendVerificationSchema, description = "Email address of the account to verify", example = json!({"email": "user1@mail.com"})),
+    responses(
+        (status=200, description= "Verification email sent if the account exists and is unverified", body= Response ),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn resend_verification_handler(
+    body: ValidatedJson<ResendVerificationSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let user = UserService::new(data.db.clone())
+        .get_user(None, None, Some(&body.email))
+        .await?;
+
+    if let Some(user) = user.filter(|user| user.verified == 0) {
+        AuthService::new(data.db.clone())
+            .send_verification_email(
+                &user,
+                &data.config,
+                &data.jwt_keys,
+                &MailService::new(data.mailer.clone(), data.mail_templates.clone()),
+            )
+            .await?;
+    }
+
+    // Same answer either way so the endpoint cannot be used to probe emails.
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message:
+            "If the account exists and is not verified yet, a verification email has been sent"
+                .to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/forgot-password",
+    tag = "Password Reset Endpoint",
+    request_body(content = ForgotPasswordSchema, description = "Email address of the account to recover", example = json!({"email": "user1@mail.com"})),
+    responses(
+        (status=200, description= "Reset email sent if the account exists", body= Response ),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+    )
+)]
+pub async fn forgot_password_handler(
+    body: ValidatedJson<ForgotPasswordSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let pool = data.db.clone();
+    let config = data.config.clone();
+    let mail_service = MailService::new(data.mailer.clone(), data.mail_templates.clone());
+    let email = body.into_inner().email;
+
+    // Handled off the request so known and unknown addresses answer equally
+    // fast, not only with the same body.
+    actix_web::rt::spawn(async move {
+        if let Err(e) = AuthService::new(pool)
+            .request_password_reset(&email, &config, &mail_service)
+            .await
+        {
+            eprintln!("🔥 Failed to process password reset request: {}", e);
+        }
+    });
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "If an account with that email exists, a password reset link has been sent"
+            .to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/reset-password",
+    tag = "Password Reset Endpoint",
+    request_body(content = ResetPasswordSchema, description = "Reset token and the new password", example = json!({"token": "5f2b...e91c","password": "newpassword123","passwordConfirm": "newpassword123"})),
+    responses(
+        (status=200, description= "Password reset successfully", body= Response ),
+        (status=400, description= "Reset token is invalid or expired", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn reset_password_handler(
+    body: ValidatedJson<ResetPasswordSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    AuthService::new(data.db.clone())
+        .reset_password(&body.token, &body.password)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Password reset successfully, please log in again".to_string(),
+    }))
 }
 
 #[utoipa::path(
     post,
     path = "/api/auth/logout",
     tag = "Logout Account Endpoint",
-    request_body(content = (), description = "Credentials to logout"),
+    request_body(content = RefreshTokenSchema, description = "Refresh token of the session to end, falls back to the refresh_token cookie", example = json!({"refreshToken": "5f2b...e91c"})),
     responses(
         (status=200, description= "Account logout successfully", body= Response ),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
     )
 )]
-pub async fn logout_user_handler() -> impl Responder {
-    let cookie = Cookie::build("token", "")
-        .path("/")
-        .max_age(ActixWebDuration::new(-1, 0))
-        .http_only(true)
-        .finish();
+pub async fn logout_user_handler(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: Option<web::Json<RefreshTokenSchema>>,
+) -> Result<HttpResponse, AppError> {
+    // Goes by the refresh token rather than the access token, which has
+    // usually expired by the time a client logs out. Revoking its family
+    // also revokes the session.
+    if let Some(raw_token) = refresh_token_from_request(&req, body) {
+        TokenService::new(data.db.clone())
+            .revoke_refresh_token(&raw_token)
+            .await?;
+    }
 
-    HttpResponse::Ok().cookie(cookie).json(Response {
-        status: "success",
-        message: "Account logout successfully".to_string(),
-    })
+    let [cookie, refresh_cookie] = expired_auth_cookies();
+
+    Ok(HttpResponse::Ok()
+        .cookie(cookie)
+        .cookie(refresh_cookie)
+        .json(Response {
+            status: "success",
+            message: "Account logout successfully".to_string(),
+        }))
 }
@@ -0,0 +1,128 @@
+use actix_web::{
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web, HttpResponse,
+};
+
+use crate::{
+    dtos::data_export::{DataExportData, DataExportResponseDto},
+    schemas::user::DownloadExportQuery,
+    services::{
+        data_export_service::{self, DataExportService},
+        mail_service::MailService,
+    },
+    utils::{error::AppError, extractor::Authenticated},
+    AppState,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/export",
+    tag = "User Endpoint",
+    responses(
+        (status=202, description= "Export queued, a download link is emailed once it is ready", body= DataExportResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=409, description= "An export is already being prepared", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn request_export_handler(
+    user: Authenticated,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let export = DataExportService::new(data.db.clone())
+        .request_export(&user.id)
+        .await?;
+
+    // Built in the background. If this task dies, the periodic job retries.
+    let export_id = export.id.clone();
+    let state = data.clone();
+    actix_web::rt::spawn(async move {
+        let mail_service = MailService::new(state.mailer.clone(), state.mail_templates.clone());
+        if let Err(e) = DataExportService::new(state.db.clone())
+            .process_export(
+                &export_id,
+                &state.config,
+                state.storage.as_ref(),
+                &mail_service,
+            )
+            .await
+        {
+            eprintln!("🔥 Failed to build data export {}: {}", export_id, e);
+        }
+    });
+
+    Ok(HttpResponse::Accepted().json(DataExportResponseDto {
+        status: "success".to_string(),
+        data: DataExportData {
+            export: export.into(),
+        },
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export/{id}",
+    tag = "User Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the export"),
+    ),
+    responses(
+        (status=200, description= "Progress of the export", body= DataExportResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=404, description= "Data export not found", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn get_export_handler(
+    user: Authenticated,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let export = DataExportService::new(data.db.clone())
+        .get_export(&user.id, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(DataExportResponseDto {
+        status: "success".to_string(),
+        data: DataExportData {
+            export: export.into(),
+        },
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/exports/{id}/download",
+    tag = "User Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the export"),
+        DownloadExportQuery,
+    ),
+    responses(
+        (status=200, description= "ZIP archive with the user's data", content_type = "application/zip"),
+        (status=404, description= "Download link is invalid or has expired", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn download_export_handler(
+    path: web::Path<String>,
+    query: web::Query<DownloadExportQuery>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (export, archive) = DataExportService::new(data.db.clone())
+        .download(&path.into_inner(), &query.token, data.storage.as_ref())
+        .await?;
+
+    let filename = format!("data-export-{}.zip", export.id);
+
+    Ok(HttpResponse::Ok()
+        .content_type(data_export_service::CONTENT_TYPE)
+        .insert_header(("Cache-Control", "no-store"))
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .body(archive))
+}
@@ -0,0 +1,115 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+    dtos::{
+        global::Response,
+        identity::{
+            IdentityData, IdentityDto, IdentityListData, IdentityListResponseDto,
+            IdentityResponseDto,
+        },
+    },
+    schemas::user::LinkIdentitySchema,
+    services::identity_service::IdentityService,
+    utils::{error::AppError, extractor::Authenticated, validated_json::ValidatedJson},
+    AppState,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/identities",
+    tag = "Identity Endpoint",
+    responses(
+        (status=200, description= "Sign-in providers linked to the authenticated user", body= IdentityListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Called with an API key", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_identities_handler(
+    user: Authenticated,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let identities = IdentityService::new(data.db.clone())
+        .list_identities(&user.id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(IdentityListResponseDto {
+        status: "success".to_string(),
+        data: IdentityListData {
+            identities: identities.into_iter().map(IdentityDto::from).collect(),
+        },
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/identities/{provider}",
+    tag = "Identity Endpoint",
+    params(
+        ("provider" = String, Path, description = "Name of the sign-in provider, e.g. google"),
+    ),
+    request_body(content = LinkIdentitySchema, description = "ID token the app got from the provider", example = json!({"idToken": "eyJhbGciOiJSUzI1NiIsImtpZCI6IjEifQ...","nonce": "n-0S6_WzA2Mj"})),
+    responses(
+        (status=201, description= "Provider linked successfully", body= IdentityResponseDto ),
+        (status=401, description= "Authentication or ID token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Called with an API key", body= ErrorResponse),
+        (status=404, description= "Sign-in provider not found", body= ErrorResponse),
+        (status=409, description= "Provider account or provider already linked", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn link_identity_handler(
+    user: Authenticated,
+    path: web::Path<String>,
+    body: ValidatedJson<LinkIdentitySchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let provider = path.into_inner();
+    let claims = data
+        .oidc
+        .verify(&provider, &body.id_token, body.nonce.as_deref())
+        .await?;
+
+    let identity = IdentityService::new(data.db.clone())
+        .link(&user.id, &provider, &claims)
+        .await?;
+
+    Ok(HttpResponse::Created().json(IdentityResponseDto {
+        status: "success".to_string(),
+        data: IdentityData {
+            identity: IdentityDto::from(identity),
+        },
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/identities/{provider}",
+    tag = "Identity Endpoint",
+    params(
+        ("provider" = String, Path, description = "Name of the sign-in provider to unlink"),
+    ),
+    responses(
+        (status=200, description= "Provider unlinked successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Called with an API key", body= ErrorResponse),
+        (status=404, description= "No account from this provider is linked", body= ErrorResponse),
+        (status=409, description= "The provider is the last way to sign in", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn unlink_identity_handler(
+    user: Authenticated,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    IdentityService::new(data.db.clone())
+        .unlink(&user, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Provider unlinked successfully".to_string(),
+    }))
+}
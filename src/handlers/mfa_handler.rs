@@ -0,0 +1,137 @@
+use actix_web::{web, HttpResponse};
+
+use crate::{
+    dtos::{
+        global::Response,
+        mfa::{
+            MfaEnrollmentData, MfaEnrollmentResponseDto, RecoveryCodesData,
+            RecoveryCodesResponseDto,
+        },
+    },
+    schemas::user::{ConfirmMfaSchema, DisableMfaSchema},
+    services::{
+        login_throttle_service::{self, LoginThrottleService},
+        mfa_service::MfaService,
+        role_service::RoleService,
+    },
+    utils::{error::AppError, extractor::Authenticated, validated_json::ValidatedJson},
+    AppState,
+};
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/mfa",
+    tag = "Two-Factor Authentication Endpoint",
+    responses(
+        (status=200, description= "Secret created, scan it and confirm with a code", body= MfaEnrollmentResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=409, description= "Two-factor authentication is already enabled", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn enroll_mfa_handler(
+    user: Authenticated,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let enrollment = MfaService::new(data.db.clone())
+        .enroll(&user, &data.config.mfa_issuer)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(MfaEnrollmentResponseDto {
+        status: "success".to_string(),
+        data: MfaEnrollmentData {
+            secret: enrollment.secret,
+            otpauth_uri: enrollment.otpauth_uri,
+            qr_code: enrollment.qr_code,
+        },
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/mfa/confirm",
+    tag = "Two-Factor Authentication Endpoint",
+    request_body(content = ConfirmMfaSchema, description = "Current code from the authenticator app", example = json!({"code": "123456"})),
+    responses(
+        (status=200, description= "Two-factor authentication enabled, recovery codes are only shown once", body= RecoveryCodesResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=409, description= "Two-factor authentication is already enabled", body= ErrorResponse),
+        (status=422, description= "Validation Errors or invalid code", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn confirm_mfa_handler(
+    user: Authenticated,
+    body: ValidatedJson<ConfirmMfaSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let recovery_codes = MfaService::new(data.db.clone())
+        .confirm(&user, &body.code, &data.config.mfa_issuer)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(RecoveryCodesResponseDto {
+        status: "success".to_string(),
+        data: RecoveryCodesData { recovery_codes },
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/mfa",
+    tag = "Two-Factor Authentication Endpoint",
+    request_body(content = DisableMfaSchema, description = "Current password and authenticator code", example = json!({"currentPassword": "password123","code": "123456"})),
+    responses(
+        (status=200, description= "Two-factor authentication disabled", body= Response ),
+        (status=400, description= "Wrong password", body= ErrorResponse),
+        (status=403, description= "Two-factor authentication is mandatory for the user's role", body= ErrorResponse),
+        (status=422, description= "Validation Errors or the authentication code is invalid", body= ErrorResponse),
+        (status=429, description= "Too many wrong passwords or codes, also counting those of sign-ins", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn disable_mfa_handler(
+    user: Authenticated,
+    body: ValidatedJson<DisableMfaSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let roles = RoleService::new(data.db.clone())
+        .get_user_roles(&user.id)
+        .await?;
+    if data.config.requires_mfa(&roles) {
+        return Err(AppError::MfaRequiredForRole);
+    }
+
+    // Shares the budget of wrong codes at sign-in, so a stolen session
+    // cannot guess codes here instead.
+    let throttle_service = LoginThrottleService::new(data.db.clone());
+    let mfa_key = login_throttle_service::mfa_key(&user.id);
+    if throttle_service
+        .is_blocked(std::slice::from_ref(&mfa_key))
+        .await?
+    {
+        return Err(AppError::TooManyRequests);
+    }
+
+    let disabled = MfaService::new(data.db.clone())
+        .disable(
+            &user,
+            &body.current_password,
+            &body.code,
+            &data.config.mfa_issuer,
+        )
+        .await;
+
+    if let Err(AppError::WrongCurrentPassword | AppError::WrongCurrentMfaCode) = disabled {
+        throttle_service
+            .record_failure(&mfa_key, None, &data.config)
+            .await?;
+    }
+    disabled?;
+
+    throttle_service.clear(&mfa_key).await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Two-factor authentication disabled".to_string(),
+    }))
+}
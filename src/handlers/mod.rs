@@ -1,2 +1,9 @@
+pub mod admin_handler;
+pub mod api_key_handler;
 pub mod auth_handler;
+pub mod data_export_handler;
+pub mod identity_handler;
+pub mod mfa_handler;
+pub mod organization_handler;
 pub mod user_handler;
+pub mod well_known_handler;
@@ -0,0 +1,474 @@
+use actix_web::{cookie::time::Duration as ActixWebDuration, cookie::Cookie, web, HttpResponse};
+
+use crate::{
+    dtos::{
+        global::Response,
+        organization::{
+            InvitationData, InvitationDto, InvitationListData, InvitationListResponseDto,
+            InvitationResponseDto, MemberDto, MemberListData, MemberListResponseDto,
+            OrganizationData, OrganizationDto, OrganizationListData, OrganizationListResponseDto,
+            OrganizationResponseDto, SwitchOrganizationData, SwitchOrganizationResponseDto,
+        },
+    },
+    models::organization::OrganizationRole,
+    schemas::organization::{
+        AcceptInvitationSchema, CreateOrganizationSchema, InviteMemberSchema, UpdateMemberSchema,
+        UpdateOrganizationSchema,
+    },
+    services::{mail_service::MailService, organization_service::OrganizationService},
+    utils::{
+        error::AppError,
+        extractor::{Authenticated, CurrentMembership, CurrentSession},
+        validated_json::ValidatedJson,
+    },
+    AppState,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/organizations",
+    tag = "Organization Endpoint",
+    responses(
+        (status=200, description= "Organizations the user belongs to", body= OrganizationListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_organizations_handler(
+    user: Authenticated,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let organizations = OrganizationService::new(data.db.clone())
+        .list_organizations(&user.id)
+        .await?;
+
+    let response_data = OrganizationListResponseDto {
+        status: "success".to_string(),
+        data: OrganizationListData {
+            organizations: organizations
+                .into_iter()
+                .map(OrganizationDto::from)
+                .collect(),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/organizations",
+    tag = "Organization Endpoint",
+    request_body(content = CreateOrganizationSchema, description = "Organization to create, the caller becomes its owner", example = json!({"name": "Acme Inc","slug": "acme"})),
+    responses(
+        (status=201, description= "Organization created successfully", body= OrganizationResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=409, description= "Organization with slug already exists", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn create_organization_handler(
+    user: Authenticated,
+    body: ValidatedJson<CreateOrganizationSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let organization = OrganizationService::new(data.db.clone())
+        .create_organization(&user, &body)
+        .await?;
+
+    Ok(HttpResponse::Created().json(OrganizationResponseDto {
+        status: "success".to_string(),
+        data: OrganizationData {
+            organization: OrganizationDto::from_model(organization, OrganizationRole::Owner),
+        },
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/organizations/{org_id}/switch",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the organization"),
+    ),
+    responses(
+        (status=200, description= "Access token for the organization, also set as the token cookie", body= SwitchOrganizationResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=404, description= "Organization not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn switch_organization_handler(
+    user: Authenticated,
+    session: CurrentSession,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (token, organization, role) = OrganizationService::new(data.db.clone())
+        .switch_organization(
+            &user,
+            &session,
+            &path.into_inner(),
+            &data.config,
+            &data.jwt_keys,
+        )
+        .await?;
+
+    let cookie = Cookie::build("token", token.to_owned())
+        .path("/")
+        .max_age(ActixWebDuration::new(60 * data.config.jwt_maxage, 0))
+        .http_only(true)
+        .finish();
+
+    Ok(HttpResponse::Ok()
+        .cookie(cookie)
+        .json(SwitchOrganizationResponseDto {
+            status: "success".to_string(),
+            data: SwitchOrganizationData {
+                token,
+                organization: OrganizationDto::from_model(organization, role),
+            },
+        }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/organizations/{org_id}",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+    ),
+    responses(
+        (status=200, description= "The organization", body= OrganizationResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Organization is not the active one of the token", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn get_organization_handler(
+    membership: CurrentMembership,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let organization = OrganizationService::new(data.db.clone())
+        .get_organization(&membership.organization_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(OrganizationResponseDto {
+        status: "success".to_string(),
+        data: OrganizationData {
+            organization: OrganizationDto::from_model(organization, membership.role),
+        },
+    }))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/organizations/{org_id}",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+    ),
+    request_body(content = UpdateOrganizationSchema, description = "Fields to change, omitted fields are kept", example = json!({"name": "Acme Corporation"})),
+    responses(
+        (status=200, description= "Organization updated successfully", body= OrganizationResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not an admin of the active organization", body= ErrorResponse),
+        (status=409, description= "Organization with slug already exists", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn update_organization_handler(
+    membership: CurrentMembership,
+    body: ValidatedJson<UpdateOrganizationSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    membership.require(OrganizationRole::Admin)?;
+
+    let organization = OrganizationService::new(data.db.clone())
+        .update_organization(&membership.organization_id, &body)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(OrganizationResponseDto {
+        status: "success".to_string(),
+        data: OrganizationData {
+            organization: OrganizationDto::from_model(organization, membership.role),
+        },
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{org_id}",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+    ),
+    responses(
+        (status=200, description= "Organization deleted successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not an owner of the active organization", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn delete_organization_handler(
+    membership: CurrentMembership,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    membership.require(OrganizationRole::Owner)?;
+
+    OrganizationService::new(data.db.clone())
+        .delete_organization(&membership.organization_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Organization deleted successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/organizations/{org_id}/members",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+    ),
+    responses(
+        (status=200, description= "Members of the organization", body= MemberListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Organization is not the active one of the token", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_members_handler(
+    membership: CurrentMembership,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let members = OrganizationService::new(data.db.clone())
+        .list_members(&membership.organization_id)
+        .await?;
+
+    let response_data = MemberListResponseDto {
+        status: "success".to_string(),
+        data: MemberListData {
+            members: members.into_iter().map(MemberDto::from).collect(),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/organizations/{org_id}/members/{user_id}",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+        ("user_id" = String, Path, description = "Id of the member"),
+    ),
+    request_body(content = UpdateMemberSchema, description = "New role of the member", example = json!({"role": "admin"})),
+    responses(
+        (status=200, description= "Member updated successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not allowed to change this member", body= ErrorResponse),
+        (status=404, description= "Member not found", body= ErrorResponse),
+        (status=409, description= "The last owner cannot be demoted", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn update_member_handler(
+    membership: CurrentMembership,
+    path: web::Path<(String, String)>,
+    body: ValidatedJson<UpdateMemberSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    membership.require(OrganizationRole::Admin)?;
+    let (_, user_id) = path.into_inner();
+
+    OrganizationService::new(data.db.clone())
+        .update_member(&membership, &user_id, body.role)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Member updated successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{org_id}/members/{user_id}",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+        ("user_id" = String, Path, description = "Id of the member, or the caller's own id to leave"),
+    ),
+    responses(
+        (status=200, description= "Member removed successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not allowed to remove this member", body= ErrorResponse),
+        (status=404, description= "Member not found", body= ErrorResponse),
+        (status=409, description= "The last owner cannot leave", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn remove_member_handler(
+    membership: CurrentMembership,
+    path: web::Path<(String, String)>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (_, user_id) = path.into_inner();
+
+    OrganizationService::new(data.db.clone())
+        .remove_member(&membership, &user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Member removed successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/organizations/{org_id}/invitations",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+    ),
+    responses(
+        (status=200, description= "Invitations waiting to be accepted", body= InvitationListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not an admin of the active organization", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn list_invitations_handler(
+    membership: CurrentMembership,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    membership.require(OrganizationRole::Admin)?;
+
+    let invitations = OrganizationService::new(data.db.clone())
+        .list_invitations(&membership.organization_id)
+        .await?;
+
+    let response_data = InvitationListResponseDto {
+        status: "success".to_string(),
+        data: InvitationListData {
+            invitations: invitations.into_iter().map(InvitationDto::from).collect(),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/organizations/{org_id}/invitations",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+    ),
+    request_body(content = InviteMemberSchema, description = "Address to invite and the role to give", example = json!({"email": "jane@mail.com","role": "member"})),
+    responses(
+        (status=201, description= "Invitation sent successfully", body= InvitationResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not allowed to invite with this role", body= ErrorResponse),
+        (status=409, description= "Already a member", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn invite_member_handler(
+    user: Authenticated,
+    membership: CurrentMembership,
+    body: ValidatedJson<InviteMemberSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    membership.require(OrganizationRole::Admin)?;
+
+    let invitation = OrganizationService::new(data.db.clone())
+        .invite(
+            &membership,
+            &user,
+            &body,
+            &data.config,
+            &MailService::new(data.mailer.clone(), data.mail_templates.clone()),
+        )
+        .await?;
+
+    Ok(HttpResponse::Created().json(InvitationResponseDto {
+        status: "success".to_string(),
+        data: InvitationData {
+            invitation: InvitationDto::from(invitation),
+        },
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{org_id}/invitations/{id}",
+    tag = "Organization Endpoint",
+    params(
+        ("org_id" = String, Path, description = "Id of the active organization"),
+        ("id" = String, Path, description = "Id of the invitation"),
+    ),
+    responses(
+        (status=200, description= "Invitation revoked successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Not an admin of the active organization", body= ErrorResponse),
+        (status=404, description= "Invitation not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn revoke_invitation_handler(
+    membership: CurrentMembership,
+    path: web::Path<(String, String)>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    membership.require(OrganizationRole::Admin)?;
+    let (_, invitation_id) = path.into_inner();
+
+    OrganizationService::new(data.db.clone())
+        .revoke_invitation(&membership.organization_id, &invitation_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Invitation revoked successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/organizations/invitations/accept",
+    tag = "Organization Endpoint",
+    request_body(content = AcceptInvitationSchema, description = "Token from the invitation email", example = json!({"token": "3q2-7wAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"})),
+    responses(
+        (status=200, description= "Joined the organization", body= OrganizationResponseDto ),
+        (status=400, description= "Invitation is invalid, expired or already accepted", body= ErrorResponse),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=403, description= "Invitation was sent to a different address", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn accept_invitation_handler(
+    user: Authenticated,
+    body: ValidatedJson<AcceptInvitationSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let (organization, role) = OrganizationService::new(data.db.clone())
+        .accept_invitation(&user, &body.token)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(OrganizationResponseDto {
+        status: "success".to_string(),
+        data: OrganizationData {
+            organization: OrganizationDto::from_model(organization, role),
+        },
+    }))
+}
@@ -1,13 +1,44 @@
-use actix_web::{HttpResponse, Responder};
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse};
+use futures_util::TryStreamExt;
 
 use crate::{
-    dtos::user::{UserData, UserDto, UserResponseDto},
-    models::user::UserModel,
-    utils::extractor::Authenticated,
+    dtos::{
+        global::Response,
+        session::{SessionDto, SessionListData, SessionListResponseDto},
+        user::{UserData, UserDto, UserResponseDto},
+    },
+    handlers::auth_handler::expired_auth_cookies,
+    schemas::user::{ChangePasswordSchema, DeleteAccountSchema, UpdateProfileSchema},
+    services::{
+        role_service::RoleService, session_service::SessionService, user_services::UserService,
+    },
+    utils::{
+        error::AppError,
+        extractor::{Authenticated, CurrentSession},
+        validated_json::ValidatedJson,
+    },
+    AppState,
 };
 
-pub async fn get_me_handler(user: Authenticated) -> impl Responder {
-    let user_dto: UserDto = UserModel::into(user);
+#[utoipa::path(
+    get,
+    path = "/api/users/me",
+    tag = "User Endpoint",
+    responses(
+        (status=200, description= "The authenticated user", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn get_me_handler(
+    user: Authenticated,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let roles = RoleService::new(data.db.clone())
+        .get_user_roles(&user.id)
+        .await?;
+    let user_dto = UserDto::from_model(user.clone(), roles, data.storage.as_ref());
 
     let response_data = UserResponseDto {
         status: "success".to_string(),
@@ -16,3 +47,274 @@ pub async fn get_me_handler(user: Authenticated) -> impl Responder {
 
     Ok(HttpResponse::Ok().json(response_data))
 }
+
+#[utoipa::path(
+    patch,
+    path = "/api/users/me",
+    tag = "User Endpoint",
+    request_body(content = UpdateProfileSchema, description = "Profile fields to change, omitted fields are kept", example = json!({"name": "John Doe","photo": "https://example.com/john.png"})),
+    responses(
+        (status=200, description= "Profile updated successfully", body= UserResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn update_me_handler(
+    user: Authenticated,
+    body: ValidatedJson<UpdateProfileSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let updated_user = UserService::new(data.db.clone())
+        .update_profile(&user.id, &body)
+        .await?;
+    let roles = RoleService::new(data.db.clone())
+        .get_user_roles(&user.id)
+        .await?;
+
+    let response_data = UserResponseDto {
+        status: "success".to_string(),
+        data: UserData {
+            user: UserDto::from_model(updated_user, roles, data.storage.as_ref()),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me",
+    tag = "User Endpoint",
+    request_body(content = DeleteAccountSchema, description = "Current password to confirm the deletion", example = json!({"password": "password123"})),
+    responses(
+        (status=200, description= "Account scheduled for deletion and signed out everywhere", body= Response ),
+        (status=400, description= "Wrong password", body= ErrorResponse),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn delete_me_handler(
+    user: Authenticated,
+    body: ValidatedJson<DeleteAccountSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let scheduled_at = UserService::new(data.db.clone())
+        .request_deletion(
+            &user,
+            &body.password,
+            data.config.account_deletion_grace_period,
+        )
+        .await?;
+
+    let [cookie, refresh_cookie] = expired_auth_cookies();
+
+    Ok(HttpResponse::Ok()
+        .cookie(cookie)
+        .cookie(refresh_cookie)
+        .json(Response {
+            status: "success",
+            message: format!(
+                "Your account will be deleted on {}. Log in again before then to cancel",
+                scheduled_at.to_rfc3339()
+            ),
+        }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/me/photo",
+    tag = "User Endpoint",
+    request_body(content = UploadPhotoSchema, content_type = "multipart/form-data", description = "PNG, JPEG, WebP or GIF image in a field named photo"),
+    responses(
+        (status=200, description= "Photo uploaded successfully", body= UserResponseDto ),
+        (status=400, description= "Missing photo", body= ErrorResponse),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=413, description= "Photo exceeds the upload limit", body= ErrorResponse),
+        (status=422, description= "Not a supported image", body= ErrorResponse),
+        (status=429, description= "Too many requests, retry after Retry-After seconds", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn upload_photo_handler(
+    user: Authenticated,
+    mut payload: Multipart,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let max_size = data.config.avatar_max_size;
+    let mut photo: Option<Vec<u8>> = None;
+
+    while let Some(mut field) = payload
+        .try_next()
+        .await
+        .map_err(|e| AppError::InvalidPayload(e.to_string()))?
+    {
+        if field.name() != "photo" {
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field
+            .try_next()
+            .await
+            .map_err(|e| AppError::InvalidPayload(e.to_string()))?
+        {
+            if bytes.len() + chunk.len() > max_size {
+                return Err(AppError::PhotoTooLarge(max_size));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        photo = Some(bytes);
+        break;
+    }
+
+    let photo = photo
+        .filter(|bytes| !bytes.is_empty())
+        .ok_or(AppError::PhotoNotProvided)?;
+
+    let updated_user = UserService::new(data.db.clone())
+        .update_photo(&user, photo, data.storage.as_ref())
+        .await?;
+    let roles = RoleService::new(data.db.clone())
+        .get_user_roles(&user.id)
+        .await?;
+
+    let response_data = UserResponseDto {
+        status: "success".to_string(),
+        data: UserData {
+            user: UserDto::from_model(updated_user, roles, data.storage.as_ref()),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/sessions",
+    tag = "Session Endpoint",
+    responses(
+        (status=200, description= "Active sessions of the authenticated user", body= SessionListResponseDto ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn get_sessions_handler(
+    user: Authenticated,
+    session: CurrentSession,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let sessions = SessionService::new(data.db.clone())
+        .get_sessions(&user.id)
+        .await?;
+
+    let response_data = SessionListResponseDto {
+        status: "success".to_string(),
+        data: SessionListData {
+            sessions: sessions
+                .into_iter()
+                .map(|s| SessionDto::from_model(s, &session.id))
+                .collect(),
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(response_data))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/sessions/{id}",
+    tag = "Session Endpoint",
+    params(
+        ("id" = String, Path, description = "Id of the session to revoke"),
+    ),
+    responses(
+        (status=200, description= "Session revoked successfully", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=404, description= "Session not found", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn revoke_session_handler(
+    user: Authenticated,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let session_id = path.into_inner();
+
+    let revoked = SessionService::new(data.db.clone())
+        .revoke_session(&user.id, &session_id)
+        .await?;
+
+    if !revoked {
+        return Err(AppError::SessionNotFound);
+    }
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Session revoked successfully".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/sessions",
+    tag = "Session Endpoint",
+    responses(
+        (status=200, description= "Every session except the current one was revoked", body= Response ),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn revoke_other_sessions_handler(
+    user: Authenticated,
+    session: CurrentSession,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let revoked = SessionService::new(data.db.clone())
+        .revoke_user_sessions(&user.id, Some(&session.id))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: format!("{} other session(s) revoked", revoked),
+    }))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/users/me/password",
+    tag = "User Endpoint",
+    request_body(content = ChangePasswordSchema, description = "Current and new password", example = json!({"currentPassword": "password123","newPassword": "newpassword123","newPasswordConfirm": "newpassword123","signOutOtherSessions": true})),
+    responses(
+        (status=200, description= "Password changed successfully", body= Response ),
+        (status=400, description= "Wrong current password", body= ErrorResponse),
+        (status=401, description= "Authentication token is invalid or expired", body= ErrorResponse),
+        (status=422, description= "Validation Errors", body= ErrorResponse),
+        (status=500, description= "Internal Server Error", body= ErrorResponse ),
+    )
+)]
+pub async fn change_password_handler(
+    user: Authenticated,
+    session: CurrentSession,
+    body: ValidatedJson<ChangePasswordSchema>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let keep_session_id = body.sign_out_other_sessions.then_some(session.id.as_str());
+
+    UserService::new(data.db.clone())
+        .change_password(
+            &user,
+            &body.current_password,
+            &body.new_password,
+            keep_session_id,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(Response {
+        status: "success",
+        message: "Password changed successfully".to_string(),
+    }))
+}
@@ -0,0 +1,17 @@
+use actix_web::{web, HttpResponse};
+
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "Well-Known Endpoint",
+    responses(
+        (status=200, description= "Public keys that verify issued tokens, empty when signing with HS256", example = json!({"keys": [{"kty": "OKP", "crv": "Ed25519", "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo", "kid": "default", "use": "sig", "alg": "EdDSA"}]})),
+    )
+)]
+pub async fn jwks_handler(data: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(data.jwt_keys.jwks())
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use sqlx::MySqlPool;
+
+use crate::{
+    models::user::UserModel,
+    repositories::{data_export_repository, user_deletion_repository, user_repository},
+    services::user_services::UserService,
+    utils::{avatar, storage::Storage},
+};
+
+/// Accounts purged per run, so a backlog never holds one run for long.
+const BATCH_SIZE: u32 = 100;
+
+/// Hard-deletes accounts whose deletion grace period is over, leaving a
+/// tombstone in `user_deletions` and removing their uploaded photos and
+/// data exports.
+pub async fn purge_deleted_accounts(
+    pool: MySqlPool,
+    storage: Arc<dyn Storage>,
+) -> Result<u64, String> {
+    let users = user_repository::get_users_due_for_purge(BATCH_SIZE, pool.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut purged = 0;
+    for user in users {
+        match purge_user(&user, pool.clone(), storage.as_ref()).await {
+            Ok(true) => purged += 1,
+            Ok(false) => {}
+            Err(e) => eprintln!("🔥 Failed to purge user {}: {}", user.id, e),
+        }
+    }
+
+    Ok(purged)
+}
+
+async fn purge_user(
+    user: &UserModel,
+    pool: MySqlPool,
+    storage: &dyn Storage,
+) -> Result<bool, sqlx::Error> {
+    // The rows go with the user, so collect the archive keys first.
+    let exports = data_export_repository::get_user_data_exports(&user.id, pool.clone()).await?;
+
+    let email_hash = hex::encode(Sha256::digest(user.email.to_lowercase().as_bytes()));
+
+    let purged = user_deletion_repository::purge_user(
+        &user.id,
+        &email_hash,
+        user.deletion_requested_at,
+        pool,
+    )
+    .await?;
+
+    if !purged {
+        return Ok(false);
+    }
+
+    if avatar::is_avatar_key(&user.photo) {
+        UserService::delete_photo_files(&user.photo, storage).await;
+    }
+
+    for storage_key in exports
+        .iter()
+        .filter_map(|export| export.storage_key.as_ref())
+    {
+        if let Err(e) = storage.delete(storage_key).await {
+            eprintln!("🔥 Failed to delete {}: {}", storage_key, e);
+        }
+    }
+
+    Ok(purged)
+}
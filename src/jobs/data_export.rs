@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use sqlx::MySqlPool;
+
+use crate::{
+    repositories::data_export_repository,
+    services::{
+        data_export_service::{self, DataExportService},
+        mail_service::MailService,
+    },
+    utils::{
+        config::Config,
+        mailer::{EmailTemplates, Mailer},
+        storage::Storage,
+    },
+};
+
+/// Exports handled per run, so a backlog never holds one run for long.
+const BATCH_SIZE: u32 = 20;
+
+/// Builds exports that are still queued, e.g. because the server restarted
+/// before the request's own task got to them, or whose run died halfway.
+pub async fn process_pending_exports(
+    pool: MySqlPool,
+    config: Config,
+    storage: Arc<dyn Storage>,
+    mailer: Arc<dyn Mailer>,
+    templates: Arc<EmailTemplates>,
+) -> Result<u64, String> {
+    let exports = data_export_repository::get_pending_data_exports(
+        data_export_service::stale_before(),
+        BATCH_SIZE,
+        pool.clone(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let service = DataExportService::new(pool);
+    let mail_service = MailService::new(mailer, templates);
+
+    let mut processed = 0;
+    for export in exports {
+        match service
+            .process_export(&export.id, &config, storage.as_ref(), &mail_service)
+            .await
+        {
+            Ok(()) => processed += 1,
+            Err(e) => eprintln!("🔥 Failed to build data export {}: {}", export.id, e),
+        }
+    }
+
+    Ok(processed)
+}
+
+/// Deletes archives whose download link expired, together with their rows.
+pub async fn delete_expired_exports(
+    pool: MySqlPool,
+    storage: Arc<dyn Storage>,
+) -> Result<u64, String> {
+    let exports = data_export_repository::get_expired_data_exports(BATCH_SIZE, pool.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut deleted = 0;
+    for export in exports {
+        if let Some(storage_key) = &export.storage_key {
+            if let Err(e) = storage.delete(storage_key).await {
+                eprintln!("🔥 Failed to delete {}: {}", storage_key, e);
+                continue;
+            }
+        }
+
+        data_export_repository::delete_data_export(&export.id, pool.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        deleted += 1;
+    }
+
+    Ok(deleted)
+}
@@ -0,0 +1,13 @@
+use sqlx::MySqlPool;
+
+use crate::repositories::login_throttle_repository;
+
+/// Deletes failed-login counters that have started over anyway, so the table
+/// does not grow with every address that ever mistyped a password.
+pub async fn delete_stale_throttles(pool: MySqlPool, window_minutes: i64) -> Result<u64, String> {
+    let query_result = login_throttle_repository::delete_stale_throttles(window_minutes, pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(query_result.rows_affected())
+}
@@ -0,0 +1,29 @@
+use std::{future::Future, time::Duration};
+
+pub mod account_purge;
+pub mod data_export;
+pub mod login_throttle;
+pub mod user_status;
+
+/// Runs `job` every `period` on the current actix runtime for as long as the
+/// server lives. `job` returns how many rows it touched, which is logged
+/// when non-zero.
+///
+/// Every instance runs its own copy, so jobs must be idempotent.
+pub fn spawn_periodic<F, Fut>(name: &'static str, period: Duration, job: F)
+where
+    F: Fn() -> Fut + 'static,
+    Fut: Future<Output = Result<u64, String>> + 'static,
+{
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(period);
+        loop {
+            interval.tick().await;
+            match job().await {
+                Ok(0) => {}
+                Ok(count) => println!("✅ {}: {} row(s) updated", name, count),
+                Err(e) => eprintln!("🔥 {} failed: {}", name, e),
+            }
+        }
+    });
+}
@@ -0,0 +1,14 @@
+use sqlx::MySqlPool;
+
+use crate::repositories::user_repository;
+
+/// Reactivates accounts whose suspension or ban expired. Requests already
+/// treat those accounts as active, this only tidies the rows so filters and
+/// the admin panel agree.
+pub async fn lift_expired_statuses(pool: MySqlPool) -> Result<u64, String> {
+    let query_result = user_repository::lift_expired_statuses(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(query_result.rows_affected())
+}
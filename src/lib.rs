@@ -1,8 +1,18 @@
+use std::sync::Arc;
+
 use sqlx::MySqlPool;
-use utils::config::Config;
+use utils::{
+    config::Config,
+    jwt_keys::JwtKeys,
+    mailer::{EmailTemplates, Mailer},
+    oidc::OidcVerifier,
+    rate_limit::RateLimitStore,
+    storage::Storage,
+};
 
 pub mod dtos;
 pub mod handlers;
+pub mod jobs;
 pub mod models;
 pub mod repositories;
 pub mod routes;
@@ -13,4 +23,10 @@ pub mod utils;
 pub struct AppState {
     pub db: MySqlPool,
     pub config: Config,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub mailer: Arc<dyn Mailer>,
+    pub mail_templates: Arc<EmailTemplates>,
+    pub storage: Arc<dyn Storage>,
+    pub oidc: Arc<OidcVerifier>,
+    pub rate_limit_store: Arc<dyn RateLimitStore>,
 }
@@ -1,16 +1,75 @@
+use std::{sync::Arc, time::Duration};
+
 use actix_cors::Cors;
+use actix_files::Files;
 use actix_web::{http::header, middleware::Logger, web, App, HttpResponse, HttpServer, Responder};
 use dotenv::dotenv;
 use rust_flutter_application::{
     dtos::{
-        global::Response,
-        user::{TokenData, UserData, UserDto, UserLoginResponseDto, UserResponseDto},
+        api_key::{
+            ApiKeyDto, ApiKeyListData, ApiKeyListResponseDto, CreatedApiKeyData,
+            CreatedApiKeyResponseDto,
+        },
+        data_export::{DataExportData, DataExportDto, DataExportResponseDto},
+        global::{PaginationDto, Response},
+        identity::{
+            IdentityData, IdentityDto, IdentityListData, IdentityListResponseDto,
+            IdentityResponseDto,
+        },
+        mfa::{
+            MfaEnrollmentData, MfaEnrollmentResponseDto, MfaPendingData, MfaPendingResponseDto,
+            RecoveryCodesData, RecoveryCodesResponseDto,
+        },
+        organization::{
+            InvitationData, InvitationDto, InvitationListData, InvitationListResponseDto,
+            InvitationResponseDto, MemberDto, MemberListData, MemberListResponseDto,
+            OrganizationData, OrganizationDto, OrganizationListData, OrganizationListResponseDto,
+            OrganizationResponseDto, SwitchOrganizationData, SwitchOrganizationResponseDto,
+        },
+        role::{
+            PermissionDto, PermissionListData, PermissionListResponseDto, RoleData, RoleDto,
+            RoleListData, RoleListResponseDto, RoleResponseDto,
+        },
+        session::{SessionDto, SessionListData, SessionListResponseDto},
+        user::{
+            TokenData, UserData, UserDto, UserListData, UserListResponseDto, UserLoginResponseDto,
+            UserResponseDto,
+        },
+    },
+    handlers, jobs,
+    models::{data_export::DataExportStatus, organization::OrganizationRole, user::UserStatus},
+    routes::{
+        admin::admin_config, auth::auth_config, export::export_config,
+        organization::organization_config, user::auth_config as user_config,
+        well_known::well_known_config,
+    },
+    schemas::admin::{
+        AdminUpdateUserSchema, CreateRoleSchema, SetUserRolesSchema, SetUserStatusSchema,
+        SortOrder, UpdateRoleSchema, UserSortField,
+    },
+    schemas::auth::{
+        ForgotPasswordSchema, LoginUserSchema, MagicLinkSchema, OidcLoginSchema,
+        RefreshTokenSchema, RegisterUserSchema, ResendVerificationSchema, ResetPasswordSchema,
+        VerifyEmailSchema, VerifyMagicLinkSchema, VerifyMfaSchema,
+    },
+    schemas::organization::{
+        AcceptInvitationSchema, CreateOrganizationSchema, InviteMemberSchema, UpdateMemberSchema,
+        UpdateOrganizationSchema,
+    },
+    schemas::user::{
+        ChangePasswordSchema, ConfirmMfaSchema, CreateApiKeySchema, DeleteAccountSchema,
+        DisableMfaSchema, LinkIdentitySchema, UpdateProfileSchema, UploadPhotoSchema,
+    },
+    utils::{
+        config::Config,
+        error::{AppError, ErrorResponse, FieldError},
+        extractor::{RequireAuth, API_KEY_HEADER},
+        jwt_keys::JwtKeys,
+        mailer::{self, EmailTemplates},
+        oidc::OidcVerifier,
+        problem::{ProblemDetails, ProblemJson, REQUEST_ID_HEADER},
+        rate_limit, storage,
     },
-    handlers,
-    models::user::UserRole,
-    routes::auth::auth_config,
-    schemas::auth::{LoginUserSchema, RegisterUserSchema},
-    utils::{config::Config, extractor::RequireAuth},
     AppState,
 };
 use sqlx::mysql::MySqlPoolOptions;
@@ -20,13 +79,21 @@ use utoipa_swagger_ui::SwaggerUi;
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        handlers::auth_handler::logout_user_handler,handlers::auth_handler::login_user_handler,handlers::auth_handler::register_user_handler,health_checker_handler
+        handlers::auth_handler::logout_user_handler,handlers::auth_handler::login_user_handler,handlers::auth_handler::register_user_handler,handlers::auth_handler::request_magic_link_handler,handlers::auth_handler::verify_magic_link_handler,handlers::auth_handler::oidc_login_handler,handlers::auth_handler::verify_mfa_handler,handlers::auth_handler::refresh_token_handler,handlers::mfa_handler::enroll_mfa_handler,handlers::mfa_handler::confirm_mfa_handler,handlers::mfa_handler::disable_mfa_handler,handlers::auth_handler::verify_email_handler,handlers::auth_handler::resend_verification_handler,handlers::auth_handler::forgot_password_handler,handlers::auth_handler::reset_password_handler,handlers::user_handler::get_me_handler,handlers::user_handler::update_me_handler,handlers::user_handler::delete_me_handler,handlers::user_handler::upload_photo_handler,handlers::user_handler::change_password_handler,handlers::user_handler::get_sessions_handler,handlers::user_handler::revoke_session_handler,handlers::user_handler::revoke_other_sessions_handler,handlers::identity_handler::list_identities_handler,handlers::identity_handler::link_identity_handler,handlers::identity_handler::unlink_identity_handler,handlers::api_key_handler::list_api_keys_handler,handlers::api_key_handler::create_api_key_handler,handlers::api_key_handler::delete_api_key_handler,handlers::data_export_handler::request_export_handler,handlers::data_export_handler::get_export_handler,handlers::data_export_handler::download_export_handler,handlers::admin_handler::list_users_handler,handlers::admin_handler::get_user_handler,handlers::admin_handler::update_user_handler,handlers::admin_handler::set_roles_handler,handlers::admin_handler::set_status_handler,handlers::admin_handler::clear_status_handler,handlers::admin_handler::unlock_user_handler,handlers::admin_handler::list_roles_handler,handlers::admin_handler::get_role_handler,handlers::admin_handler::create_role_handler,handlers::admin_handler::update_role_handler,handlers::admin_handler::delete_role_handler,handlers::admin_handler::list_permissions_handler,handlers::organization_handler::list_organizations_handler,handlers::organization_handler::create_organization_handler,handlers::organization_handler::switch_organization_handler,handlers::organization_handler::get_organization_handler,handlers::organization_handler::update_organization_handler,handlers::organization_handler::delete_organization_handler,handlers::organization_handler::list_members_handler,handlers::organization_handler::update_member_handler,handlers::organization_handler::remove_member_handler,handlers::organization_handler::list_invitations_handler,handlers::organization_handler::invite_member_handler,handlers::organization_handler::revoke_invitation_handler,handlers::organization_handler::accept_invitation_handler,handlers::well_known_handler::jwks_handler,health_checker_handler
     ),
     components(
-        schemas(UserRole,UserDto,UserData,UserResponseDto,RegisterUserSchema,Response,UserLoginResponseDto,LoginUserSchema,TokenData)
+        schemas(UserStatus,UserDto,UserData,UserResponseDto,RegisterUserSchema,Response,ErrorResponse,FieldError,ProblemDetails,UserLoginResponseDto,LoginUserSchema,MagicLinkSchema,VerifyMagicLinkSchema,OidcLoginSchema,TokenData,RefreshTokenSchema,VerifyEmailSchema,ResendVerificationSchema,ForgotPasswordSchema,ResetPasswordSchema,UpdateProfileSchema,UploadPhotoSchema,DeleteAccountSchema,ChangePasswordSchema,VerifyMfaSchema,ConfirmMfaSchema,DisableMfaSchema,MfaEnrollmentData,MfaEnrollmentResponseDto,MfaPendingData,MfaPendingResponseDto,RecoveryCodesData,RecoveryCodesResponseDto,SessionDto,SessionListData,SessionListResponseDto,LinkIdentitySchema,IdentityDto,IdentityData,IdentityResponseDto,IdentityListData,IdentityListResponseDto,CreateApiKeySchema,ApiKeyDto,ApiKeyListData,ApiKeyListResponseDto,CreatedApiKeyData,CreatedApiKeyResponseDto,PaginationDto,UserListData,UserListResponseDto,UserSortField,SortOrder,AdminUpdateUserSchema,SetUserRolesSchema,SetUserStatusSchema,CreateRoleSchema,UpdateRoleSchema,RoleDto,RoleData,RoleResponseDto,RoleListData,RoleListResponseDto,PermissionDto,PermissionListData,PermissionListResponseDto,DataExportStatus,DataExportDto,DataExportData,DataExportResponseDto,OrganizationRole,CreateOrganizationSchema,UpdateOrganizationSchema,InviteMemberSchema,UpdateMemberSchema,AcceptInvitationSchema,OrganizationDto,OrganizationData,OrganizationResponseDto,OrganizationListData,OrganizationListResponseDto,SwitchOrganizationData,SwitchOrganizationResponseDto,MemberDto,MemberListData,MemberListResponseDto,InvitationDto,InvitationData,InvitationResponseDto,InvitationListData,InvitationListResponseDto)
     ),
     tags(
-        (name = "Authentication Endpoint", description = "Handle user authentication")
+        (name = "Authentication Endpoint", description = "Handle user authentication"),
+        (name = "User Endpoint", description = "Manage the authenticated user's account"),
+        (name = "Session Endpoint", description = "List and revoke signed-in devices"),
+        (name = "Identity Endpoint", description = "Link and unlink sign-in providers such as Google and Apple"),
+        (name = "API Key Endpoint", description = "Manage API keys for scripts and integrations"),
+        (name = "Two-Factor Authentication Endpoint", description = "Enroll in and manage TOTP two-factor authentication"),
+        (name = "Admin Endpoint", description = "Manage user accounts, roles and permissions"),
+        (name = "Organization Endpoint", description = "Manage organizations, their members and invitations"),
+        (name = "Well-Known Endpoint", description = "Public metadata for verifying issued tokens")
     ),
 )]
 struct ApiDoc;
@@ -66,21 +133,153 @@ async fn main() -> std::io::Result<()> {
     };
 
     let port = config.clone().port;
-    println!("{}", format!("🚀 Server is running on port {}", port));
+    println!("🚀 Server is running on port {}", port);
 
     let openapi = ApiDoc::openapi();
 
+    // setup mailer
+    let mailer = match mailer::from_config(&config) {
+        Ok(mailer) => {
+            println!("✅ Mailer \"{}\" is ready!", config.mailer);
+            mailer
+        }
+        Err(err) => {
+            eprintln!("🔥 Failed to set up the mailer: {}", err);
+            std::process::exit(1)
+        }
+    };
+
+    let mail_templates =
+        match EmailTemplates::load(&config.mail_template_dir, &config.default_locale) {
+            Ok(templates) => Arc::new(templates),
+            Err(err) => {
+                eprintln!("🔥 Failed to load email templates: {}", err);
+                std::process::exit(1)
+            }
+        };
+
+    // setup storage
+    let storage = match storage::from_config(&config) {
+        Ok(storage) => {
+            println!("✅ Storage \"{}\" is ready!", config.storage);
+            storage
+        }
+        Err(err) => {
+            eprintln!("🔥 Failed to set up the storage: {}", err);
+            std::process::exit(1)
+        }
+    };
+
+    let jwt_keys = match JwtKeys::from_config(&config) {
+        Ok(keys) => {
+            println!("✅ JWT signing key \"{}\" is ready!", config.jwt_key_id);
+            Arc::new(keys)
+        }
+        Err(err) => {
+            eprintln!("🔥 Failed to load JWT keys: {}", err);
+            std::process::exit(1)
+        }
+    };
+
+    let oidc = match OidcVerifier::from_config(&config) {
+        Ok(verifier) => Arc::new(verifier),
+        Err(err) => {
+            eprintln!("🔥 Failed to set up sign-in providers: {}", err);
+            std::process::exit(1)
+        }
+    };
+
+    let rate_limit_store = match rate_limit::from_config(&config).await {
+        Ok(store) => {
+            println!(
+                "✅ Rate limit store \"{}\" is ready!",
+                config.rate_limit_store
+            );
+            store
+        }
+        Err(err) => {
+            eprintln!("🔥 Failed to set up the rate limit store: {}", err);
+            std::process::exit(1)
+        }
+    };
+
+    // start background jobs
+    let sweep_pool = pool.clone();
+    jobs::spawn_periodic(
+        "Lift expired suspensions",
+        Duration::from_secs(config.status_sweep_interval),
+        move || jobs::user_status::lift_expired_statuses(sweep_pool.clone()),
+    );
+
+    let purge_pool = pool.clone();
+    let purge_storage = storage.clone();
+    jobs::spawn_periodic(
+        "Purge deleted accounts",
+        Duration::from_secs(config.account_purge_interval),
+        move || {
+            jobs::account_purge::purge_deleted_accounts(purge_pool.clone(), purge_storage.clone())
+        },
+    );
+
+    let export_pool = pool.clone();
+    let export_job_config = config.clone();
+    let export_storage = storage.clone();
+    let export_mailer = mailer.clone();
+    let export_templates = mail_templates.clone();
+    jobs::spawn_periodic(
+        "Process data exports",
+        Duration::from_secs(config.data_export_interval),
+        move || {
+            jobs::data_export::process_pending_exports(
+                export_pool.clone(),
+                export_job_config.clone(),
+                export_storage.clone(),
+                export_mailer.clone(),
+                export_templates.clone(),
+            )
+        },
+    );
+
+    let cleanup_pool = pool.clone();
+    let cleanup_storage = storage.clone();
+    jobs::spawn_periodic(
+        "Delete expired data exports",
+        Duration::from_secs(config.data_export_interval),
+        move || {
+            jobs::data_export::delete_expired_exports(cleanup_pool.clone(), cleanup_storage.clone())
+        },
+    );
+
+    let throttle_pool = pool.clone();
+    let throttle_window = config.login_failure_window;
+    jobs::spawn_periodic(
+        "Delete stale login throttles",
+        Duration::from_secs(60 * config.login_failure_window.max(1) as u64),
+        move || {
+            jobs::login_throttle::delete_stale_throttles(throttle_pool.clone(), throttle_window)
+        },
+    );
+
     // setup server
     let server = HttpServer::new(move || {
         // configure cors
         let cors = Cors::default()
             // .allowed_origin("http://localhost:3000")
             // .allow_any_origin()
-            .allowed_methods(vec!["GET", "POST", "PATCH", "DELETE"])
+            .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
             .allowed_headers(vec![
                 header::CONTENT_TYPE,
                 header::AUTHORIZATION,
                 header::ACCEPT,
+                header::HeaderName::from_static(API_KEY_HEADER),
+                header::HeaderName::from_static(REQUEST_ID_HEADER),
+            ])
+            .expose_headers(vec![
+                header::RETRY_AFTER,
+                header::HeaderName::from_static("ratelimit-limit"),
+                header::HeaderName::from_static("ratelimit-remaining"),
+                header::HeaderName::from_static("ratelimit-reset"),
+                header::HeaderName::from_static(REQUEST_ID_HEADER),
             ])
             .supports_credentials();
 
@@ -88,19 +287,39 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(AppState {
                 db: pool.clone(),
                 config: config.clone(),
+                jwt_keys: jwt_keys.clone(),
+                mailer: mailer.clone(),
+                mail_templates: mail_templates.clone(),
+                storage: storage.clone(),
+                oidc: oidc.clone(),
+                rate_limit_store: rate_limit_store.clone(),
             }))
+            // Bodies taken with plain `web::Json` fail like `ValidatedJson` ones.
+            .app_data(web::JsonConfig::default().error_handler(|e, _| AppError::from(e).into()))
+            .wrap(ProblemJson::new(&config.api_url))
             .wrap(cors)
             .wrap(Logger::default())
             .configure(auth_config)
+            .configure(user_config)
+            .configure(admin_config)
+            .configure(organization_config)
+            .configure(export_config)
+            .configure(well_known_config)
+            .configure(|conf| {
+                // S3 serves its own URLs, only local uploads go through us.
+                if config.storage == "local" {
+                    // Exports are only handed out through their download link.
+                    conf.service(
+                        Files::new("/uploads", &config.storage_local_dir)
+                            .path_filter(|path, _| !path.starts_with("exports")),
+                    );
+                }
+            })
             .route(
                 "/api/healthchecker",
                 web::get()
                     .to(health_checker_handler)
-                    .wrap(RequireAuth::allowed_roles(vec![
-                        UserRole::User,
-                        UserRole::Admin,
-                        UserRole::Moderator,
-                    ])),
+                    .wrap(RequireAuth::authenticated()),
             )
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
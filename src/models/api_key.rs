@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct ApiKeyModel {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    /// Start of the key in clear text, for telling keys apart.
+    pub key_prefix: String,
+    /// SHA-256 of the whole key.
+    pub key_hash: String,
+    /// `None` means the key never expires.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "data_export_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DataExportStatus {
+    Pending,
+    Processing,
+    Ready,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct DataExportModel {
+    pub id: String,
+    pub user_id: String,
+    pub status: DataExportStatus,
+    /// Key of the archive in storage once it is ready.
+    pub storage_key: Option<String>,
+    /// SHA-256 of the token in the download link.
+    pub token_hash: Option<String>,
+    pub error: Option<String>,
+    /// When the download link stops working and the archive is deleted.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct LoginThrottleModel {
+    /// `email:<address>` or `ip:<address>`.
+    pub throttle_key: String,
+    /// Failed logins since the counter last reset.
+    pub failures: i32,
+    pub last_failure_at: chrono::DateTime<chrono::Utc>,
+    pub blocked_until: Option<chrono::DateTime<chrono::Utc>>,
+}
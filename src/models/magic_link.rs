@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct MagicLinkModel {
+    pub id: String,
+    pub user_id: String,
+    pub token_hash: String,
+    pub code_hash: String,
+    /// Codes entered so far, counted before each is checked. The code stops
+    /// working at the limit.
+    pub failed_attempts: i32,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct UserMfaModel {
+    pub user_id: String,
+    /// Base32 encoded TOTP secret.
+    pub secret: String,
+    /// Last TOTP time step accepted, so a code cannot be replayed.
+    pub last_used_step: Option<i64>,
+    /// `jti` of the outstanding `mfa_pending` token, see
+    /// `MfaService::start_challenge`.
+    pub pending_token_id: Option<String>,
+    pub confirmed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct MfaRecoveryCodeModel {
+    pub id: String,
+    pub user_id: String,
+    pub code_hash: String,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -1 +1,14 @@
+pub mod api_key;
+pub mod data_export;
+pub mod login_throttle;
+pub mod magic_link;
+pub mod mfa;
+pub mod organization;
+pub mod password_reset;
+pub mod refresh_token;
+pub mod role;
+pub mod role_history;
+pub mod session;
 pub mod user;
+pub mod user_deletion;
+pub mod user_identity;
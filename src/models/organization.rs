@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Role of a user inside one organization, independent of their global
+/// roles.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "organization_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizationRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+impl OrganizationRole {
+    pub fn to_str(&self) -> &str {
+        match self {
+            OrganizationRole::Owner => "owner",
+            OrganizationRole::Admin => "admin",
+            OrganizationRole::Member => "member",
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            OrganizationRole::Owner => 2,
+            OrganizationRole::Admin => 1,
+            OrganizationRole::Member => 0,
+        }
+    }
+
+    /// Whether this role grants at least what `other` grants.
+    pub fn includes(&self, other: OrganizationRole) -> bool {
+        self.rank() >= other.rank()
+    }
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct OrganizationModel {
+    pub id: String,
+    pub name: String,
+    /// Unique, URL-friendly name.
+    pub slug: String,
+    pub created_by: Option<String>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct OrganizationMemberModel {
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: OrganizationRole,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A membership joined with the member's name and email.
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct OrganizationMemberUserModel {
+    pub user_id: String,
+    pub name: String,
+    pub email: String,
+    pub role: OrganizationRole,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// An organization joined with the role the user holds in it.
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct UserOrganizationModel {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub role: OrganizationRole,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct OrganizationInvitationModel {
+    pub id: String,
+    pub organization_id: String,
+    pub email: String,
+    pub role: OrganizationRole,
+    /// SHA-256 of the token in the invitation link.
+    pub token_hash: String,
+    pub invited_by: Option<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub accepted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Roles created by the RBAC migration. They cannot be renamed or deleted,
+/// only their permissions can change.
+pub const ADMIN_ROLE: &str = "admin";
+pub const MODERATOR_ROLE: &str = "moderator";
+/// Given to every account on registration.
+pub const USER_ROLE: &str = "user";
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct RoleModel {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: i8,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct PermissionModel {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A role name together with the user or role it belongs to, for loading
+/// the roles of many users or the permissions of many roles at once.
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct NamedGrantModel {
+    pub owner_id: String,
+    pub name: String,
+}
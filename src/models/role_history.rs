@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "role_change", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RoleChange {
+    Assigned,
+    Removed,
+}
+
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct RoleHistoryModel {
+    pub id: String,
+    pub user_id: String,
+    /// Name of the role at the time of the change.
+    pub role: String,
+    pub action: RoleChange,
+    /// Admin who made the change, `None` once that admin is deleted.
+    pub changed_by: Option<String>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -1,33 +1,21 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use crate::dtos::user::UserDto;
-
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, sqlx::Type, PartialEq, ToSchema)]
-#[sqlx(type_name = "user_role", rename_all = "lowercase")]
-pub enum UserRole {
-    Admin,
-    Moderator,
-    User,
+#[sqlx(type_name = "user_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    Active,
+    Suspended,
+    Banned,
 }
 
-impl UserRole {
+impl UserStatus {
     pub fn to_str(&self) -> &str {
         match self {
-            UserRole::Admin => "admin",
-            UserRole::Moderator => "moderator",
-            UserRole::User => "user",
-        }
-    }
-}
-
-impl From<String> for UserRole {
-    fn from(s: String) -> Self {
-        match s.to_lowercase().as_str() {
-            "admin" => UserRole::Admin,
-            "moderator" => UserRole::Moderator,
-            "user" => UserRole::User,
-            _ => UserRole::User, // Default to UserRole::User or handle as needed
+            UserStatus::Active => "active",
+            UserStatus::Suspended => "suspended",
+            UserStatus::Banned => "banned",
         }
     }
 }
@@ -38,26 +26,37 @@ pub struct UserModel {
     pub name: String,
     pub email: String,
     pub password: String,
-    pub role: UserRole,
+    /// `false` for accounts created through a sign-in provider until the
+    /// user sets a password.
+    pub has_password: i8,
+    pub status: UserStatus,
+    pub status_reason: Option<String>,
+    /// Admin who applied the current status.
+    pub status_changed_by: Option<String>,
+    /// When a suspension or ban lifts by itself. `None` means it lasts until
+    /// an admin clears it.
+    pub status_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deletion_requested_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the purge job may delete the account. Logging in before then
+    /// cancels the deletion.
+    pub deletion_scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub locale: String,
     pub photo: String,
     pub verified: i8,
+    pub mfa_enabled: i8,
     #[serde(rename = "createdAt")]
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-impl Into<UserDto> for UserModel {
-    fn into(self) -> UserDto {
-        UserDto {
-            id: self.id,
-            name: self.name,
-            email: self.email,
-            role: self.role,
-            photo: self.photo,
-            verified: self.verified != 0,
-            created_at: self.created_at,
-            updated_at: self.updated_at,
+impl UserModel {
+    /// The status that currently applies. A suspension whose expiry has
+    /// passed counts as lifted even before the sweep job resets the row.
+    pub fn effective_status(&self) -> UserStatus {
+        match self.status_expires_at {
+            Some(expires_at) if expires_at <= chrono::Utc::now() => UserStatus::Active,
+            _ => self.status,
         }
     }
 }
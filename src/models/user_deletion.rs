@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Record that an account existed and was purged. Holds no personal data
+/// beyond a hash of the email address, so it can be kept indefinitely to
+/// answer "was this account deleted, and when".
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct UserDeletionModel {
+    pub id: String,
+    pub user_id: String,
+    pub email_hash: String,
+    pub requested_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub purged_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// A sign-in provider account linked to a user.
+#[derive(Debug, Deserialize, sqlx::FromRow, Serialize, Clone)]
+pub struct UserIdentityModel {
+    pub id: String,
+    pub user_id: String,
+    /// Name of the provider in `OIDC_PROVIDERS`.
+    pub provider: String,
+    /// The provider's `sub` claim.
+    pub subject: String,
+    /// Email the provider reported when the identity was last used.
+    pub email: Option<String>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -0,0 +1,166 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool, QueryBuilder};
+
+use crate::models::{api_key::ApiKeyModel, role::NamedGrantModel};
+
+/// Stores the key and grants it the permissions named. Unknown names are
+/// ignored, callers validate them first.
+pub async fn create_api_key(
+    api_key: &ApiKeyModel,
+    permissions: &[String],
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO api_keys (id, user_id, name, key_prefix, key_hash, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&api_key.id)
+    .bind(&api_key.user_id)
+    .bind(&api_key.name)
+    .bind(&api_key.key_prefix)
+    .bind(&api_key.key_hash)
+    .bind(api_key.expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    if !permissions.is_empty() {
+        let mut builder =
+            QueryBuilder::new("INSERT INTO api_key_permissions (api_key_id, permission_id) ");
+        builder
+            .push("SELECT ")
+            .push_bind(&api_key.id)
+            .push(", id FROM permissions WHERE name IN (");
+        let mut separated = builder.separated(", ");
+        for permission in permissions {
+            separated.push_bind(permission);
+        }
+        separated.push_unseparated(")");
+
+        builder.build().execute(&mut *tx).await?;
+    }
+
+    tx.commit().await
+}
+
+pub async fn get_api_key(
+    api_key_id: &str,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<ApiKeyModel>, sqlx::Error> {
+    let api_key = sqlx::query_as!(
+        ApiKeyModel,
+        r#"
+            SELECT *
+            FROM api_keys
+            WHERE id = ? AND user_id = ?
+        "#,
+        api_key_id,
+        user_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(api_key)
+}
+
+pub async fn get_api_keys(user_id: &str, pool: MySqlPool) -> Result<Vec<ApiKeyModel>, sqlx::Error> {
+    let api_keys = sqlx::query_as!(
+        ApiKeyModel,
+        r#"
+            SELECT *
+            FROM api_keys
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(api_keys)
+}
+
+/// The key with this hash, unless it has expired.
+pub async fn get_active_api_key_by_hash(
+    key_hash: &str,
+    pool: MySqlPool,
+) -> Result<Option<ApiKeyModel>, sqlx::Error> {
+    let api_key = sqlx::query_as!(
+        ApiKeyModel,
+        r#"
+            SELECT *
+            FROM api_keys
+            WHERE key_hash = ? AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        "#,
+        key_hash,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(api_key)
+}
+
+/// Permission names of each key, keyed by `owner_id` = api key id.
+pub async fn get_api_key_permissions(
+    api_key_ids: &[String],
+    pool: MySqlPool,
+) -> Result<Vec<NamedGrantModel>, sqlx::Error> {
+    if api_key_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT ap.api_key_id AS owner_id, p.name FROM api_key_permissions ap \
+         JOIN permissions p ON p.id = ap.permission_id WHERE ap.api_key_id IN (",
+    );
+    let mut separated = builder.separated(", ");
+    for api_key_id in api_key_ids {
+        separated.push_bind(api_key_id);
+    }
+    separated.push_unseparated(") ORDER BY p.name");
+
+    let grants = builder
+        .build_query_as::<NamedGrantModel>()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(grants)
+}
+
+/// Bumps `last_used_at`, at most once a minute so that busy integrations do
+/// not turn every request into a write.
+pub async fn touch_api_key(
+    api_key_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE api_keys
+            SET last_used_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+                AND (last_used_at IS NULL OR last_used_at < CURRENT_TIMESTAMP - INTERVAL 1 MINUTE)
+        "#,
+    )
+    .bind(api_key_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn delete_api_key(
+    api_key_id: &str,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query("DELETE FROM api_keys WHERE id = ? AND user_id = ?")
+        .bind(api_key_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(query_result)
+}
@@ -1,27 +1,99 @@
 use sqlx::{mysql::MySqlQueryResult, MySqlPool};
 
-use crate::{schemas::auth::RegisterUserSchema, utils::password};
+use crate::{
+    models::{role::USER_ROLE, user_identity::UserIdentityModel},
+    schemas::auth::RegisterUserSchema,
+};
 
 pub async fn register_user(
-    user_id: &String,
+    user_id: &str,
     body: &RegisterUserSchema,
+    hashed_password: &str,
+    locale: &str,
     pool: MySqlPool,
-) -> Result<MySqlQueryResult, String> {
-    let hashed_password = password::hash(&body.password).map_err(|e| (e.to_string()))?;
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let mut tx = pool.begin().await?;
 
     let query_result = sqlx::query(
         r#"
-            INSERT INTO users (id, name, email, password) 
-            VALUES (?, ?, ?, ?)
+            INSERT INTO users (id, name, email, password, locale) 
+            VALUES (?, ?, ?, ?, ?)
         "#,
     )
-    .bind(user_id.clone())
+    .bind(user_id)
     .bind(body.name.to_string())
     .bind(body.email.to_string())
     .bind(hashed_password)
-    .execute(&pool)
-    .await
-    .map_err(|err: sqlx::Error| err.to_string());
+    .bind(locale)
+    .execute(&mut *tx)
+    .await?;
 
-    Ok(query_result?)
+    sqlx::query(
+        r#"
+            INSERT INTO user_roles (user_id, role_id)
+            SELECT ?, id FROM roles WHERE name = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(USER_ROLE)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(query_result)
+}
+
+/// Creates a verified account for someone signing in through a provider for
+/// the first time, together with the identity link. `identity.user_id` is the
+/// new user's id and `identity.email` their email.
+pub async fn register_identity_user(
+    name: &str,
+    locale: &str,
+    hashed_password: &str,
+    identity: &UserIdentityModel,
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO users (id, name, email, password, has_password, locale, verified)
+            VALUES (?, ?, ?, ?, FALSE, ?, TRUE)
+        "#,
+    )
+    .bind(&identity.user_id)
+    .bind(name)
+    .bind(&identity.email)
+    .bind(hashed_password)
+    .bind(locale)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO user_roles (user_id, role_id)
+            SELECT ?, id FROM roles WHERE name = ?
+        "#,
+    )
+    .bind(&identity.user_id)
+    .bind(USER_ROLE)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO user_identities (id, user_id, provider, subject, email, last_used_at)
+            VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(&identity.id)
+    .bind(&identity.user_id)
+    .bind(&identity.provider)
+    .bind(&identity.subject)
+    .bind(&identity.email)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
 }
@@ -0,0 +1,210 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::data_export::DataExportModel;
+
+pub async fn create_data_export(
+    export_id: &str,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO data_exports (id, user_id)
+            VALUES (?, ?)
+        "#,
+    )
+    .bind(export_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_data_export(
+    export_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<DataExportModel>, sqlx::Error> {
+    let export = sqlx::query_as!(
+        DataExportModel,
+        r#"
+            SELECT *
+            FROM data_exports
+            WHERE id = ?
+        "#,
+        export_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(export)
+}
+
+/// Exports of the user that are queued or running. Runs that started
+/// before `stale_before` are considered crashed and ignored.
+pub async fn get_active_data_export(
+    user_id: &str,
+    stale_before: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<Option<DataExportModel>, sqlx::Error> {
+    let export = sqlx::query_as!(
+        DataExportModel,
+        r#"
+            SELECT *
+            FROM data_exports
+            WHERE user_id = ?
+                AND (status = 'pending' OR (status = 'processing' AND started_at >= ?))
+            LIMIT 1
+        "#,
+        user_id,
+        stale_before,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(export)
+}
+
+/// Queued exports, plus runs that started before `stale_before` and never
+/// finished.
+pub async fn get_pending_data_exports(
+    stale_before: chrono::DateTime<chrono::Utc>,
+    limit: u32,
+    pool: MySqlPool,
+) -> Result<Vec<DataExportModel>, sqlx::Error> {
+    let exports = sqlx::query_as!(
+        DataExportModel,
+        r#"
+            SELECT *
+            FROM data_exports
+            WHERE status = 'pending' OR (status = 'processing' AND started_at < ?)
+            ORDER BY created_at
+            LIMIT ?
+        "#,
+        stale_before,
+        limit,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(exports)
+}
+
+/// Marks the export as running. Returns `false` when another worker got to
+/// it first.
+pub async fn claim_data_export(
+    export_id: &str,
+    stale_before: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE data_exports
+            SET status = 'processing', started_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+                AND (status = 'pending' OR (status = 'processing' AND started_at < ?))
+        "#,
+    )
+    .bind(export_id)
+    .bind(stale_before)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
+
+pub async fn complete_data_export(
+    export_id: &str,
+    storage_key: &str,
+    token_hash: &str,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE data_exports
+            SET status = 'ready', storage_key = ?, token_hash = ?, expires_at = ?,
+                completed_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+        "#,
+    )
+    .bind(storage_key)
+    .bind(token_hash)
+    .bind(expires_at)
+    .bind(export_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn fail_data_export(
+    export_id: &str,
+    error: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE data_exports
+            SET status = 'failed', error = ?, completed_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+        "#,
+    )
+    .bind(error.chars().take(500).collect::<String>())
+    .bind(export_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_expired_data_exports(
+    limit: u32,
+    pool: MySqlPool,
+) -> Result<Vec<DataExportModel>, sqlx::Error> {
+    let exports = sqlx::query_as!(
+        DataExportModel,
+        r#"
+            SELECT *
+            FROM data_exports
+            WHERE status = 'ready' AND expires_at <= CURRENT_TIMESTAMP
+            LIMIT ?
+        "#,
+        limit,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(exports)
+}
+
+pub async fn get_user_data_exports(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<DataExportModel>, sqlx::Error> {
+    let exports = sqlx::query_as!(
+        DataExportModel,
+        r#"
+            SELECT *
+            FROM data_exports
+            WHERE user_id = ?
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(exports)
+}
+
+pub async fn delete_data_export(
+    export_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query("DELETE FROM data_exports WHERE id = ?")
+        .bind(export_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(query_result)
+}
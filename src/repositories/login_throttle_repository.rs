@@ -0,0 +1,118 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool, QueryBuilder};
+
+use crate::models::login_throttle::LoginThrottleModel;
+
+pub async fn get_throttles(
+    keys: &[String],
+    pool: MySqlPool,
+) -> Result<Vec<LoginThrottleModel>, sqlx::Error> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new("SELECT * FROM login_throttles WHERE throttle_key IN (");
+    let mut separated = builder.separated(", ");
+    for key in keys {
+        separated.push_bind(key);
+    }
+    separated.push_unseparated(")");
+
+    let throttles = builder
+        .build_query_as::<LoginThrottleModel>()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(throttles)
+}
+
+/// Counts a failed login and returns the number of failures so far. The
+/// count starts over when the previous failure is older than
+/// `window_minutes`.
+pub async fn record_failure(
+    key: &str,
+    window_minutes: i64,
+    pool: MySqlPool,
+) -> Result<i32, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO login_throttles (throttle_key, failures, last_failure_at)
+            VALUES (?, 1, CURRENT_TIMESTAMP)
+            ON DUPLICATE KEY UPDATE
+                failures = IF(last_failure_at < CURRENT_TIMESTAMP - INTERVAL ? MINUTE, 1, failures + 1),
+                last_failure_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(key)
+    .bind(window_minutes)
+    .execute(&mut *tx)
+    .await?;
+
+    let failures = sqlx::query_scalar!(
+        r#"
+            SELECT failures
+            FROM login_throttles
+            WHERE throttle_key = ?
+        "#,
+        key,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(failures)
+}
+
+/// Refuses logins for the key until `blocked_until`. An existing block that
+/// lasts longer is kept.
+pub async fn block(
+    key: &str,
+    blocked_until: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE login_throttles
+            SET blocked_until = GREATEST(COALESCE(blocked_until, ?), ?)
+            WHERE throttle_key = ?
+        "#,
+    )
+    .bind(blocked_until)
+    .bind(blocked_until)
+    .bind(key)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn delete_throttle(key: &str, pool: MySqlPool) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query("DELETE FROM login_throttles WHERE throttle_key = ?")
+        .bind(key)
+        .execute(&pool)
+        .await?;
+
+    Ok(query_result)
+}
+
+/// Deletes counters whose last failure is older than `window_minutes` and
+/// that no longer block anything.
+pub async fn delete_stale_throttles(
+    window_minutes: i64,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            DELETE FROM login_throttles
+            WHERE last_failure_at < CURRENT_TIMESTAMP - INTERVAL ? MINUTE
+                AND (blocked_until IS NULL OR blocked_until < CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(window_minutes)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
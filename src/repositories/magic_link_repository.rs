@@ -0,0 +1,113 @@
+use sqlx::MySqlPool;
+
+use crate::models::magic_link::MagicLinkModel;
+
+/// Stores a new link and drops the user's earlier ones, so only the latest
+/// email works.
+pub async fn create_magic_link(
+    magic_link: &MagicLinkModel,
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM magic_links WHERE user_id = ?")
+        .bind(&magic_link.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO magic_links (id, user_id, token_hash, code_hash, expires_at)
+            VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&magic_link.id)
+    .bind(&magic_link.user_id)
+    .bind(&magic_link.token_hash)
+    .bind(&magic_link.code_hash)
+    .bind(magic_link.expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+pub async fn get_magic_link_by_hash(
+    token_hash: &str,
+    pool: MySqlPool,
+) -> Result<Option<MagicLinkModel>, sqlx::Error> {
+    let magic_link = sqlx::query_as!(
+        MagicLinkModel,
+        r#"
+            SELECT *
+            FROM magic_links
+            WHERE token_hash = ?
+        "#,
+        token_hash,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(magic_link)
+}
+
+/// The user's link that is still unused and unexpired, if any.
+pub async fn get_active_magic_link(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<MagicLinkModel>, sqlx::Error> {
+    let magic_link = sqlx::query_as!(
+        MagicLinkModel,
+        r#"
+            SELECT *
+            FROM magic_links
+            WHERE user_id = ? AND used_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+            ORDER BY created_at DESC
+            LIMIT 1
+        "#,
+        user_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(magic_link)
+}
+
+/// Counts an attempt at the code, before it is compared. Returns `false`
+/// once `max_attempts` had already been reached; the check and the count are
+/// one statement, so concurrent guesses cannot go past the limit.
+pub async fn reserve_attempt(
+    magic_link_id: &str,
+    max_attempts: i32,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE magic_links
+            SET failed_attempts = failed_attempts + 1
+            WHERE id = ? AND failed_attempts < ?
+        "#,
+    )
+    .bind(magic_link_id)
+    .bind(max_attempts)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() > 0)
+}
+
+/// Marks the link used. Returns `false` when another request used it first.
+pub async fn use_magic_link(magic_link_id: &str, pool: MySqlPool) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE magic_links
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND used_at IS NULL
+        "#,
+    )
+    .bind(magic_link_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() > 0)
+}
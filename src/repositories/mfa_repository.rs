@@ -0,0 +1,212 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::mfa::{MfaRecoveryCodeModel, UserMfaModel};
+
+/// Stores a fresh, unconfirmed secret, replacing any earlier enrollment.
+pub async fn upsert_user_mfa(
+    user_id: &str,
+    secret: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO user_mfa (user_id, secret)
+            VALUES (?, ?)
+            ON DUPLICATE KEY UPDATE
+                secret = VALUES(secret),
+                last_used_step = NULL,
+                pending_token_id = NULL,
+                confirmed_at = NULL,
+                created_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(user_id)
+    .bind(secret)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_user_mfa(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<UserMfaModel>, sqlx::Error> {
+    let user_mfa = sqlx::query_as!(
+        UserMfaModel,
+        r#"
+            SELECT *
+            FROM user_mfa
+            WHERE user_id = ?
+        "#,
+        user_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(user_mfa)
+}
+
+pub async fn confirm_user_mfa(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE user_mfa
+            SET confirmed_at = CURRENT_TIMESTAMP
+            WHERE user_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Records `step` as used. Returns `false` when the same or a later step was
+/// already accepted, which means the code is being replayed.
+pub async fn use_time_step(user_id: &str, step: i64, pool: MySqlPool) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE user_mfa
+            SET last_used_step = ?
+            WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)
+        "#,
+    )
+    .bind(step)
+    .bind(user_id)
+    .bind(step)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
+
+/// Makes `token_id` the only `mfa_pending` token of the user that can be
+/// exchanged, replacing any earlier one.
+pub async fn set_pending_token(
+    user_id: &str,
+    token_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE user_mfa
+            SET pending_token_id = ?
+            WHERE user_id = ?
+        "#,
+    )
+    .bind(token_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Burns the pending token. Returns `false` when it is not the outstanding
+/// one, because it was replaced or another request used it first.
+pub async fn take_pending_token(
+    user_id: &str,
+    token_id: &str,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE user_mfa
+            SET pending_token_id = NULL
+            WHERE user_id = ? AND pending_token_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
+
+pub async fn delete_user_mfa(user_id: &str, pool: MySqlPool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM user_mfa WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn replace_recovery_codes(
+    user_id: &str,
+    code_hashes: &[String],
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for code_hash in code_hashes {
+        sqlx::query(
+            r#"
+                INSERT INTO mfa_recovery_codes (id, user_id, code_hash)
+                VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn get_unused_recovery_codes(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<MfaRecoveryCodeModel>, sqlx::Error> {
+    let recovery_codes = sqlx::query_as!(
+        MfaRecoveryCodeModel,
+        r#"
+            SELECT *
+            FROM mfa_recovery_codes
+            WHERE user_id = ? AND used_at IS NULL
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(recovery_codes)
+}
+
+pub async fn use_recovery_code(code_id: &str, pool: MySqlPool) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE mfa_recovery_codes
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND used_at IS NULL
+        "#,
+    )
+    .bind(code_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
@@ -1,2 +1,15 @@
+pub mod api_key_repository;
 pub mod auth_repository;
+pub mod data_export_repository;
+pub mod login_throttle_repository;
+pub mod magic_link_repository;
+pub mod mfa_repository;
+pub mod organization_repository;
+pub mod password_reset_repository;
+pub mod refresh_token_repository;
+pub mod role_history_repository;
+pub mod role_repository;
+pub mod session_repository;
+pub mod user_deletion_repository;
+pub mod user_identity_repository;
 pub mod user_repository;
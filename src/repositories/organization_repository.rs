@@ -0,0 +1,385 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::organization::{
+    OrganizationInvitationModel, OrganizationMemberModel, OrganizationMemberUserModel,
+    OrganizationModel, OrganizationRole, UserOrganizationModel,
+};
+
+/// Creates the organization and makes `owner_id` its first owner.
+pub async fn create_organization(
+    organization_id: &str,
+    name: &str,
+    slug: &str,
+    owner_id: &str,
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO organizations (id, name, slug, created_by)
+            VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(organization_id)
+    .bind(name)
+    .bind(slug)
+    .bind(owner_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO organization_members (organization_id, user_id, role)
+            VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(organization_id)
+    .bind(owner_id)
+    .bind(OrganizationRole::Owner)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn get_organization(
+    organization_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<OrganizationModel>, sqlx::Error> {
+    let organization = sqlx::query_as!(
+        OrganizationModel,
+        r#"
+            SELECT *
+            FROM organizations
+            WHERE id = ?
+        "#,
+        organization_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(organization)
+}
+
+/// Updates the given fields and leaves the ones passed as `None` untouched.
+pub async fn update_organization(
+    organization_id: &str,
+    name: Option<&str>,
+    slug: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE organizations
+            SET name = COALESCE(?, name), slug = COALESCE(?, slug)
+            WHERE id = ?
+        "#,
+    )
+    .bind(name)
+    .bind(slug)
+    .bind(organization_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn delete_organization(
+    organization_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query("DELETE FROM organizations WHERE id = ?")
+        .bind(organization_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(query_result)
+}
+
+/// Organizations the user belongs to, with the role they hold in each.
+pub async fn get_user_organizations(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<UserOrganizationModel>, sqlx::Error> {
+    let organizations = sqlx::query_as!(
+        UserOrganizationModel,
+        r#"
+            SELECT o.id, o.name, o.slug, m.role as "role: OrganizationRole", o.created_at
+            FROM organization_members m
+            JOIN organizations o ON o.id = m.organization_id
+            WHERE m.user_id = ?
+            ORDER BY o.name
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(organizations)
+}
+
+pub async fn get_membership(
+    organization_id: &str,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<OrganizationMemberModel>, sqlx::Error> {
+    let membership = sqlx::query_as!(
+        OrganizationMemberModel,
+        r#"
+            SELECT *
+            FROM organization_members
+            WHERE organization_id = ? AND user_id = ?
+        "#,
+        organization_id,
+        user_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(membership)
+}
+
+pub async fn get_members(
+    organization_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<OrganizationMemberUserModel>, sqlx::Error> {
+    let members = sqlx::query_as!(
+        OrganizationMemberUserModel,
+        r#"
+            SELECT m.user_id, u.name, u.email, m.role as "role: OrganizationRole", m.created_at
+            FROM organization_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organization_id = ?
+            ORDER BY u.name
+        "#,
+        organization_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(members)
+}
+
+pub async fn count_owners(organization_id: &str, pool: MySqlPool) -> Result<i64, sqlx::Error> {
+    let count = sqlx::query_scalar!(
+        r#"
+            SELECT COUNT(*)
+            FROM organization_members
+            WHERE organization_id = ? AND role = 'owner'
+        "#,
+        organization_id,
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(count)
+}
+
+pub async fn update_member_role(
+    organization_id: &str,
+    user_id: &str,
+    role: OrganizationRole,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE organization_members
+            SET role = ?
+            WHERE organization_id = ? AND user_id = ?
+        "#,
+    )
+    .bind(role)
+    .bind(organization_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Removes the membership and takes the organization out of every session
+/// the user had switched to it.
+pub async fn remove_member(
+    organization_id: &str,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let query_result = sqlx::query(
+        r#"
+            DELETE FROM organization_members
+            WHERE organization_id = ? AND user_id = ?
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+            UPDATE sessions
+            SET active_organization_id = NULL
+            WHERE user_id = ? AND active_organization_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(organization_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(query_result)
+}
+
+pub async fn create_invitation(
+    invitation: &OrganizationInvitationModel,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO organization_invitations
+                (id, organization_id, email, role, token_hash, invited_by, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&invitation.id)
+    .bind(&invitation.organization_id)
+    .bind(&invitation.email)
+    .bind(invitation.role)
+    .bind(&invitation.token_hash)
+    .bind(&invitation.invited_by)
+    .bind(invitation.expires_at)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Invitations that have been neither accepted nor let expire.
+pub async fn get_pending_invitations(
+    organization_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<OrganizationInvitationModel>, sqlx::Error> {
+    let invitations = sqlx::query_as!(
+        OrganizationInvitationModel,
+        r#"
+            SELECT *
+            FROM organization_invitations
+            WHERE organization_id = ? AND accepted_at IS NULL
+                AND expires_at > CURRENT_TIMESTAMP
+            ORDER BY created_at DESC
+        "#,
+        organization_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(invitations)
+}
+
+pub async fn get_invitation_by_hash(
+    token_hash: &str,
+    pool: MySqlPool,
+) -> Result<Option<OrganizationInvitationModel>, sqlx::Error> {
+    let invitation = sqlx::query_as!(
+        OrganizationInvitationModel,
+        r#"
+            SELECT *
+            FROM organization_invitations
+            WHERE token_hash = ?
+        "#,
+        token_hash,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(invitation)
+}
+
+/// Drops earlier pending invitations of the same address, so only the
+/// latest link works.
+pub async fn delete_pending_invitations(
+    organization_id: &str,
+    email: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            DELETE FROM organization_invitations
+            WHERE organization_id = ? AND email = ? AND accepted_at IS NULL
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn delete_invitation(
+    invitation_id: &str,
+    organization_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            DELETE FROM organization_invitations
+            WHERE id = ? AND organization_id = ? AND accepted_at IS NULL
+        "#,
+    )
+    .bind(invitation_id)
+    .bind(organization_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Marks the invitation accepted and adds the user with the invited role.
+/// Returns `false` when another request accepted it first. Someone who is
+/// already a member keeps their current role.
+pub async fn accept_invitation(
+    invitation: &OrganizationInvitationModel,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let accepted = sqlx::query(
+        r#"
+            UPDATE organization_invitations
+            SET accepted_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND accepted_at IS NULL
+        "#,
+    )
+    .bind(&invitation.id)
+    .execute(&mut *tx)
+    .await?;
+
+    if accepted.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+            INSERT IGNORE INTO organization_members (organization_id, user_id, role)
+            VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(&invitation.organization_id)
+    .bind(user_id)
+    .bind(invitation.role)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
@@ -0,0 +1,82 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::password_reset::PasswordResetModel;
+
+pub async fn create_password_reset(
+    reset_id: &str,
+    user_id: &str,
+    token_hash: &str,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO password_resets (id, user_id, token_hash, expires_at)
+            VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(reset_id)
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_password_reset_by_hash(
+    token_hash: &str,
+    pool: MySqlPool,
+) -> Result<Option<PasswordResetModel>, sqlx::Error> {
+    let password_reset = sqlx::query_as!(
+        PasswordResetModel,
+        r#"
+            SELECT *
+            FROM password_resets
+            WHERE token_hash = ?
+        "#,
+        token_hash,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(password_reset)
+}
+
+/// Consumes every outstanding reset of the user. Returns `true` only if
+/// `reset_id` itself was still unused, which makes the token single-use even
+/// when two requests race.
+pub async fn use_password_resets(
+    reset_id: &str,
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE password_resets
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND used_at IS NULL
+        "#,
+    )
+    .bind(reset_id)
+    .execute(&pool)
+    .await?;
+
+    if query_result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+            UPDATE password_resets
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE user_id = ? AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(true)
+}
@@ -0,0 +1,109 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::refresh_token::RefreshTokenModel;
+
+pub async fn create_refresh_token(
+    token_id: &str,
+    user_id: &str,
+    family_id: &str,
+    token_hash: &str,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+            VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(token_id)
+    .bind(user_id)
+    .bind(family_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_refresh_token_by_hash(
+    token_hash: &str,
+    pool: MySqlPool,
+) -> Result<Option<RefreshTokenModel>, sqlx::Error> {
+    let refresh_token = sqlx::query_as!(
+        RefreshTokenModel,
+        r#"
+            SELECT *
+            FROM refresh_tokens
+            WHERE token_hash = ?
+        "#,
+        token_hash,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(refresh_token)
+}
+
+/// Marks a token as rotated. The `revoked_at IS NULL` guard makes this the
+/// single point of truth when two requests race on the same token: only one
+/// of them gets `true` back.
+pub async fn rotate_refresh_token(
+    token_id: &str,
+    replaced_by: &str,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP, replaced_by = ?
+            WHERE id = ? AND revoked_at IS NULL
+        "#,
+    )
+    .bind(replaced_by)
+    .bind(token_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result.rows_affected() == 1)
+}
+
+pub async fn revoke_refresh_token_family(
+    family_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE family_id = ? AND revoked_at IS NULL
+        "#,
+    )
+    .bind(family_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn revoke_user_refresh_tokens(
+    user_id: &str,
+    except_family_id: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE refresh_tokens
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = ? AND (? IS NULL OR family_id <> ?) AND revoked_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(except_family_id)
+    .bind(except_family_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
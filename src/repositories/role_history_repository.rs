@@ -0,0 +1,47 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::role_history::{RoleChange, RoleHistoryModel};
+
+pub async fn create_role_history(
+    user_id: &str,
+    role: &str,
+    action: RoleChange,
+    changed_by: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO role_history (id, user_id, role, action, changed_by)
+            VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(user_id)
+    .bind(role)
+    .bind(action)
+    .bind(changed_by)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_role_history(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<RoleHistoryModel>, sqlx::Error> {
+    let history = sqlx::query_as!(
+        RoleHistoryModel,
+        r#"
+            SELECT *
+            FROM role_history
+            WHERE user_id = ?
+            ORDER BY created_at
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(history)
+}
@@ -0,0 +1,280 @@
+use sqlx::{mysql::MySqlQueryResult, MySql, MySqlPool, QueryBuilder, Transaction};
+
+use crate::models::role::{NamedGrantModel, PermissionModel, RoleModel};
+
+pub async fn get_roles(pool: MySqlPool) -> Result<Vec<RoleModel>, sqlx::Error> {
+    let roles = sqlx::query_as!(
+        RoleModel,
+        r#"
+            SELECT *
+            FROM roles
+            ORDER BY name
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(roles)
+}
+
+pub async fn get_role(role_id: &str, pool: MySqlPool) -> Result<Option<RoleModel>, sqlx::Error> {
+    let role = sqlx::query_as!(
+        RoleModel,
+        r#"
+            SELECT *
+            FROM roles
+            WHERE id = ?
+        "#,
+        role_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(role)
+}
+
+pub async fn get_roles_by_name(
+    names: &[String],
+    pool: MySqlPool,
+) -> Result<Vec<RoleModel>, sqlx::Error> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new("SELECT * FROM roles WHERE name IN (");
+    let mut separated = builder.separated(", ");
+    for name in names {
+        separated.push_bind(name);
+    }
+    separated.push_unseparated(")");
+
+    let roles = builder
+        .build_query_as::<RoleModel>()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(roles)
+}
+
+/// Replaces the permissions of a role with the ones named. Unknown names are
+/// ignored, callers validate them against `get_permissions` first.
+async fn replace_role_permissions(
+    tx: &mut Transaction<'_, MySql>,
+    role_id: &str,
+    permissions: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM role_permissions WHERE role_id = ?")
+        .bind(role_id)
+        .execute(&mut **tx)
+        .await?;
+
+    if permissions.is_empty() {
+        return Ok(());
+    }
+
+    let mut builder = QueryBuilder::new("INSERT INTO role_permissions (role_id, permission_id) ");
+    builder
+        .push("SELECT ")
+        .push_bind(role_id)
+        .push(", id FROM permissions WHERE name IN (");
+    let mut separated = builder.separated(", ");
+    for permission in permissions {
+        separated.push_bind(permission);
+    }
+    separated.push_unseparated(")");
+
+    builder.build().execute(&mut **tx).await?;
+
+    Ok(())
+}
+
+pub async fn create_role(
+    role_id: &str,
+    name: &str,
+    description: Option<&str>,
+    permissions: &[String],
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO roles (id, name, description)
+            VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(role_id)
+    .bind(name)
+    .bind(description)
+    .execute(&mut *tx)
+    .await?;
+
+    replace_role_permissions(&mut tx, role_id, permissions).await?;
+
+    tx.commit().await
+}
+
+/// Updates the given fields and leaves the ones passed as `None` untouched.
+pub async fn update_role(
+    role_id: &str,
+    name: Option<&str>,
+    description: Option<&str>,
+    permissions: Option<&[String]>,
+    pool: MySqlPool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            UPDATE roles
+            SET name = COALESCE(?, name), description = COALESCE(?, description)
+            WHERE id = ?
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .bind(role_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if let Some(permissions) = permissions {
+        replace_role_permissions(&mut tx, role_id, permissions).await?;
+    }
+
+    tx.commit().await
+}
+
+pub async fn delete_role(role_id: &str, pool: MySqlPool) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query("DELETE FROM roles WHERE id = ?")
+        .bind(role_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_permissions(pool: MySqlPool) -> Result<Vec<PermissionModel>, sqlx::Error> {
+    let permissions = sqlx::query_as!(
+        PermissionModel,
+        r#"
+            SELECT *
+            FROM permissions
+            ORDER BY name
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(permissions)
+}
+
+/// Permission names of each role, keyed by `owner_id` = role id.
+pub async fn get_role_permissions(
+    role_ids: &[String],
+    pool: MySqlPool,
+) -> Result<Vec<NamedGrantModel>, sqlx::Error> {
+    if role_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT rp.role_id AS owner_id, p.name FROM role_permissions rp \
+         JOIN permissions p ON p.id = rp.permission_id WHERE rp.role_id IN (",
+    );
+    let mut separated = builder.separated(", ");
+    for role_id in role_ids {
+        separated.push_bind(role_id);
+    }
+    separated.push_unseparated(") ORDER BY p.name");
+
+    let grants = builder
+        .build_query_as::<NamedGrantModel>()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(grants)
+}
+
+/// Role names of each user, keyed by `owner_id` = user id.
+pub async fn get_user_roles(
+    user_ids: &[String],
+    pool: MySqlPool,
+) -> Result<Vec<NamedGrantModel>, sqlx::Error> {
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT ur.user_id AS owner_id, r.name FROM user_roles ur \
+         JOIN roles r ON r.id = ur.role_id WHERE ur.user_id IN (",
+    );
+    let mut separated = builder.separated(", ");
+    for user_id in user_ids {
+        separated.push_bind(user_id);
+    }
+    separated.push_unseparated(") ORDER BY r.name");
+
+    let grants = builder
+        .build_query_as::<NamedGrantModel>()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(grants)
+}
+
+/// Every permission granted to the user through any of their roles.
+pub async fn get_user_permissions(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<String>, sqlx::Error> {
+    let permissions = sqlx::query_scalar!(
+        r#"
+            SELECT DISTINCT p.name
+            FROM user_roles ur
+            JOIN role_permissions rp ON rp.role_id = ur.role_id
+            JOIN permissions p ON p.id = rp.permission_id
+            WHERE ur.user_id = ?
+            ORDER BY p.name
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(permissions)
+}
+
+pub async fn assign_role(
+    user_id: &str,
+    role_id: &str,
+    assigned_by: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT IGNORE INTO user_roles (user_id, role_id, assigned_by)
+            VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(user_id)
+    .bind(role_id)
+    .bind(assigned_by)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn remove_role(
+    user_id: &str,
+    role_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query("DELETE FROM user_roles WHERE user_id = ? AND role_id = ?")
+        .bind(user_id)
+        .bind(role_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(query_result)
+}
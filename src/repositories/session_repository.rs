@@ -0,0 +1,192 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::session::SessionModel;
+
+pub async fn create_session(
+    session_id: &str,
+    user_id: &str,
+    device_name: Option<&str>,
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO sessions (id, user_id, device_name, ip_address, user_agent, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(device_name)
+    .bind(ip_address)
+    .bind(user_agent)
+    .bind(expires_at)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_active_session(
+    session_id: &str,
+    pool: MySqlPool,
+) -> Result<Option<SessionModel>, sqlx::Error> {
+    let session = sqlx::query_as!(
+        SessionModel,
+        r#"
+            SELECT *
+            FROM sessions
+            WHERE id = ? AND revoked_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+        "#,
+        session_id,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(session)
+}
+
+pub async fn get_active_sessions(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<SessionModel>, sqlx::Error> {
+    let sessions = sqlx::query_as!(
+        SessionModel,
+        r#"
+            SELECT *
+            FROM sessions
+            WHERE user_id = ? AND revoked_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+            ORDER BY last_seen_at DESC
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Bumps `last_seen_at`, at most once a minute so that busy clients do not
+/// turn every authenticated request into a write.
+pub async fn touch_session(
+    session_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE sessions
+            SET last_seen_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND last_seen_at < CURRENT_TIMESTAMP - INTERVAL 1 MINUTE
+        "#,
+    )
+    .bind(session_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn extend_session(
+    session_id: &str,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE sessions
+            SET expires_at = ?, last_seen_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+        "#,
+    )
+    .bind(expires_at)
+    .bind(session_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn revoke_session(
+    session_id: &str,
+    user_id: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE sessions
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND (? IS NULL OR user_id = ?) AND revoked_at IS NULL
+        "#,
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn revoke_user_sessions(
+    user_id: &str,
+    except_session_id: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE sessions
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = ? AND (? IS NULL OR id <> ?) AND revoked_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(except_session_id)
+    .bind(except_session_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Every session the user ever opened, including revoked and expired ones.
+pub async fn get_all_sessions(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<SessionModel>, sqlx::Error> {
+    let sessions = sqlx::query_as!(
+        SessionModel,
+        r#"
+            SELECT *
+            FROM sessions
+            WHERE user_id = ?
+            ORDER BY created_at
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+pub async fn set_active_organization(
+    session_id: &str,
+    organization_id: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE sessions
+            SET active_organization_id = ?
+            WHERE id = ?
+        "#,
+    )
+    .bind(organization_id)
+    .bind(session_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
@@ -0,0 +1,47 @@
+use sqlx::MySqlPool;
+
+/// Writes the tombstone and deletes the user in one transaction. Rows that
+/// reference the user are removed by their `ON DELETE CASCADE` keys.
+///
+/// Returns `false` without writing anything when the deletion was cancelled
+/// after the user was picked up for purging.
+pub async fn purge_user(
+    user_id: &str,
+    email_hash: &str,
+    requested_at: Option<chrono::DateTime<chrono::Utc>>,
+    pool: MySqlPool,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+            INSERT INTO user_deletions (id, user_id, email_hash, requested_at)
+            VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(user_id)
+    .bind(email_hash)
+    .bind(requested_at)
+    .execute(&mut *tx)
+    .await?;
+
+    let deleted = sqlx::query(
+        r#"
+            DELETE FROM users
+            WHERE id = ? AND deletion_scheduled_at <= CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if deleted.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    tx.commit().await?;
+
+    Ok(true)
+}
@@ -0,0 +1,101 @@
+use sqlx::{mysql::MySqlQueryResult, MySqlPool};
+
+use crate::models::user_identity::UserIdentityModel;
+
+pub async fn create_identity(
+    identity: &UserIdentityModel,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            INSERT INTO user_identities (id, user_id, provider, subject, email, last_used_at)
+            VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(&identity.id)
+    .bind(&identity.user_id)
+    .bind(&identity.provider)
+    .bind(&identity.subject)
+    .bind(&identity.email)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_identity(
+    provider: &str,
+    subject: &str,
+    pool: MySqlPool,
+) -> Result<Option<UserIdentityModel>, sqlx::Error> {
+    let identity = sqlx::query_as!(
+        UserIdentityModel,
+        r#"
+            SELECT *
+            FROM user_identities
+            WHERE provider = ? AND subject = ?
+        "#,
+        provider,
+        subject,
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(identity)
+}
+
+pub async fn get_user_identities(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<Vec<UserIdentityModel>, sqlx::Error> {
+    let identities = sqlx::query_as!(
+        UserIdentityModel,
+        r#"
+            SELECT *
+            FROM user_identities
+            WHERE user_id = ?
+            ORDER BY provider
+        "#,
+        user_id,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(identities)
+}
+
+/// Records a sign-in and keeps the email in step with the provider.
+pub async fn touch_identity(
+    identity_id: &str,
+    email: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE user_identities
+            SET last_used_at = CURRENT_TIMESTAMP, email = COALESCE(?, email)
+            WHERE id = ?
+        "#,
+    )
+    .bind(email)
+    .bind(identity_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn delete_identity(
+    user_id: &str,
+    provider: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result =
+        sqlx::query("DELETE FROM user_identities WHERE user_id = ? AND provider = ?")
+            .bind(user_id)
+            .bind(provider)
+            .execute(&pool)
+            .await?;
+
+    Ok(query_result)
+}
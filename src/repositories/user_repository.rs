@@ -1,6 +1,9 @@
-use sqlx::MySqlPool;
+use sqlx::{mysql::MySqlQueryResult, MySql, MySqlPool, QueryBuilder};
 
-use crate::models::user::UserModel;
+use crate::{
+    models::user::{UserModel, UserStatus},
+    schemas::admin::{AdminUpdateUserSchema, UserListQuery},
+};
 
 pub async fn get_user(
     user_id: Option<&str>,
@@ -24,3 +27,309 @@ pub async fn get_user(
 
     Ok(user)
 }
+
+pub async fn verify_user(user_id: &str, pool: MySqlPool) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET verified = TRUE
+            WHERE id = ?
+        "#,
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn update_password(
+    user_id: &str,
+    hashed_password: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET password = ?, has_password = TRUE
+            WHERE id = ?
+        "#,
+    )
+    .bind(hashed_password)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn set_mfa_enabled(
+    user_id: &str,
+    enabled: bool,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET mfa_enabled = ?
+            WHERE id = ?
+        "#,
+    )
+    .bind(enabled)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Updates the given profile fields and leaves the ones passed as `None`
+/// untouched.
+pub async fn update_profile(
+    user_id: &str,
+    name: Option<&str>,
+    photo: Option<&str>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET name = COALESCE(?, name), photo = COALESCE(?, photo)
+            WHERE id = ?
+        "#,
+    )
+    .bind(name)
+    .bind(photo)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn update_photo(
+    user_id: &str,
+    photo: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET photo = ?
+            WHERE id = ?
+        "#,
+    )
+    .bind(photo)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Appends the `WHERE` clause shared by the list and count queries.
+fn push_user_filters<'a>(builder: &mut QueryBuilder<'a, MySql>, query: &'a UserListQuery) {
+    builder.push(" WHERE 1 = 1");
+
+    if let Some(role) = query.role.as_deref() {
+        builder
+            .push(
+                " AND id IN (SELECT ur.user_id FROM user_roles ur \
+                 JOIN roles r ON r.id = ur.role_id WHERE r.name = ",
+            )
+            .push_bind(role)
+            .push(")");
+    }
+    if let Some(status) = query.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+    if let Some(verified) = query.verified {
+        builder.push(" AND verified = ").push_bind(verified);
+    }
+    if let Some(created_from) = query.created_from {
+        builder.push(" AND created_at >= ").push_bind(created_from);
+    }
+    if let Some(created_to) = query.created_to {
+        builder.push(" AND created_at < ").push_bind(created_to);
+    }
+    if let Some(search) = query.search.as_deref().filter(|s| !s.trim().is_empty()) {
+        let pattern = format!(
+            "%{}%",
+            search
+                .trim()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        builder
+            .push(" AND (name LIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR email LIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+}
+
+pub async fn get_users(
+    query: &UserListQuery,
+    pool: MySqlPool,
+) -> Result<Vec<UserModel>, sqlx::Error> {
+    let sort = query.sort.unwrap_or_default();
+    let order = query.order.unwrap_or_default();
+
+    let mut builder = QueryBuilder::new("SELECT * FROM users");
+    push_user_filters(&mut builder, query);
+    // `id` breaks ties so pages never overlap or skip rows.
+    builder
+        .push(format!(
+            " ORDER BY {} {}, id {}",
+            sort.column(),
+            order.to_sql(),
+            order.to_sql()
+        ))
+        .push(" LIMIT ")
+        .push_bind(query.limit())
+        .push(" OFFSET ")
+        .push_bind((query.page() as u64 - 1) * query.limit() as u64);
+
+    let users = builder
+        .build_query_as::<UserModel>()
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(users)
+}
+
+pub async fn count_users(query: &UserListQuery, pool: MySqlPool) -> Result<i64, sqlx::Error> {
+    let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM users");
+    push_user_filters(&mut builder, query);
+
+    let count = builder.build_query_scalar::<i64>().fetch_one(&pool).await?;
+
+    Ok(count)
+}
+
+/// Updates the given fields and leaves the ones passed as `None` untouched.
+pub async fn admin_update_user(
+    user_id: &str,
+    body: &AdminUpdateUserSchema,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET name = COALESCE(?, name), email = COALESCE(?, email),
+                verified = COALESCE(?, verified)
+            WHERE id = ?
+        "#,
+    )
+    .bind(body.name.as_deref().map(str::trim))
+    .bind(body.email.as_deref().map(str::trim))
+    .bind(body.verified)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn set_status(
+    user_id: &str,
+    status: UserStatus,
+    reason: Option<&str>,
+    changed_by: Option<&str>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET status = ?, status_reason = ?, status_changed_by = ?, status_expires_at = ?
+            WHERE id = ?
+        "#,
+    )
+    .bind(status)
+    .bind(reason)
+    .bind(changed_by)
+    .bind(expires_at)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+/// Reactivates every account whose suspension or ban has run out.
+pub async fn lift_expired_statuses(pool: MySqlPool) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET status = 'active', status_reason = NULL, status_changed_by = NULL,
+                status_expires_at = NULL
+            WHERE status <> 'active' AND status_expires_at <= CURRENT_TIMESTAMP
+        "#,
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn schedule_deletion(
+    user_id: &str,
+    scheduled_at: chrono::DateTime<chrono::Utc>,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET deletion_requested_at = CURRENT_TIMESTAMP, deletion_scheduled_at = ?
+            WHERE id = ?
+        "#,
+    )
+    .bind(scheduled_at)
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn cancel_deletion(
+    user_id: &str,
+    pool: MySqlPool,
+) -> Result<MySqlQueryResult, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+            UPDATE users
+            SET deletion_requested_at = NULL, deletion_scheduled_at = NULL
+            WHERE id = ?
+        "#,
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(query_result)
+}
+
+pub async fn get_users_due_for_purge(
+    limit: u32,
+    pool: MySqlPool,
+) -> Result<Vec<UserModel>, sqlx::Error> {
+    let users = sqlx::query_as!(
+        UserModel,
+        r#"
+            SELECT *
+            FROM users
+            WHERE deletion_scheduled_at <= CURRENT_TIMESTAMP
+            ORDER BY deletion_scheduled_at
+            LIMIT ?
+        "#,
+        limit,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(users)
+}
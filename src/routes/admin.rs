@@ -0,0 +1,44 @@
+use actix_web::web;
+
+use crate::{
+    handlers::admin_handler::{
+        clear_status_handler, create_role_handler, delete_role_handler, get_role_handler,
+        get_user_handler, list_permissions_handler, list_roles_handler, list_users_handler,
+        set_roles_handler, set_status_handler, unlock_user_handler, update_role_handler,
+        update_user_handler,
+    },
+    utils::{extractor::RequireAuth, scope},
+};
+
+pub fn admin_config(conf: &mut web::ServiceConfig) {
+    // Reading needs the scope-level permission, handlers that write check
+    // the matching `:write` permission themselves.
+    let scope = web::scope("/api/admin")
+        .service(
+            web::scope("/users")
+                .wrap(RequireAuth::permissions(&[scope::USERS_READ]))
+                .route("", web::get().to(list_users_handler))
+                .route("/{id}", web::get().to(get_user_handler))
+                .route("/{id}", web::patch().to(update_user_handler))
+                .route("/{id}/roles", web::put().to(set_roles_handler))
+                .route("/{id}/status", web::put().to(set_status_handler))
+                .route("/{id}/status", web::delete().to(clear_status_handler))
+                .route("/{id}/lockout", web::delete().to(unlock_user_handler)),
+        )
+        .service(
+            web::scope("/roles")
+                .wrap(RequireAuth::permissions(&[scope::ROLES_READ]))
+                .route("", web::get().to(list_roles_handler))
+                .route("", web::post().to(create_role_handler))
+                .route("/{id}", web::get().to(get_role_handler))
+                .route("/{id}", web::patch().to(update_role_handler))
+                .route("/{id}", web::delete().to(delete_role_handler)),
+        )
+        .service(
+            web::resource("/permissions")
+                .wrap(RequireAuth::permissions(&[scope::ROLES_READ]))
+                .route(web::get().to(list_permissions_handler)),
+        );
+
+    conf.service(scope);
+}
@@ -1,25 +1,81 @@
+use std::time::Duration;
+
 use actix_web::web;
 
 use crate::{
-    handlers::auth_handler::{login_user_handler, logout_user_handler, register_user_handler},
-    models::user::UserRole,
-    utils::extractor::RequireAuth,
+    handlers::auth_handler::{
+        forgot_password_handler, login_user_handler, logout_user_handler, oidc_login_handler,
+        refresh_token_handler, register_user_handler, request_magic_link_handler,
+        resend_verification_handler, reset_password_handler, verify_email_handler,
+        verify_magic_link_handler, verify_mfa_handler,
+    },
+    utils::rate_limit::RateLimit,
 };
 
+/// Shared by every route that takes a credential, so a client cannot
+/// spread its guesses over login, codes and reset tokens.
+fn credentials_limit() -> RateLimit {
+    RateLimit::per_ip("auth", 30, Duration::from_secs(60))
+}
+
 pub fn auth_config(conf: &mut web::ServiceConfig) {
-    let scope = web::scope("/auth")
-        .route("/register", web::post().to(register_user_handler))
-        .route("/login", web::post().to(login_user_handler))
+    let scope = web::scope("/api/auth")
+        .route(
+            "/register",
+            web::post()
+                .to(register_user_handler)
+                .wrap(credentials_limit()),
+        )
+        .route(
+            "/login",
+            web::post().to(login_user_handler).wrap(credentials_limit()),
+        )
+        .route(
+            "/magic-link",
+            web::post()
+                .to(request_magic_link_handler)
+                .wrap(credentials_limit()),
+        )
+        .route(
+            "/magic-link/verify",
+            web::post()
+                .to(verify_magic_link_handler)
+                .wrap(credentials_limit()),
+        )
+        .route(
+            "/oidc/{provider}",
+            web::post().to(oidc_login_handler).wrap(credentials_limit()),
+        )
+        .route(
+            "/mfa/verify",
+            web::post().to(verify_mfa_handler).wrap(credentials_limit()),
+        )
+        .route("/refresh", web::post().to(refresh_token_handler))
+        .route(
+            "/verify-email",
+            web::post()
+                .to(verify_email_handler)
+                .wrap(credentials_limit()),
+        )
+        .route(
+            "/resend-verification",
+            web::post()
+                .to(resend_verification_handler)
+                .wrap(credentials_limit()),
+        )
+        .route(
+            "/forgot-password",
+            web::post()
+                .to(forgot_password_handler)
+                .wrap(credentials_limit()),
+        )
         .route(
-            "/logout",
+            "/reset-password",
             web::post()
-                .to(logout_user_handler)
-                .wrap(RequireAuth::allowed_roles(vec![
-                    UserRole::User,
-                    UserRole::Moderator,
-                    UserRole::Admin,
-                ])),
-        );
+                .to(reset_password_handler)
+                .wrap(credentials_limit()),
+        )
+        .route("/logout", web::post().to(logout_user_handler));
 
     conf.service(scope);
 }
@@ -0,0 +1,12 @@
+use actix_web::web;
+
+use crate::handlers::data_export_handler::download_export_handler;
+
+/// The download link is opened from an email, so it authenticates with the
+/// token in the link instead of a session.
+pub fn export_config(conf: &mut web::ServiceConfig) {
+    let scope =
+        web::scope("/api/exports").route("/{id}/download", web::get().to(download_export_handler));
+
+    conf.service(scope);
+}
@@ -1,2 +1,6 @@
+pub mod admin;
 pub mod auth;
+pub mod export;
+pub mod organization;
 pub mod user;
+pub mod well_known;
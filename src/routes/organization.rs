@@ -0,0 +1,57 @@
+use actix_web::web;
+
+use crate::{
+    handlers::organization_handler::{
+        accept_invitation_handler, create_organization_handler, delete_organization_handler,
+        get_organization_handler, invite_member_handler, list_invitations_handler,
+        list_members_handler, list_organizations_handler, remove_member_handler,
+        revoke_invitation_handler, switch_organization_handler, update_member_handler,
+        update_organization_handler,
+    },
+    utils::extractor::RequireAuth,
+};
+
+pub fn organization_config(conf: &mut web::ServiceConfig) {
+    // Routes under `/{org_id}` resolve the caller's membership through
+    // `CurrentMembership`, which also rejects tokens switched to another
+    // organization. Switching itself only needs a membership. API keys have
+    // no session to switch, so they cannot act in an organization.
+    let scope = web::scope("/api/organizations")
+        .wrap(RequireAuth::authenticated().without_api_keys())
+        .route("", web::get().to(list_organizations_handler))
+        .route("", web::post().to(create_organization_handler))
+        .route(
+            "/invitations/accept",
+            web::post().to(accept_invitation_handler),
+        )
+        .route("/{org_id}", web::get().to(get_organization_handler))
+        .route("/{org_id}", web::patch().to(update_organization_handler))
+        .route("/{org_id}", web::delete().to(delete_organization_handler))
+        .route(
+            "/{org_id}/switch",
+            web::post().to(switch_organization_handler),
+        )
+        .route("/{org_id}/members", web::get().to(list_members_handler))
+        .route(
+            "/{org_id}/members/{user_id}",
+            web::patch().to(update_member_handler),
+        )
+        .route(
+            "/{org_id}/members/{user_id}",
+            web::delete().to(remove_member_handler),
+        )
+        .route(
+            "/{org_id}/invitations",
+            web::get().to(list_invitations_handler),
+        )
+        .route(
+            "/{org_id}/invitations",
+            web::post().to(invite_member_handler),
+        )
+        .route(
+            "/{org_id}/invitations/{id}",
+            web::delete().to(revoke_invitation_handler),
+        );
+
+    conf.service(scope);
+}
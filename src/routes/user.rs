@@ -1,9 +1,106 @@
+use std::time::Duration;
+
 use actix_web::web;
 
-use crate::handlers::user_handler::get_me_handler;
+use crate::{
+    handlers::api_key_handler::{
+        create_api_key_handler, delete_api_key_handler, list_api_keys_handler,
+    },
+    handlers::data_export_handler::{get_export_handler, request_export_handler},
+    handlers::identity_handler::{
+        link_identity_handler, list_identities_handler, unlink_identity_handler,
+    },
+    handlers::mfa_handler::{confirm_mfa_handler, disable_mfa_handler, enroll_mfa_handler},
+    handlers::user_handler::{
+        change_password_handler, delete_me_handler, get_me_handler, get_sessions_handler,
+        revoke_other_sessions_handler, revoke_session_handler, update_me_handler,
+        upload_photo_handler,
+    },
+    utils::{extractor::RequireAuth, rate_limit::RateLimit, scope},
+};
+
+fn require(permission: &str) -> RequireAuth {
+    RequireAuth::permissions(&[permission])
+}
 
 pub fn auth_config(conf: &mut web::ServiceConfig) {
-    let scope = web::scope("/api/users").route("/me", web::get().to(get_me_handler));
+    let scope = web::scope("/api/users")
+        // Reachable before two-factor authentication is enabled so accounts
+        // whose role requires it can enroll.
+        .service(
+            web::scope("/me/mfa")
+                .wrap(
+                    require(scope::PROFILE_WRITE)
+                        .allow_without_mfa()
+                        .without_api_keys(),
+                )
+                .route("", web::post().to(enroll_mfa_handler))
+                .route("", web::delete().to(disable_mfa_handler))
+                .route("/confirm", web::post().to(confirm_mfa_handler)),
+        )
+        // Securing the account takes a signed-in user, an API key can
+        // neither manage keys nor touch passwords and sessions.
+        .service(
+            web::scope("/me/api-keys")
+                .wrap(require(scope::PROFILE_WRITE).without_api_keys())
+                .route("", web::get().to(list_api_keys_handler))
+                .route("", web::post().to(create_api_key_handler))
+                .route("/{id}", web::delete().to(delete_api_key_handler)),
+        )
+        .service(
+            web::scope("/me/identities")
+                .wrap(require(scope::PROFILE_WRITE).without_api_keys())
+                .route("", web::get().to(list_identities_handler))
+                .route("/{provider}", web::post().to(link_identity_handler))
+                .route("/{provider}", web::delete().to(unlink_identity_handler)),
+        )
+        .service(
+            web::scope("/me/sessions")
+                .wrap(require(scope::PROFILE_WRITE).without_api_keys())
+                .route("", web::get().to(get_sessions_handler))
+                .route("", web::delete().to(revoke_other_sessions_handler))
+                .route("/{id}", web::delete().to(revoke_session_handler)),
+        )
+        .service(
+            web::resource("/me/password")
+                .wrap(require(scope::PROFILE_WRITE).without_api_keys())
+                .route(web::patch().to(change_password_handler)),
+        )
+        .service(
+            web::resource("/me")
+                .route(
+                    web::get()
+                        .to(get_me_handler)
+                        .wrap(require(scope::PROFILE_READ).allow_without_mfa()),
+                )
+                .route(
+                    web::patch()
+                        .to(update_me_handler)
+                        .wrap(require(scope::PROFILE_WRITE)),
+                )
+                // Deleting an account must never be blocked on enrolling 2FA.
+                .route(
+                    web::delete().to(delete_me_handler).wrap(
+                        require(scope::PROFILE_WRITE)
+                            .allow_without_mfa()
+                            .without_api_keys(),
+                    ),
+                ),
+        )
+        .service(
+            // Uploads and exports are heavy, each key or user gets its own
+            // allowance. Wrapped inside `require` so the caller is known.
+            web::scope("/me")
+                .wrap(RateLimit::per_api_key(
+                    "uploads",
+                    20,
+                    Duration::from_secs(60),
+                ))
+                .wrap(require(scope::PROFILE_WRITE))
+                .route("/photo", web::post().to(upload_photo_handler))
+                .route("/export", web::post().to(request_export_handler))
+                .route("/export/{id}", web::get().to(get_export_handler)),
+        );
 
-    conf.service(scope)
+    conf.service(scope);
 }
@@ -0,0 +1,9 @@
+use actix_web::web;
+
+use crate::handlers::well_known_handler::jwks_handler;
+
+pub fn well_known_config(conf: &mut web::ServiceConfig) {
+    let scope = web::scope("/.well-known").route("/jwks.json", web::get().to(jwks_handler));
+
+    conf.service(scope);
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+use crate::models::user::UserStatus;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum UserSortField {
+    #[default]
+    CreatedAt,
+    Name,
+    Email,
+    Role,
+}
+
+impl UserSortField {
+    /// SQL expression the field sorts on. Only ever interpolated from this
+    /// match, so it is safe to put into SQL.
+    pub fn column(&self) -> &'static str {
+        match self {
+            UserSortField::CreatedAt => "created_at",
+            UserSortField::Name => "name",
+            UserSortField::Email => "email",
+            // Users can hold several roles, sort on the first by name.
+            UserSortField::Role => {
+                "(SELECT MIN(r.name) FROM user_roles ur \
+                 JOIN roles r ON r.id = ur.role_id WHERE ur.user_id = users.id)"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Validate, Debug, Default, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[validate(schema(function = "validate_created_range"))]
+pub struct UserListQuery {
+    /// Page number, starting at 1.
+    #[validate(range(min = 1, message = "Page must be at least 1"))]
+    pub page: Option<u32>,
+    /// Users per page, at most 100.
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    pub limit: Option<u32>,
+    /// Name of a role the users must hold.
+    pub role: Option<String>,
+    pub status: Option<UserStatus>,
+    pub verified: Option<bool>,
+    /// Only users created at or after this instant (RFC 3339).
+    #[serde(rename = "createdFrom")]
+    pub created_from: Option<DateTime<Utc>>,
+    /// Only users created before this instant (RFC 3339).
+    #[serde(rename = "createdTo")]
+    pub created_to: Option<DateTime<Utc>>,
+    /// Case-insensitive match on name or email.
+    #[validate(length(max = 100, message = "Search must not be more than 100 characters"))]
+    pub search: Option<String>,
+    #[param(inline)]
+    pub sort: Option<UserSortField>,
+    #[param(inline)]
+    pub order: Option<SortOrder>,
+}
+
+impl UserListQuery {
+    pub const DEFAULT_LIMIT: u32 = 20;
+
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1)
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT)
+    }
+}
+
+fn validate_created_range(query: &UserListQuery) -> Result<(), ValidationError> {
+    if let (Some(from), Some(to)) = (query.created_from, query.created_to) {
+        if from > to {
+            let mut error = ValidationError::new("created_range");
+            error.message = Some("createdFrom must not be after createdTo".into());
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_admin_update_user"))]
+pub struct AdminUpdateUserSchema {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1 to 100 characters"))]
+    pub name: Option<String>,
+    #[validate(email(message = "Email is invalid"))]
+    pub email: Option<String>,
+    pub verified: Option<bool>,
+}
+
+fn validate_admin_update_user(body: &AdminUpdateUserSchema) -> Result<(), ValidationError> {
+    if body.name.is_none() && body.email.is_none() && body.verified.is_none() {
+        let mut error = ValidationError::new("empty_update");
+        error.message = Some("Provide a name, email or verified flag to update".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetUserRolesSchema {
+    /// Names of every role the user should hold afterwards.
+    #[validate(length(min = 1, message = "Provide at least one role"))]
+    pub roles: Vec<String>,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_set_user_status"))]
+pub struct SetUserStatusSchema {
+    /// `suspended` or `banned`.
+    pub status: UserStatus,
+    #[validate(length(min = 1, max = 500, message = "Reason must be 1 to 500 characters"))]
+    pub reason: String,
+    /// When the status lifts by itself. Omit to keep it until cleared.
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn validate_set_user_status(body: &SetUserStatusSchema) -> Result<(), ValidationError> {
+    if let Some(expires_at) = body.expires_at {
+        if expires_at <= Utc::now() {
+            let mut error = ValidationError::new("expires_at");
+            error.message = Some("expiresAt must be in the future".into());
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_role_name(name: &str) -> Result<(), ValidationError> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        && name.starts_with(|c: char| c.is_ascii_lowercase());
+
+    if !valid {
+        let mut error = ValidationError::new("role_name");
+        error.message = Some(
+            "Role name must start with a lowercase letter and contain only lowercase letters, digits, _ and -"
+                .into(),
+        );
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateRoleSchema {
+    #[validate(
+        length(min = 1, max = 50, message = "Role name must be 1 to 50 characters"),
+        custom = "validate_role_name"
+    )]
+    pub name: String,
+    #[validate(length(
+        max = 255,
+        message = "Description must not be more than 255 characters"
+    ))]
+    pub description: Option<String>,
+    /// Names of the permissions the role grants.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_update_role"))]
+pub struct UpdateRoleSchema {
+    #[validate(
+        length(min = 1, max = 50, message = "Role name must be 1 to 50 characters"),
+        custom = "validate_role_name"
+    )]
+    pub name: Option<String>,
+    #[validate(length(
+        max = 255,
+        message = "Description must not be more than 255 characters"
+    ))]
+    pub description: Option<String>,
+    /// Replaces every permission of the role when given.
+    pub permissions: Option<Vec<String>>,
+}
+
+fn validate_update_role(body: &UpdateRoleSchema) -> Result<(), ValidationError> {
+    if body.name.is_none() && body.description.is_none() && body.permissions.is_none() {
+        let mut error = ValidationError::new("empty_update");
+        error.message = Some("Provide a name, description or permissions to update".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
@@ -36,4 +36,118 @@ pub struct LoginUserSchema {
         length(min = 6, message = "Password must be at least 6 characters")
     )]
     pub password: String,
+    #[validate(length(
+        max = 100,
+        message = "Device name must not be more than 100 characters"
+    ))]
+    #[serde(rename = "deviceName")]
+    pub device_name: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OidcLoginSchema {
+    #[validate(length(min = 1, message = "ID token is required"))]
+    #[serde(rename = "idToken")]
+    pub id_token: String,
+    /// Nonce the app passed to the provider, checked against the token.
+    pub nonce: Option<String>,
+    #[validate(length(
+        max = 100,
+        message = "Device name must not be more than 100 characters"
+    ))]
+    #[serde(rename = "deviceName")]
+    pub device_name: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RefreshTokenSchema {
+    #[validate(length(min = 1, message = "Refresh token is required"))]
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VerifyEmailSchema {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResendVerificationSchema {
+    #[validate(
+        length(min = 1, message = "Email is required"),
+        email(message = "Email is invalid")
+    )]
+    pub email: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ForgotPasswordSchema {
+    #[validate(
+        length(min = 1, message = "Email is required"),
+        email(message = "Email is invalid")
+    )]
+    pub email: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResetPasswordSchema {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+    #[validate(
+        length(min = 1, message = "Password is required"),
+        length(min = 6, message = "Password must be at least 6 characters")
+    )]
+    pub password: String,
+    #[validate(
+        length(min = 1, message = "Please confirm your password"),
+        must_match(other = "password", message = "Passwords do not match")
+    )]
+    #[serde(rename = "passwordConfirm")]
+    pub password_confirm: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VerifyMfaSchema {
+    #[validate(length(min = 1, message = "MFA token is required"))]
+    #[serde(rename = "mfaToken")]
+    pub mfa_token: String,
+    #[validate(length(equal = 6, message = "Code must be 6 digits"))]
+    pub code: Option<String>,
+    #[validate(length(min = 1, message = "Recovery code is required"))]
+    #[serde(rename = "recoveryCode")]
+    pub recovery_code: Option<String>,
+    #[validate(length(
+        max = 100,
+        message = "Device name must not be more than 100 characters"
+    ))]
+    #[serde(rename = "deviceName")]
+    pub device_name: Option<String>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MagicLinkSchema {
+    #[validate(
+        length(min = 1, message = "Email is required"),
+        email(message = "Email is invalid")
+    )]
+    pub email: String,
+}
+
+/// Either the token from the emailed link, or the email together with the
+/// code from the same email.
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VerifyMagicLinkSchema {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: Option<String>,
+    #[validate(email(message = "Email is invalid"))]
+    pub email: Option<String>,
+    #[validate(length(equal = 6, message = "Code must be 6 digits"))]
+    pub code: Option<String>,
+    #[validate(length(
+        max = 100,
+        message = "Device name must not be more than 100 characters"
+    ))]
+    #[serde(rename = "deviceName")]
+    pub device_name: Option<String>,
 }
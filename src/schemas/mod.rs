@@ -1 +1,4 @@
+pub mod admin;
 pub mod auth;
+pub mod organization;
+pub mod user;
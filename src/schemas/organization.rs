@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::models::organization::OrganizationRole;
+
+fn validate_slug(slug: &str) -> Result<(), ValidationError> {
+    let valid = slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+        && !slug.ends_with('-');
+
+    if !valid {
+        let mut error = ValidationError::new("slug");
+        error.message = Some(
+            "Slug must contain only lowercase letters, digits and -, and not start or end with -"
+                .into(),
+        );
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateOrganizationSchema {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1 to 100 characters"))]
+    pub name: String,
+    /// Derived from the name when omitted.
+    #[validate(
+        length(min = 1, max = 100, message = "Slug must be 1 to 100 characters"),
+        custom = "validate_slug"
+    )]
+    pub slug: Option<String>,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_update_organization"))]
+pub struct UpdateOrganizationSchema {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1 to 100 characters"))]
+    pub name: Option<String>,
+    #[validate(
+        length(min = 1, max = 100, message = "Slug must be 1 to 100 characters"),
+        custom = "validate_slug"
+    )]
+    pub slug: Option<String>,
+}
+
+fn validate_update_organization(body: &UpdateOrganizationSchema) -> Result<(), ValidationError> {
+    if body.name.is_none() && body.slug.is_none() {
+        let mut error = ValidationError::new("empty_update");
+        error.message = Some("Provide a name or a slug to update".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct InviteMemberSchema {
+    #[validate(
+        length(min = 1, message = "Email is required"),
+        email(message = "Email is invalid")
+    )]
+    pub email: String,
+    /// Role the invitee gets on accepting, `member` when omitted.
+    #[serde(default = "default_invitation_role")]
+    pub role: OrganizationRole,
+}
+
+fn default_invitation_role() -> OrganizationRole {
+    OrganizationRole::Member
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateMemberSchema {
+    pub role: OrganizationRole,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AcceptInvitationSchema {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
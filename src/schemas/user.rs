@@ -0,0 +1,126 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_update_profile"))]
+pub struct UpdateProfileSchema {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1 to 100 characters"))]
+    #[serde(default, deserialize_with = "trimmed")]
+    pub name: Option<String>,
+    #[validate(
+        length(max = 255, message = "Photo must not be more than 255 characters"),
+        url(message = "Photo must be a valid URL")
+    )]
+    pub photo: Option<String>,
+}
+
+/// Trims surrounding whitespace while deserializing, so that `length` sees
+/// the value that gets stored and a blank name fails `min = 1`.
+fn trimmed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+
+    Ok(value.map(|value| value.trim().to_string()))
+}
+
+fn validate_update_profile(body: &UpdateProfileSchema) -> Result<(), ValidationError> {
+    if body.name.is_none() && body.photo.is_none() {
+        let mut error = ValidationError::new("empty_update");
+        error.message = Some("Provide a name or a photo to update".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Multipart body of `POST /api/users/me/photo`. Only used for the OpenAPI
+/// document, the handler reads the stream itself.
+#[derive(Debug, ToSchema)]
+pub struct UploadPhotoSchema {
+    #[schema(value_type = String, format = Binary)]
+    pub photo: Vec<u8>,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChangePasswordSchema {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    #[serde(rename = "currentPassword")]
+    pub current_password: String,
+    #[validate(
+        length(min = 1, message = "New password is required"),
+        length(min = 6, message = "New password must be at least 6 characters")
+    )]
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+    #[validate(
+        length(min = 1, message = "Please confirm your new password"),
+        must_match(other = "new_password", message = "Passwords do not match")
+    )]
+    #[serde(rename = "newPasswordConfirm")]
+    pub new_password_confirm: String,
+    /// Revoke every session except the one making the request.
+    #[serde(rename = "signOutOtherSessions", default)]
+    pub sign_out_other_sessions: bool,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ConfirmMfaSchema {
+    #[validate(length(equal = 6, message = "Code must be 6 digits"))]
+    pub code: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DisableMfaSchema {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    #[serde(rename = "currentPassword")]
+    pub current_password: String,
+    #[validate(length(equal = 6, message = "Code must be 6 digits"))]
+    pub code: String,
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeleteAccountSchema {
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DownloadExportQuery {
+    /// Token from the download link in the email.
+    pub token: String,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_create_api_key"))]
+pub struct CreateApiKeySchema {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1 to 100 characters"))]
+    pub name: String,
+    /// Permissions the key grants. Each must be one the user holds.
+    #[validate(length(min = 1, message = "Provide at least one scope"))]
+    pub scopes: Vec<String>,
+    /// When the key stops working. Omit for a key that never expires.
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn validate_create_api_key(body: &CreateApiKeySchema) -> Result<(), ValidationError> {
+    if let Some(expires_at) = body.expires_at {
+        if expires_at <= chrono::Utc::now() {
+            let mut error = ValidationError::new("expires_at");
+            error.message = Some("expiresAt must be in the future".into());
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Validate, Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LinkIdentitySchema {
+    #[validate(length(min = 1, message = "ID token is required"))]
+    #[serde(rename = "idToken")]
+    pub id_token: String,
+    /// Nonce the app passed to the provider, checked against the token.
+    pub nonce: Option<String>,
+}
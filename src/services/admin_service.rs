@@ -0,0 +1,221 @@
+use sqlx::MySqlPool;
+
+use super::{
+    login_throttle_service::{self, LoginThrottleService},
+    role_service,
+    session_service::SessionService,
+};
+
+use crate::{
+    models::{
+        role_history::RoleChange,
+        user::{UserModel, UserStatus},
+    },
+    repositories::{role_history_repository, role_repository, user_repository},
+    schemas::admin::{AdminUpdateUserSchema, SetUserStatusSchema, UserListQuery},
+    utils::error::AppError,
+};
+
+#[derive(Debug)]
+pub struct AdminService {
+    pool: MySqlPool,
+}
+
+impl AdminService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns one page of users matching `query` and the total number of
+    /// matches across all pages.
+    pub async fn list_users(
+        &self,
+        query: &UserListQuery,
+    ) -> Result<(Vec<UserModel>, i64), AppError> {
+        let users = user_repository::get_users(query, self.pool.clone()).await?;
+
+        let total = user_repository::count_users(query, self.pool.clone()).await?;
+
+        Ok((users, total))
+    }
+
+    pub async fn get_user(&self, user_id: &str) -> Result<UserModel, AppError> {
+        user_repository::get_user(Some(user_id), None, None, self.pool.clone())
+            .await?
+            .ok_or(AppError::UserNotFound)
+    }
+
+    pub async fn update_user(
+        &self,
+        user_id: &str,
+        body: &AdminUpdateUserSchema,
+    ) -> Result<UserModel, AppError> {
+        self.get_user(user_id).await?;
+
+        user_repository::admin_update_user(user_id, body, self.pool.clone())
+            .await
+            .map_err(|e| AppError::from(e).on_conflict(AppError::EmailExist))?;
+
+        self.get_user(user_id).await
+    }
+
+    /// Replaces the roles of another user with `role_names`, recording
+    /// every assignment and removal. Admins cannot change their own roles so
+    /// the last admin can never lock everyone out of this API. Roles can
+    /// only be assigned when `granted`, the admin's scope, covers all their
+    /// permissions.
+    pub async fn set_roles(
+        &self,
+        admin: &UserModel,
+        granted: &[String],
+        user_id: &str,
+        role_names: &[String],
+    ) -> Result<UserModel, AppError> {
+        if admin.id == user_id {
+            return Err(AppError::CannotChangeOwnRole);
+        }
+
+        let user = self.get_user(user_id).await?;
+
+        let roles = role_repository::get_roles_by_name(role_names, self.pool.clone()).await?;
+
+        if let Some(unknown) = role_names
+            .iter()
+            .find(|name| !roles.iter().any(|role| &role.name == *name))
+        {
+            return Err(AppError::UnknownRole(unknown.to_string()));
+        }
+
+        let current =
+            role_repository::get_user_roles(std::slice::from_ref(&user.id), self.pool.clone())
+                .await?;
+
+        let assigned = roles
+            .iter()
+            .filter(|role| !current.iter().any(|grant| grant.name == role.name))
+            .collect::<Vec<_>>();
+
+        let assigned_ids = assigned
+            .iter()
+            .map(|role| role.id.clone())
+            .collect::<Vec<_>>();
+        let permissions =
+            role_repository::get_role_permissions(&assigned_ids, self.pool.clone()).await?;
+        role_service::check_granted(
+            &permissions
+                .into_iter()
+                .map(|grant| grant.name)
+                .collect::<Vec<_>>(),
+            granted,
+        )?;
+
+        for role in assigned {
+            role_repository::assign_role(&user.id, &role.id, Some(&admin.id), self.pool.clone())
+                .await?;
+
+            self.record_role_change(&user.id, &role.name, RoleChange::Assigned, &admin.id)
+                .await?;
+        }
+
+        let removed = current
+            .iter()
+            .filter(|grant| !role_names.contains(&grant.name))
+            .map(|grant| grant.name.clone())
+            .collect::<Vec<_>>();
+        let removed_roles = role_repository::get_roles_by_name(&removed, self.pool.clone()).await?;
+
+        for role in removed_roles {
+            role_repository::remove_role(&user.id, &role.id, self.pool.clone()).await?;
+
+            self.record_role_change(&user.id, &role.name, RoleChange::Removed, &admin.id)
+                .await?;
+        }
+
+        Ok(user)
+    }
+
+    async fn record_role_change(
+        &self,
+        user_id: &str,
+        role: &str,
+        action: RoleChange,
+        admin_id: &str,
+    ) -> Result<(), AppError> {
+        role_history_repository::create_role_history(
+            user_id,
+            role,
+            action,
+            Some(admin_id),
+            self.pool.clone(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Suspends or bans another user and signs them out everywhere, so the
+    /// status also takes effect on routes that only check the token.
+    pub async fn set_status(
+        &self,
+        admin: &UserModel,
+        user_id: &str,
+        body: &SetUserStatusSchema,
+    ) -> Result<UserModel, AppError> {
+        if body.status == UserStatus::Active {
+            return Err(AppError::InvalidStatusChange);
+        }
+        if admin.id == user_id {
+            return Err(AppError::CannotChangeOwnStatus);
+        }
+
+        self.get_user(user_id).await?;
+
+        user_repository::set_status(
+            user_id,
+            body.status,
+            Some(body.reason.trim()),
+            Some(&admin.id),
+            body.expires_at,
+            self.pool.clone(),
+        )
+        .await?;
+
+        SessionService::new(self.pool.clone())
+            .revoke_user_sessions(user_id, None)
+            .await?;
+
+        self.get_user(user_id).await
+    }
+
+    /// Lifts a suspension or ban before it expires.
+    pub async fn clear_status(&self, user_id: &str) -> Result<UserModel, AppError> {
+        self.get_user(user_id).await?;
+
+        user_repository::set_status(
+            user_id,
+            UserStatus::Active,
+            None,
+            None,
+            None,
+            self.pool.clone(),
+        )
+        .await?;
+
+        self.get_user(user_id).await
+    }
+
+    /// Lifts a lockout or backoff after failed logins of the account.
+    pub async fn unlock(&self, user_id: &str) -> Result<UserModel, AppError> {
+        let user = self.get_user(user_id).await?;
+
+        let throttle_service = LoginThrottleService::new(self.pool.clone());
+        throttle_service
+            .clear(&login_throttle_service::email_key(&user.email))
+            .await?;
+        throttle_service
+            .clear(&login_throttle_service::mfa_key(&user.id))
+            .await?;
+
+        Ok(user)
+    }
+}
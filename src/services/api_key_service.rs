@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use sqlx::MySqlPool;
+
+use crate::{
+    models::api_key::ApiKeyModel,
+    repositories::{api_key_repository, role_repository},
+    schemas::user::CreateApiKeySchema,
+    utils::{error::AppError, token},
+};
+
+/// An API key together with the names of the permissions it grants.
+#[derive(Debug, Clone)]
+pub struct ApiKeyWithScopes {
+    pub api_key: ApiKeyModel,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ApiKeyService {
+    pool: MySqlPool,
+}
+
+impl ApiKeyService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_api_keys(&self, user_id: &str) -> Result<Vec<ApiKeyWithScopes>, AppError> {
+        let api_keys = api_key_repository::get_api_keys(user_id, self.pool.clone()).await?;
+
+        self.with_scopes(api_keys).await.map_err(AppError::from)
+    }
+
+    /// Creates a key for the user and returns it with the raw key, which is
+    /// never available again afterwards. A key may only grant permissions
+    /// the user holds.
+    pub async fn create_api_key(
+        &self,
+        user_id: &str,
+        body: &CreateApiKeySchema,
+    ) -> Result<(ApiKeyWithScopes, String), AppError> {
+        let permissions = role_repository::get_user_permissions(user_id, self.pool.clone()).await?;
+
+        if let Some(scope) = body
+            .scopes
+            .iter()
+            .find(|scope| !permissions.contains(scope))
+        {
+            return Err(AppError::ScopeNotGranted(scope.to_string()));
+        }
+
+        let raw_key = token::generate_api_key();
+        let api_key = ApiKeyModel {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: body.name.clone(),
+            key_prefix: raw_key[..token::API_KEY_DISPLAY_LENGTH].to_string(),
+            key_hash: token::hash_opaque_token(&raw_key),
+            expires_at: body.expires_at,
+            last_used_at: None,
+            created_at: None,
+        };
+
+        api_key_repository::create_api_key(&api_key, &body.scopes, self.pool.clone()).await?;
+
+        let api_key = api_key_repository::get_api_key(&api_key.id, user_id, self.pool.clone())
+            .await?
+            .ok_or(AppError::internal(
+                "API key missing right after creating it",
+            ))?;
+
+        let mut api_keys = self.with_scopes(vec![api_key]).await?;
+
+        Ok((api_keys.remove(0), raw_key))
+    }
+
+    pub async fn delete_api_key(&self, user_id: &str, api_key_id: &str) -> Result<(), AppError> {
+        let result =
+            api_key_repository::delete_api_key(api_key_id, user_id, self.pool.clone()).await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::ApiKeyNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Looks up an unexpired key by its raw value and records the use.
+    pub async fn authenticate(
+        &self,
+        raw_key: &str,
+    ) -> Result<Option<ApiKeyWithScopes>, sqlx::Error> {
+        let api_key = match api_key_repository::get_active_api_key_by_hash(
+            &token::hash_opaque_token(raw_key),
+            self.pool.clone(),
+        )
+        .await?
+        {
+            Some(api_key) => api_key,
+            None => return Ok(None),
+        };
+
+        api_key_repository::touch_api_key(&api_key.id, self.pool.clone()).await?;
+
+        let mut api_keys = self.with_scopes(vec![api_key]).await?;
+        Ok(api_keys.pop())
+    }
+
+    async fn with_scopes(
+        &self,
+        api_keys: Vec<ApiKeyModel>,
+    ) -> Result<Vec<ApiKeyWithScopes>, sqlx::Error> {
+        let api_key_ids: Vec<String> = api_keys.iter().map(|key| key.id.clone()).collect();
+        let grants =
+            api_key_repository::get_api_key_permissions(&api_key_ids, self.pool.clone()).await?;
+
+        let mut scopes: HashMap<String, Vec<String>> = HashMap::new();
+        for grant in grants {
+            scopes.entry(grant.owner_id).or_default().push(grant.name);
+        }
+
+        Ok(api_keys
+            .into_iter()
+            .map(|api_key| ApiKeyWithScopes {
+                scopes: scopes.remove(&api_key.id).unwrap_or_default(),
+                api_key,
+            })
+            .collect())
+    }
+}
@@ -1,7 +1,24 @@
-use actix_web::web::Json;
+use chrono::{Duration, Utc};
 use sqlx::{mysql::MySqlQueryResult, MySqlPool};
 
-use crate::{repositories::auth_repository, schemas::auth::RegisterUserSchema};
+use crate::{
+    models::{magic_link::MagicLinkModel, user::UserModel},
+    repositories::{
+        auth_repository, magic_link_repository, password_reset_repository, user_repository,
+    },
+    schemas::auth::RegisterUserSchema,
+    services::{mail_service::MailService, session_service::SessionService},
+    utils::{
+        config::Config,
+        error::AppError,
+        jwt_keys::JwtKeys,
+        password,
+        token::{self, TokenPurpose},
+    },
+};
+
+/// Attempts at one emailed sign-in code before it stops working.
+const MAX_LOGIN_CODE_ATTEMPTS: i32 = 5;
 
 #[derive(Debug)]
 pub struct AuthService {
@@ -15,11 +32,282 @@ impl AuthService {
 
     pub async fn create_user(
         &self,
-        user_id: &String,
-        body: Json<RegisterUserSchema>,
-    ) -> Result<MySqlQueryResult, String> {
-        let query_result = auth_repository::register_user(&user_id, &body, self.pool.clone()).await;
+        user_id: &str,
+        body: &RegisterUserSchema,
+        locale: &str,
+    ) -> Result<MySqlQueryResult, AppError> {
+        let hashed_password = password::hash(&body.password).map_err(AppError::InvalidPassword)?;
+
+        auth_repository::register_user(user_id, body, &hashed_password, locale, self.pool.clone())
+            .await
+            .map_err(|e| AppError::from(e).on_conflict(AppError::EmailExist))
+    }
+
+    pub async fn send_verification_email(
+        &self,
+        user: &UserModel,
+        config: &Config,
+        keys: &JwtKeys,
+        mail_service: &MailService,
+    ) -> Result<(), AppError> {
+        let verification_token = token::create_purpose_token(
+            &user.id,
+            TokenPurpose::EmailVerification,
+            None,
+            keys,
+            config.email_verification_maxage,
+        )
+        .map_err(AppError::internal)?;
+
+        let link = format!(
+            "{}/verify-email?token={}",
+            config.app_url, verification_token
+        );
+
+        mail_service
+            .send_template(
+                &user.email,
+                "verify_email",
+                &user.locale,
+                &[
+                    ("name", &user.name),
+                    ("link", &link),
+                    ("expires_in", &config.email_verification_maxage.to_string()),
+                ],
+            )
+            .await
+    }
+
+    /// Marks the account behind `verification_token` as verified. Verifying an
+    /// already verified account is not an error.
+    pub async fn verify_email(
+        &self,
+        verification_token: &str,
+        keys: &JwtKeys,
+    ) -> Result<(), AppError> {
+        let user_id =
+            token::decode_purpose_token(verification_token, TokenPurpose::EmailVerification, keys)
+                .map_err(|_| AppError::InvalidVerificationToken)?
+                .sub;
+
+        let query_result = user_repository::verify_user(&user_id, self.pool.clone()).await?;
+
+        // MySQL reports zero affected rows when the value did not change, so
+        // only a missing user is worth distinguishing here.
+        if query_result.rows_affected() == 0 {
+            let user =
+                user_repository::get_user(Some(&user_id), None, None, self.pool.clone()).await?;
+
+            if user.is_none() {
+                return Err(AppError::InvalidVerificationToken);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Emails a password reset link if `email` belongs to an account. Unknown
+    /// addresses are ignored without an error so callers cannot tell them
+    /// apart.
+    pub async fn request_password_reset(
+        &self,
+        email: &str,
+        config: &Config,
+        mail_service: &MailService,
+    ) -> Result<(), AppError> {
+        let user = user_repository::get_user(None, None, Some(email), self.pool.clone()).await?;
+
+        let Some(user) = user else {
+            return Ok(());
+        };
+
+        let reset_token = token::generate_opaque_token();
+
+        password_reset_repository::create_password_reset(
+            &uuid::Uuid::new_v4().to_string(),
+            &user.id,
+            &token::hash_opaque_token(&reset_token),
+            Utc::now() + Duration::minutes(config.password_reset_maxage),
+            self.pool.clone(),
+        )
+        .await?;
+
+        let link = format!("{}/reset-password?token={}", config.app_url, reset_token);
+
+        mail_service
+            .send_template(
+                &user.email,
+                "password_reset",
+                &user.locale,
+                &[
+                    ("name", &user.name),
+                    ("link", &link),
+                    ("expires_in", &config.password_reset_maxage.to_string()),
+                ],
+            )
+            .await
+    }
+
+    /// Sets a new password using a reset token and signs the user out
+    /// everywhere. The token, and every other outstanding one of the same
+    /// user, can not be used again afterwards.
+    pub async fn reset_password(
+        &self,
+        reset_token: &str,
+        new_password: &str,
+    ) -> Result<(), AppError> {
+        let reset = password_reset_repository::get_password_reset_by_hash(
+            &token::hash_opaque_token(reset_token),
+            self.pool.clone(),
+        )
+        .await?
+        .filter(|reset| reset.used_at.is_none() && reset.expires_at > Utc::now())
+        .ok_or(AppError::InvalidResetToken)?;
+
+        let hashed_password = password::hash(new_password).map_err(AppError::InvalidPassword)?;
+
+        let consumed = password_reset_repository::use_password_resets(
+            &reset.id,
+            &reset.user_id,
+            self.pool.clone(),
+        )
+        .await?;
+
+        if !consumed {
+            return Err(AppError::InvalidResetToken);
+        }
+
+        user_repository::update_password(&reset.user_id, &hashed_password, self.pool.clone())
+            .await?;
+
+        SessionService::new(self.pool.clone())
+            .revoke_user_sessions(&reset.user_id, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Emails a single-use sign-in link, and a code for typing into the app,
+    /// if `email` belongs to an account. Unknown addresses are ignored
+    /// without an error so callers cannot tell them apart.
+    pub async fn request_magic_link(
+        &self,
+        email: &str,
+        config: &Config,
+        mail_service: &MailService,
+    ) -> Result<(), AppError> {
+        let user = user_repository::get_user(None, None, Some(email), self.pool.clone()).await?;
+
+        let Some(user) = user else {
+            return Ok(());
+        };
+
+        let link_token = token::generate_opaque_token();
+        let code = token::generate_login_code();
+
+        let magic_link = MagicLinkModel {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            token_hash: token::hash_opaque_token(&link_token),
+            // Only a million codes exist, a fast digest would not protect them.
+            code_hash: password::hash(&code).map_err(AppError::internal)?,
+            failed_attempts: 0,
+            expires_at: Utc::now() + Duration::minutes(config.magic_link_maxage),
+            used_at: None,
+            created_at: None,
+        };
+
+        magic_link_repository::create_magic_link(&magic_link, self.pool.clone()).await?;
+
+        let link = format!("{}/magic-link?token={}", config.app_url, link_token);
+
+        mail_service
+            .send_template(
+                &user.email,
+                "magic_link",
+                &user.locale,
+                &[
+                    ("name", &user.name),
+                    ("link", &link),
+                    ("code", &code),
+                    ("expires_in", &config.magic_link_maxage.to_string()),
+                ],
+            )
+            .await
+    }
+
+    /// The user an emailed sign-in link belongs to.
+    pub async fn verify_magic_link(&self, link_token: &str) -> Result<UserModel, AppError> {
+        let magic_link = magic_link_repository::get_magic_link_by_hash(
+            &token::hash_opaque_token(link_token),
+            self.pool.clone(),
+        )
+        .await?
+        .filter(|magic_link| magic_link.used_at.is_none() && magic_link.expires_at > Utc::now())
+        .ok_or(AppError::InvalidMagicLink)?;
+
+        self.use_magic_link(&magic_link).await
+    }
+
+    /// The user an emailed sign-in code belongs to. Each attempt is counted
+    /// before the code is compared, so concurrent guesses cannot go past
+    /// `MAX_LOGIN_CODE_ATTEMPTS`; after that a new email has to be
+    /// requested. Callers cap the attempts per account on top, as every new
+    /// email comes with fresh attempts.
+    pub async fn verify_login_code(&self, email: &str, code: &str) -> Result<UserModel, AppError> {
+        let user = user_repository::get_user(None, None, Some(email), self.pool.clone())
+            .await?
+            .ok_or(AppError::InvalidMagicLink)?;
+
+        let magic_link = magic_link_repository::get_active_magic_link(&user.id, self.pool.clone())
+            .await?
+            .ok_or(AppError::InvalidMagicLink)?;
+
+        let reserved = magic_link_repository::reserve_attempt(
+            &magic_link.id,
+            MAX_LOGIN_CODE_ATTEMPTS,
+            self.pool.clone(),
+        )
+        .await?;
+
+        if !reserved {
+            return Err(AppError::InvalidMagicLink);
+        }
+
+        let code_matches =
+            password::compare(code, &magic_link.code_hash).map_err(AppError::internal)?;
+
+        if !code_matches {
+            return Err(AppError::InvalidMagicLink);
+        }
+
+        self.use_magic_link(&magic_link).await
+    }
+
+    /// Consumes the link and returns its user. Receiving the email proves
+    /// the address, so an unverified account becomes verified.
+    async fn use_magic_link(&self, magic_link: &MagicLinkModel) -> Result<UserModel, AppError> {
+        let consumed =
+            magic_link_repository::use_magic_link(&magic_link.id, self.pool.clone()).await?;
+
+        if !consumed {
+            return Err(AppError::InvalidMagicLink);
+        }
+
+        let user =
+            user_repository::get_user(Some(&magic_link.user_id), None, None, self.pool.clone())
+                .await?
+                .ok_or(AppError::UserNoLongerExist)?;
+
+        if user.verified == 0 {
+            user_repository::verify_user(&user.id, self.pool.clone()).await?;
+
+            return Ok(UserModel {
+                verified: 1,
+                ..user
+            });
+        }
 
-        Ok(query_result?)
+        Ok(user)
     }
 }
@@ -0,0 +1,309 @@
+use std::io::{Cursor, Write};
+
+use actix_web::web;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::MySqlPool;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    models::{data_export::DataExportModel, user::UserModel},
+    repositories::{
+        data_export_repository, mfa_repository, role_history_repository, role_repository,
+        session_repository, user_repository,
+    },
+    services::mail_service::MailService,
+    utils::{avatar, config::Config, error::AppError, storage::Storage, token},
+};
+
+/// A run that has not finished after this many minutes is assumed to have
+/// died with the process and is picked up again.
+const STALE_AFTER_MINUTES: i64 = 60;
+
+pub const CONTENT_TYPE: &str = "application/zip";
+
+pub fn export_key(user_id: &str, export_id: &str) -> String {
+    format!("exports/{}/{}.zip", user_id, export_id)
+}
+
+/// Runs that started before this are treated as dead.
+pub fn stale_before() -> chrono::DateTime<Utc> {
+    Utc::now() - Duration::minutes(STALE_AFTER_MINUTES)
+}
+
+#[derive(Debug)]
+pub struct DataExportService {
+    pool: MySqlPool,
+}
+
+impl DataExportService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Queues an export of everything stored about the user. Only one export
+    /// per user can be pending at a time.
+    pub async fn request_export(&self, user_id: &str) -> Result<DataExportModel, AppError> {
+        let active = data_export_repository::get_active_data_export(
+            user_id,
+            stale_before(),
+            self.pool.clone(),
+        )
+        .await?;
+
+        if active.is_some() {
+            return Err(AppError::DataExportInProgress);
+        }
+
+        let export_id = uuid::Uuid::new_v4().to_string();
+
+        data_export_repository::create_data_export(&export_id, user_id, self.pool.clone()).await?;
+
+        self.get_export(user_id, &export_id).await
+    }
+
+    /// Returns the export if it belongs to `user_id`.
+    pub async fn get_export(
+        &self,
+        user_id: &str,
+        export_id: &str,
+    ) -> Result<DataExportModel, AppError> {
+        data_export_repository::get_data_export(export_id, self.pool.clone())
+            .await?
+            .filter(|export| export.user_id == user_id)
+            .ok_or(AppError::DataExportNotFound)
+    }
+
+    /// Builds the archive, stores it and emails the user a download link.
+    /// Does nothing when another worker already claimed the export.
+    pub async fn process_export(
+        &self,
+        export_id: &str,
+        config: &Config,
+        storage: &dyn Storage,
+        mail_service: &MailService,
+    ) -> Result<(), String> {
+        let claimed =
+            data_export_repository::claim_data_export(export_id, stale_before(), self.pool.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+
+        if !claimed {
+            return Ok(());
+        }
+
+        let result = self
+            .build_and_send(export_id, config, storage, mail_service)
+            .await;
+
+        if let Err(e) = &result {
+            data_export_repository::fail_data_export(export_id, e, self.pool.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        result
+    }
+
+    async fn build_and_send(
+        &self,
+        export_id: &str,
+        config: &Config,
+        storage: &dyn Storage,
+        mail_service: &MailService,
+    ) -> Result<(), String> {
+        let export = data_export_repository::get_data_export(export_id, self.pool.clone())
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("Data export disappeared while processing")?;
+
+        let user = user_repository::get_user(Some(&export.user_id), None, None, self.pool.clone())
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or("User of the data export no longer exists")?;
+
+        let archive = self.build_archive(&user, storage).await?;
+
+        let storage_key = export_key(&user.id, &export.id);
+        storage.put(&storage_key, archive, CONTENT_TYPE).await?;
+
+        let download_token = token::generate_opaque_token();
+        let expires_at = Utc::now() + Duration::minutes(config.data_export_maxage);
+
+        data_export_repository::complete_data_export(
+            &export.id,
+            &storage_key,
+            &token::hash_opaque_token(&download_token),
+            expires_at,
+            self.pool.clone(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let link = format!(
+            "{}/api/exports/{}/download?token={}",
+            config.api_url, export.id, download_token
+        );
+
+        // The archive is ready either way, the status endpoint still reports it.
+        if let Err(e) = mail_service
+            .send_template(
+                &user.email,
+                "data_export_ready",
+                &user.locale,
+                &[
+                    ("name", &user.name),
+                    ("link", &link),
+                    ("expires_in", &config.data_export_maxage.to_string()),
+                ],
+            )
+            .await
+        {
+            eprintln!("🔥 Failed to email data export {}: {}", export.id, e);
+        }
+
+        Ok(())
+    }
+
+    /// Zips one JSON document per kind of data plus the uploaded files.
+    async fn build_archive(
+        &self,
+        user: &UserModel,
+        storage: &dyn Storage,
+    ) -> Result<Vec<u8>, String> {
+        let roles =
+            role_repository::get_user_roles(std::slice::from_ref(&user.id), self.pool.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+
+        let mfa = mfa_repository::get_user_mfa(&user.id, self.pool.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let role_history = role_history_repository::get_role_history(&user.id, self.pool.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let sessions = session_repository::get_all_sessions(&user.id, self.pool.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Uploaded photos go into the archive, external photo URLs stay as is.
+        let mut files = vec![];
+        let mut photo = user.photo.clone();
+        if avatar::is_avatar_key(&user.photo) {
+            photo = "files/photo.jpg".to_string();
+            files.push((photo.clone(), storage.get(&user.photo).await?));
+        }
+
+        let profile = json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "roles": roles.into_iter().map(|grant| grant.name).collect::<Vec<_>>(),
+            "status": user.status.to_str(),
+            "statusReason": user.status_reason,
+            "statusExpiresAt": user.status_expires_at,
+            "locale": user.locale,
+            "photo": photo,
+            "verified": user.verified == 1,
+            "mfaEnabled": user.mfa_enabled == 1,
+            "mfaConfirmedAt": mfa.and_then(|mfa| mfa.confirmed_at),
+            "deletionScheduledAt": user.deletion_scheduled_at,
+            "createdAt": user.created_at,
+            "updatedAt": user.updated_at,
+        });
+
+        let role_history: Vec<_> = role_history
+            .into_iter()
+            .map(|change| {
+                json!({
+                    "role": change.role,
+                    "action": change.action,
+                    "changedAt": change.created_at,
+                })
+            })
+            .collect();
+
+        let logins: Vec<_> = sessions
+            .into_iter()
+            .map(|session| {
+                json!({
+                    "deviceName": session.device_name,
+                    "ipAddress": session.ip_address,
+                    "userAgent": session.user_agent,
+                    "loggedInAt": session.created_at,
+                    "lastSeenAt": session.last_seen_at,
+                    "revokedAt": session.revoked_at,
+                    "expiresAt": session.expires_at,
+                })
+            })
+            .collect();
+
+        let mut documents = vec![
+            (
+                "profile.json".to_string(),
+                serde_json::to_vec_pretty(&profile),
+            ),
+            (
+                "role_history.json".to_string(),
+                serde_json::to_vec_pretty(&role_history),
+            ),
+            (
+                "logins.json".to_string(),
+                serde_json::to_vec_pretty(&logins),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, bytes)| bytes.map(|bytes| (name, bytes)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+        documents.extend(files);
+
+        web::block(move || write_zip(documents))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    /// Returns the export and its archive when `download_token` matches and
+    /// the link has not expired.
+    pub async fn download(
+        &self,
+        export_id: &str,
+        download_token: &str,
+        storage: &dyn Storage,
+    ) -> Result<(DataExportModel, Vec<u8>), AppError> {
+        let export = data_export_repository::get_data_export(export_id, self.pool.clone())
+            .await?
+            .filter(|export| {
+                export.token_hash.as_deref()
+                    == Some(token::hash_opaque_token(download_token).as_str())
+                    && export
+                        .expires_at
+                        .is_some_and(|expires_at| expires_at > Utc::now())
+            })
+            .ok_or(AppError::InvalidDownloadToken)?;
+
+        let storage_key = export
+            .storage_key
+            .as_deref()
+            .ok_or(AppError::InvalidDownloadToken)?;
+
+        let archive = storage.get(storage_key).await.map_err(AppError::internal)?;
+
+        Ok((export, archive))
+    }
+}
+
+fn write_zip(entries: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (name, bytes) in entries {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&bytes).map_err(|e| e.to_string())?;
+    }
+
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
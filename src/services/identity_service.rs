@@ -0,0 +1,186 @@
+use sqlx::MySqlPool;
+
+use crate::{
+    models::{user::UserModel, user_identity::UserIdentityModel},
+    repositories::{auth_repository, user_identity_repository, user_repository},
+    utils::{error::AppError, oidc::IdTokenClaims, password, token},
+};
+
+#[derive(Debug)]
+pub struct IdentityService {
+    pool: MySqlPool,
+}
+
+impl IdentityService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_identities(&self, user_id: &str) -> Result<Vec<UserIdentityModel>, AppError> {
+        user_identity_repository::get_user_identities(user_id, self.pool.clone())
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// The user a provider account signs in as. An identity seen for the
+    /// first time is linked to the account with the same email, or gets a
+    /// new account, but only when the provider verified the email.
+    pub async fn sign_in(
+        &self,
+        provider: &str,
+        claims: &IdTokenClaims,
+        locale: &str,
+    ) -> Result<UserModel, AppError> {
+        let identity =
+            user_identity_repository::get_identity(provider, &claims.subject, self.pool.clone())
+                .await?;
+
+        if let Some(identity) = identity {
+            let verified_email = claims.email.as_deref().filter(|_| claims.email_verified);
+            user_identity_repository::touch_identity(
+                &identity.id,
+                verified_email,
+                self.pool.clone(),
+            )
+            .await?;
+
+            return self.get_user(&identity.user_id).await;
+        }
+
+        let email = match (&claims.email, claims.email_verified) {
+            (Some(email), true) => email,
+            _ => return Err(AppError::IdentityEmailNotVerified),
+        };
+
+        let existing =
+            user_repository::get_user(None, None, Some(email), self.pool.clone()).await?;
+
+        let mut identity = UserIdentityModel {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: uuid::Uuid::new_v4().to_string(),
+            provider: provider.to_string(),
+            subject: claims.subject.clone(),
+            email: Some(email.clone()),
+            last_used_at: None,
+            created_at: None,
+        };
+
+        match existing {
+            // Whoever registered an unverified account may not own the
+            // address, linking it would hand them the provider's sign-in.
+            Some(user) if user.verified == 0 => Err(AppError::UnverifiedAccountExists),
+            Some(user) => {
+                identity.user_id = user.id.clone();
+                user_identity_repository::create_identity(&identity, self.pool.clone())
+                    .await
+                    .map_err(|e| identity_write_error(e, provider))?;
+
+                Ok(user)
+            }
+            None => {
+                let name = claims
+                    .name
+                    .as_deref()
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or(email.split('@').next().unwrap_or(email))
+                    .trim()
+                    .chars()
+                    .take(100)
+                    .collect::<String>();
+                // Nobody knows this password; the user can set a real one
+                // through the password reset.
+                let hashed_password =
+                    password::hash(token::generate_opaque_token()).map_err(AppError::internal)?;
+
+                auth_repository::register_identity_user(
+                    &name,
+                    locale,
+                    &hashed_password,
+                    &identity,
+                    self.pool.clone(),
+                )
+                .await
+                // Another request signed up with this email first.
+                .map_err(|e| AppError::from(e).on_conflict(AppError::EmailExist))?;
+
+                self.get_user(&identity.user_id).await
+            }
+        }
+    }
+
+    /// Links a provider account to a signed-in user. Linking the same
+    /// account again is a no-op.
+    pub async fn link(
+        &self,
+        user_id: &str,
+        provider: &str,
+        claims: &IdTokenClaims,
+    ) -> Result<UserIdentityModel, AppError> {
+        let existing =
+            user_identity_repository::get_identity(provider, &claims.subject, self.pool.clone())
+                .await?;
+
+        match existing {
+            Some(identity) if identity.user_id == user_id => return Ok(identity),
+            Some(_) => return Err(AppError::IdentityAlreadyLinked),
+            None => {}
+        }
+
+        let identity = UserIdentityModel {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            provider: provider.to_string(),
+            subject: claims.subject.clone(),
+            email: claims.email.clone(),
+            last_used_at: None,
+            created_at: None,
+        };
+
+        user_identity_repository::create_identity(&identity, self.pool.clone())
+            .await
+            .map_err(|e| identity_write_error(e, provider))?;
+
+        user_identity_repository::get_identity(provider, &claims.subject, self.pool.clone())
+            .await?
+            .ok_or(AppError::internal(
+                "Identity missing right after linking it",
+            ))
+    }
+
+    /// Unlinks the user's account at `provider`, unless it is the only way
+    /// left to sign in.
+    pub async fn unlink(&self, user: &UserModel, provider: &str) -> Result<(), AppError> {
+        let identities = self.list_identities(&user.id).await?;
+
+        if !identities
+            .iter()
+            .any(|identity| identity.provider == provider)
+        {
+            return Err(AppError::IdentityNotFound);
+        }
+
+        if user.has_password == 0 && identities.len() == 1 {
+            return Err(AppError::LastSignInMethod);
+        }
+
+        user_identity_repository::delete_identity(&user.id, provider, self.pool.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn get_user(&self, user_id: &str) -> Result<UserModel, AppError> {
+        user_repository::get_user(Some(user_id), None, None, self.pool.clone())
+            .await?
+            .ok_or(AppError::UserNoLongerExist)
+    }
+}
+
+fn identity_write_error(e: sqlx::Error, provider: &str) -> AppError {
+    match AppError::from(e) {
+        AppError::UniqueViolation(key) if key == "user_identities_provider_key" => {
+            AppError::ProviderAlreadyLinked(provider.to_string())
+        }
+        AppError::UniqueViolation(_) => AppError::IdentityAlreadyLinked,
+        e => e,
+    }
+}
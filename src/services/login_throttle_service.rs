@@ -0,0 +1,102 @@
+use chrono::{Duration, Utc};
+use sqlx::MySqlPool;
+
+use crate::{
+    repositories::login_throttle_repository,
+    utils::{config::Config, error::AppError},
+};
+
+/// Throttle key counting failed logins of one account.
+pub fn email_key(email: &str) -> String {
+    format!("email:{}", email.trim().to_lowercase())
+}
+
+/// Throttle key counting wrong second factors of one account. Kept apart
+/// from `email_key`, which a correct password clears.
+pub fn mfa_key(user_id: &str) -> String {
+    format!("mfa:{}", user_id)
+}
+
+/// Throttle key counting failed logins from one client address.
+pub fn ip_key(ip_address: &str) -> String {
+    format!("ip:{}", ip_address)
+}
+
+/// How long the next login for a key has to wait after its `failures`th
+/// failure: nothing for the first few, then an exponential backoff, and a
+/// lockout from `lockout_threshold` on.
+fn block_duration(failures: i32, lockout_threshold: i32, config: &Config) -> Option<Duration> {
+    if failures >= lockout_threshold {
+        return Some(Duration::minutes(config.login_lockout_duration));
+    }
+
+    if failures <= config.login_free_attempts {
+        return None;
+    }
+
+    let exponent = (failures - config.login_free_attempts - 1) as u32;
+    let seconds = config
+        .login_backoff_base
+        .saturating_mul(2i64.saturating_pow(exponent))
+        .min(config.login_backoff_max);
+
+    Some(Duration::seconds(seconds))
+}
+
+#[derive(Debug)]
+pub struct LoginThrottleService {
+    pool: MySqlPool,
+}
+
+impl LoginThrottleService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Whether logins for any of `keys` are currently refused.
+    pub async fn is_blocked(&self, keys: &[String]) -> Result<bool, AppError> {
+        let throttles = login_throttle_repository::get_throttles(keys, self.pool.clone()).await?;
+
+        let now = Utc::now();
+        Ok(throttles
+            .iter()
+            .any(|throttle| throttle.blocked_until.is_some_and(|until| until > now)))
+    }
+
+    /// Counts a failed login against the account and the client address, and
+    /// blocks them for as long as the policy in `config` says.
+    pub async fn record_failure(
+        &self,
+        account_key: &str,
+        ip_key: Option<&str>,
+        config: &Config,
+    ) -> Result<(), AppError> {
+        let keys = [
+            Some((account_key, config.login_lockout_threshold)),
+            ip_key.map(|key| (key, config.login_ip_lockout_threshold)),
+        ];
+
+        for (key, lockout_threshold) in keys.into_iter().flatten() {
+            let failures = login_throttle_repository::record_failure(
+                key,
+                config.login_failure_window,
+                self.pool.clone(),
+            )
+            .await?;
+
+            if let Some(duration) = block_duration(failures, lockout_threshold, config) {
+                login_throttle_repository::block(key, Utc::now() + duration, self.pool.clone())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the failures of `key` and lifts its block.
+    pub async fn clear(&self, key: &str) -> Result<(), AppError> {
+        login_throttle_repository::delete_throttle(key, self.pool.clone()).await?;
+
+        Ok(())
+    }
+}
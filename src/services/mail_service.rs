@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use crate::utils::{
+    error::AppError,
+    mailer::{EmailTemplates, Mailer},
+};
+
+pub struct MailService {
+    mailer: Arc<dyn Mailer>,
+    templates: Arc<EmailTemplates>,
+}
+
+impl MailService {
+    pub fn new(mailer: Arc<dyn Mailer>, templates: Arc<EmailTemplates>) -> Self {
+        Self { mailer, templates }
+    }
+
+    /// Renders `template` in the recipient's locale and sends it.
+    pub async fn send_template(
+        &self,
+        to: &str,
+        template: &str,
+        locale: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<(), AppError> {
+        let email = self
+            .templates
+            .render(template, locale, to, vars)
+            .map_err(AppError::internal)?;
+
+        self.mailer.send(email).await.map_err(AppError::internal)
+    }
+}
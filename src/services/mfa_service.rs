@@ -0,0 +1,210 @@
+use sqlx::MySqlPool;
+
+use crate::{
+    models::user::UserModel,
+    repositories::{mfa_repository, user_repository},
+    utils::{error::AppError, password, totp},
+};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// What a user needs to add the account to an authenticator app.
+#[derive(Debug)]
+pub struct MfaEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+    /// Base64 encoded PNG of the `otpauth://` URI.
+    pub qr_code: String,
+}
+
+#[derive(Debug)]
+pub struct MfaService {
+    pool: MySqlPool,
+}
+
+impl MfaService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Starts (or restarts) enrollment with a new secret. Two-factor
+    /// authentication is not active until [`MfaService::confirm`] succeeds.
+    pub async fn enroll(&self, user: &UserModel, issuer: &str) -> Result<MfaEnrollment, AppError> {
+        if user.mfa_enabled != 0 {
+            return Err(AppError::MfaAlreadyEnabled);
+        }
+
+        let secret = totp::generate_secret();
+        let totp = totp::build(&secret, &user.email, issuer).map_err(AppError::internal)?;
+        let qr_code = totp.get_qr_base64().map_err(AppError::internal)?;
+
+        mfa_repository::upsert_user_mfa(&user.id, &secret, self.pool.clone()).await?;
+
+        Ok(MfaEnrollment {
+            otpauth_uri: totp.get_url(),
+            secret,
+            qr_code,
+        })
+    }
+
+    /// Activates two-factor authentication once the user proves the
+    /// authenticator app works, and returns the clear-text recovery codes.
+    /// They are only stored hashed, so this is the only time they are shown.
+    pub async fn confirm(
+        &self,
+        user: &UserModel,
+        code: &str,
+        issuer: &str,
+    ) -> Result<Vec<String>, AppError> {
+        if user.mfa_enabled != 0 {
+            return Err(AppError::MfaAlreadyEnabled);
+        }
+
+        let user_mfa = mfa_repository::get_user_mfa(&user.id, self.pool.clone())
+            .await?
+            .ok_or(AppError::MfaNotEnrolled)?;
+
+        let totp =
+            totp::build(&user_mfa.secret, &user.email, issuer).map_err(AppError::internal)?;
+        let step = totp::verify(&totp, code).ok_or(AppError::InvalidEnrollmentCode)?;
+
+        mfa_repository::use_time_step(&user.id, step, self.pool.clone()).await?;
+
+        let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+        let code_hashes = recovery_codes
+            .iter()
+            .map(|code| password::hash(totp::normalize_recovery_code(code)))
+            .collect::<Result<Vec<String>, String>>()
+            .map_err(AppError::internal)?;
+
+        mfa_repository::replace_recovery_codes(&user.id, &code_hashes, self.pool.clone()).await?;
+
+        mfa_repository::confirm_user_mfa(&user.id, self.pool.clone()).await?;
+
+        user_repository::set_mfa_enabled(&user.id, true, self.pool.clone()).await?;
+
+        Ok(recovery_codes)
+    }
+
+    /// Starts the second step of a login and returns the id the
+    /// `mfa_pending` token has to carry. Only the latest token can be
+    /// exchanged, and only once.
+    pub async fn start_challenge(&self, user_id: &str) -> Result<String, AppError> {
+        let token_id = uuid::Uuid::new_v4().to_string();
+
+        mfa_repository::set_pending_token(user_id, &token_id, self.pool.clone()).await?;
+
+        Ok(token_id)
+    }
+
+    /// Checks the second factor during login. Exactly one of `code` and
+    /// `recovery_code` is expected; recovery codes are burnt on use.
+    ///
+    /// The `mfa_pending` token is burnt before the code is looked at, so each
+    /// token buys a single guess and a wrong code means signing in again.
+    pub async fn verify(
+        &self,
+        user: &UserModel,
+        token_id: &str,
+        code: Option<&str>,
+        recovery_code: Option<&str>,
+        issuer: &str,
+    ) -> Result<(), AppError> {
+        if code.is_some() == recovery_code.is_some() {
+            return Err(AppError::MfaCodeNotProvided);
+        }
+
+        let taken =
+            mfa_repository::take_pending_token(&user.id, token_id, self.pool.clone()).await?;
+
+        if !taken {
+            return Err(AppError::InvalidToken);
+        }
+
+        match (code, recovery_code) {
+            (Some(code), None) => self.verify_code(user, code, issuer).await,
+            (None, Some(recovery_code)) => self.verify_recovery_code(user, recovery_code).await,
+            _ => Err(AppError::MfaCodeNotProvided),
+        }
+    }
+
+    /// Turns two-factor authentication off after re-checking the password and
+    /// a current code.
+    pub async fn disable(
+        &self,
+        user: &UserModel,
+        current_password: &str,
+        code: &str,
+        issuer: &str,
+    ) -> Result<(), AppError> {
+        let password_matches = password::compare(current_password, &user.password)
+            .map_err(AppError::InvalidPassword)?;
+
+        if !password_matches {
+            return Err(AppError::WrongCurrentPassword);
+        }
+
+        self.verify_code(user, code, issuer)
+            .await
+            .map_err(|e| match e {
+                AppError::InvalidMfaCode => AppError::WrongCurrentMfaCode,
+                e => e,
+            })?;
+
+        mfa_repository::delete_user_mfa(&user.id, self.pool.clone()).await?;
+
+        user_repository::set_mfa_enabled(&user.id, false, self.pool.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn verify_code(
+        &self,
+        user: &UserModel,
+        code: &str,
+        issuer: &str,
+    ) -> Result<(), AppError> {
+        let user_mfa = mfa_repository::get_user_mfa(&user.id, self.pool.clone())
+            .await?
+            .filter(|user_mfa| user_mfa.confirmed_at.is_some())
+            .ok_or(AppError::MfaNotEnrolled)?;
+
+        let totp =
+            totp::build(&user_mfa.secret, &user.email, issuer).map_err(AppError::internal)?;
+        let step = totp::verify(&totp, code).ok_or(AppError::InvalidMfaCode)?;
+
+        let fresh = mfa_repository::use_time_step(&user.id, step, self.pool.clone()).await?;
+
+        if !fresh {
+            return Err(AppError::InvalidMfaCode);
+        }
+
+        Ok(())
+    }
+
+    async fn verify_recovery_code(
+        &self,
+        user: &UserModel,
+        recovery_code: &str,
+    ) -> Result<(), AppError> {
+        let recovery_code = totp::normalize_recovery_code(recovery_code);
+        if recovery_code.is_empty() {
+            return Err(AppError::InvalidMfaCode);
+        }
+
+        let stored_codes =
+            mfa_repository::get_unused_recovery_codes(&user.id, self.pool.clone()).await?;
+
+        for stored in stored_codes {
+            if password::compare(&recovery_code, &stored.code_hash).unwrap_or(false) {
+                let used = mfa_repository::use_recovery_code(&stored.id, self.pool.clone()).await?;
+
+                if used {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(AppError::InvalidMfaCode)
+    }
+}
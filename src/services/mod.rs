@@ -1,2 +1,13 @@
+pub mod admin_service;
+pub mod api_key_service;
 pub mod auth_service;
+pub mod data_export_service;
+pub mod identity_service;
+pub mod login_throttle_service;
+pub mod mail_service;
+pub mod mfa_service;
+pub mod organization_service;
+pub mod role_service;
+pub mod session_service;
+pub mod token_service;
 pub mod user_services;
@@ -0,0 +1,383 @@
+use chrono::{Duration, Utc};
+use sqlx::MySqlPool;
+
+use crate::{
+    models::{
+        organization::{
+            OrganizationInvitationModel, OrganizationMemberModel, OrganizationMemberUserModel,
+            OrganizationModel, OrganizationRole, UserOrganizationModel,
+        },
+        session::SessionModel,
+        user::UserModel,
+    },
+    repositories::{organization_repository, session_repository, user_repository},
+    schemas::organization::{
+        CreateOrganizationSchema, InviteMemberSchema, UpdateOrganizationSchema,
+    },
+    services::{mail_service::MailService, token_service::TokenService},
+    utils::{config::Config, error::AppError, jwt_keys::JwtKeys, token},
+};
+
+#[derive(Debug)]
+pub struct OrganizationService {
+    pool: MySqlPool,
+}
+
+impl OrganizationService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_membership(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> Result<Option<OrganizationMemberModel>, sqlx::Error> {
+        organization_repository::get_membership(organization_id, user_id, self.pool.clone()).await
+    }
+
+    pub async fn list_organizations(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<UserOrganizationModel>, AppError> {
+        organization_repository::get_user_organizations(user_id, self.pool.clone())
+            .await
+            .map_err(AppError::from)
+    }
+
+    pub async fn get_organization(
+        &self,
+        organization_id: &str,
+    ) -> Result<OrganizationModel, AppError> {
+        organization_repository::get_organization(organization_id, self.pool.clone())
+            .await?
+            .ok_or(AppError::OrganizationNotFound)
+    }
+
+    /// Creates an organization owned by `user`.
+    pub async fn create_organization(
+        &self,
+        user: &UserModel,
+        body: &CreateOrganizationSchema,
+    ) -> Result<OrganizationModel, AppError> {
+        let organization_id = uuid::Uuid::new_v4().to_string();
+        let slug = match &body.slug {
+            Some(slug) => slug.clone(),
+            None => slugify(&body.name, &organization_id),
+        };
+
+        organization_repository::create_organization(
+            &organization_id,
+            &body.name,
+            &slug,
+            &user.id,
+            self.pool.clone(),
+        )
+        .await
+        .map_err(organization_write_error)?;
+
+        self.get_organization(&organization_id).await
+    }
+
+    pub async fn update_organization(
+        &self,
+        organization_id: &str,
+        body: &UpdateOrganizationSchema,
+    ) -> Result<OrganizationModel, AppError> {
+        organization_repository::update_organization(
+            organization_id,
+            body.name.as_deref(),
+            body.slug.as_deref(),
+            self.pool.clone(),
+        )
+        .await
+        .map_err(organization_write_error)?;
+
+        self.get_organization(organization_id).await
+    }
+
+    /// Deletes the organization with its memberships and invitations.
+    /// Sessions acting in it fall back to having no active organization.
+    pub async fn delete_organization(&self, organization_id: &str) -> Result<(), AppError> {
+        organization_repository::delete_organization(organization_id, self.pool.clone()).await?;
+
+        Ok(())
+    }
+
+    /// Makes `organization_id` the active organization of the session and
+    /// issues an access token whose `org` claim names it.
+    pub async fn switch_organization(
+        &self,
+        user: &UserModel,
+        session: &SessionModel,
+        organization_id: &str,
+        config: &Config,
+        keys: &JwtKeys,
+    ) -> Result<(String, OrganizationModel, OrganizationRole), AppError> {
+        // Non-members get the same answer as for an unknown organization.
+        let membership = self
+            .get_membership(organization_id, &user.id)
+            .await?
+            .ok_or(AppError::OrganizationNotFound)?;
+        let organization = self.get_organization(organization_id).await?;
+
+        session_repository::set_active_organization(
+            &session.id,
+            Some(organization_id),
+            self.pool.clone(),
+        )
+        .await?;
+
+        let access_token = TokenService::new(self.pool.clone())
+            .create_access_token(user, &session.id, Some(organization_id), config, keys)
+            .await?;
+
+        Ok((access_token, organization, membership.role))
+    }
+
+    pub async fn list_members(
+        &self,
+        organization_id: &str,
+    ) -> Result<Vec<OrganizationMemberUserModel>, AppError> {
+        organization_repository::get_members(organization_id, self.pool.clone())
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Changes the role of a member. Only owners may grant or take away
+    /// ownership, and the last owner cannot step down.
+    pub async fn update_member(
+        &self,
+        actor: &OrganizationMemberModel,
+        user_id: &str,
+        role: OrganizationRole,
+    ) -> Result<(), AppError> {
+        let target = self.get_member(&actor.organization_id, user_id).await?;
+
+        let touches_owner =
+            target.role == OrganizationRole::Owner || role == OrganizationRole::Owner;
+        if touches_owner && actor.role != OrganizationRole::Owner {
+            return Err(AppError::PermissionDenied);
+        }
+
+        if target.role == OrganizationRole::Owner && role != OrganizationRole::Owner {
+            self.ensure_not_last_owner(&actor.organization_id).await?;
+        }
+
+        organization_repository::update_member_role(
+            &actor.organization_id,
+            user_id,
+            role,
+            self.pool.clone(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes a member. Anyone may leave, removing somebody else needs an
+    /// admin, and only owners can remove owners.
+    pub async fn remove_member(
+        &self,
+        actor: &OrganizationMemberModel,
+        user_id: &str,
+    ) -> Result<(), AppError> {
+        let target = self.get_member(&actor.organization_id, user_id).await?;
+
+        let is_self = actor.user_id == target.user_id;
+        if !is_self && !actor.role.includes(OrganizationRole::Admin) {
+            return Err(AppError::PermissionDenied);
+        }
+
+        if target.role == OrganizationRole::Owner {
+            if actor.role != OrganizationRole::Owner {
+                return Err(AppError::PermissionDenied);
+            }
+            self.ensure_not_last_owner(&actor.organization_id).await?;
+        }
+
+        organization_repository::remove_member(&actor.organization_id, user_id, self.pool.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_invitations(
+        &self,
+        organization_id: &str,
+    ) -> Result<Vec<OrganizationInvitationModel>, AppError> {
+        organization_repository::get_pending_invitations(organization_id, self.pool.clone())
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Emails an invitation link to `body.email`. Inviting the same address
+    /// again replaces the earlier invitation.
+    pub async fn invite(
+        &self,
+        actor: &OrganizationMemberModel,
+        inviter: &UserModel,
+        body: &InviteMemberSchema,
+        config: &Config,
+        mail_service: &MailService,
+    ) -> Result<OrganizationInvitationModel, AppError> {
+        if body.role == OrganizationRole::Owner && actor.role != OrganizationRole::Owner {
+            return Err(AppError::PermissionDenied);
+        }
+
+        let organization = self.get_organization(&actor.organization_id).await?;
+
+        let invitee =
+            user_repository::get_user(None, None, Some(&body.email), self.pool.clone()).await?;
+
+        if let Some(invitee) = &invitee {
+            let membership = self.get_membership(&organization.id, &invitee.id).await?;
+            if membership.is_some() {
+                return Err(AppError::AlreadyOrganizationMember);
+            }
+        }
+
+        organization_repository::delete_pending_invitations(
+            &organization.id,
+            &body.email,
+            self.pool.clone(),
+        )
+        .await?;
+
+        let raw_token = token::generate_opaque_token();
+        let invitation = OrganizationInvitationModel {
+            id: uuid::Uuid::new_v4().to_string(),
+            organization_id: organization.id.clone(),
+            email: body.email.clone(),
+            role: body.role,
+            token_hash: token::hash_opaque_token(&raw_token),
+            invited_by: Some(inviter.id.clone()),
+            expires_at: Utc::now() + Duration::minutes(config.organization_invitation_maxage),
+            accepted_at: None,
+            created_at: Some(Utc::now()),
+        };
+
+        organization_repository::create_invitation(&invitation, self.pool.clone()).await?;
+
+        let link = format!("{}/invitations/accept?token={}", config.app_url, raw_token);
+        let locale = invitee
+            .as_ref()
+            .map_or(&config.default_locale, |user| &user.locale);
+
+        mail_service
+            .send_template(
+                &invitation.email,
+                "organization_invitation",
+                locale,
+                &[
+                    ("name", &inviter.name),
+                    ("organization", &organization.name),
+                    ("role", invitation.role.to_str()),
+                    ("link", &link),
+                    (
+                        "expires_in",
+                        &config.organization_invitation_maxage.to_string(),
+                    ),
+                ],
+            )
+            .await?;
+
+        Ok(invitation)
+    }
+
+    pub async fn revoke_invitation(
+        &self,
+        organization_id: &str,
+        invitation_id: &str,
+    ) -> Result<(), AppError> {
+        let result = organization_repository::delete_invitation(
+            invitation_id,
+            organization_id,
+            self.pool.clone(),
+        )
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::InvitationNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Joins `user` to the organization behind `raw_token`. The invitation
+    /// only works for the account with the address it was sent to.
+    pub async fn accept_invitation(
+        &self,
+        user: &UserModel,
+        raw_token: &str,
+    ) -> Result<(OrganizationModel, OrganizationRole), AppError> {
+        let invitation = organization_repository::get_invitation_by_hash(
+            &token::hash_opaque_token(raw_token),
+            self.pool.clone(),
+        )
+        .await?
+        .filter(|invitation| invitation.accepted_at.is_none() && invitation.expires_at > Utc::now())
+        .ok_or(AppError::InvalidInvitationToken)?;
+
+        if !invitation.email.eq_ignore_ascii_case(&user.email) {
+            return Err(AppError::InvitationEmailMismatch);
+        }
+
+        let accepted =
+            organization_repository::accept_invitation(&invitation, &user.id, self.pool.clone())
+                .await?;
+        if !accepted {
+            return Err(AppError::InvalidInvitationToken);
+        }
+
+        let membership = self
+            .get_member(&invitation.organization_id, &user.id)
+            .await?;
+        let organization = self.get_organization(&invitation.organization_id).await?;
+
+        Ok((organization, membership.role))
+    }
+
+    async fn get_member(
+        &self,
+        organization_id: &str,
+        user_id: &str,
+    ) -> Result<OrganizationMemberModel, AppError> {
+        self.get_membership(organization_id, user_id)
+            .await?
+            .ok_or(AppError::OrganizationMemberNotFound)
+    }
+
+    async fn ensure_not_last_owner(&self, organization_id: &str) -> Result<(), AppError> {
+        let owners =
+            organization_repository::count_owners(organization_id, self.pool.clone()).await?;
+
+        if owners <= 1 {
+            return Err(AppError::LastOrganizationOwner);
+        }
+
+        Ok(())
+    }
+}
+
+/// Lowercase, dash-separated form of `name`. Falls back to the start of the
+/// organization id when the name has no usable characters.
+fn slugify(name: &str, organization_id: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        organization_id[..8].to_string()
+    } else {
+        let slug: String = slug.chars().take(100).collect();
+        slug.trim_end_matches('-').to_string()
+    }
+}
+
+fn organization_write_error(e: sqlx::Error) -> AppError {
+    AppError::from(e).on_conflict(AppError::OrganizationSlugExist)
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use sqlx::MySqlPool;
+
+use crate::{
+    models::role::{PermissionModel, RoleModel},
+    repositories::role_repository,
+    schemas::admin::{CreateRoleSchema, UpdateRoleSchema},
+    utils::error::AppError,
+};
+
+/// A role together with the names of the permissions it grants.
+#[derive(Debug, Clone)]
+pub struct RoleWithPermissions {
+    pub role: RoleModel,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct RoleService {
+    pool: MySqlPool,
+}
+
+impl RoleService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_user_roles(&self, user_id: &str) -> Result<Vec<String>, sqlx::Error> {
+        let grants =
+            role_repository::get_user_roles(&[user_id.to_string()], self.pool.clone()).await?;
+
+        Ok(grants.into_iter().map(|grant| grant.name).collect())
+    }
+
+    /// Role names of several users, keyed by user id. Users without roles
+    /// are missing from the map.
+    pub async fn get_roles_of_users(
+        &self,
+        user_ids: &[String],
+    ) -> Result<HashMap<String, Vec<String>>, sqlx::Error> {
+        let grants = role_repository::get_user_roles(user_ids, self.pool.clone()).await?;
+
+        let mut roles: HashMap<String, Vec<String>> = HashMap::new();
+        for grant in grants {
+            roles.entry(grant.owner_id).or_default().push(grant.name);
+        }
+
+        Ok(roles)
+    }
+
+    pub async fn get_user_permissions(&self, user_id: &str) -> Result<Vec<String>, sqlx::Error> {
+        role_repository::get_user_permissions(user_id, self.pool.clone()).await
+    }
+
+    pub async fn list_roles(&self) -> Result<Vec<RoleWithPermissions>, AppError> {
+        let roles = role_repository::get_roles(self.pool.clone()).await?;
+
+        self.with_permissions(roles).await
+    }
+
+    pub async fn get_role(&self, role_id: &str) -> Result<RoleWithPermissions, AppError> {
+        let role = role_repository::get_role(role_id, self.pool.clone())
+            .await?
+            .ok_or(AppError::RoleNotFound)?;
+
+        let mut roles = self.with_permissions(vec![role]).await?;
+        Ok(roles.remove(0))
+    }
+
+    pub async fn list_permissions(&self) -> Result<Vec<PermissionModel>, AppError> {
+        role_repository::get_permissions(self.pool.clone())
+            .await
+            .map_err(AppError::from)
+    }
+
+    /// Creates a role. `granted` is the caller's scope, a role cannot be
+    /// given permissions the caller does not have.
+    pub async fn create_role(
+        &self,
+        body: &CreateRoleSchema,
+        granted: &[String],
+    ) -> Result<RoleWithPermissions, AppError> {
+        self.check_permissions_exist(&body.permissions).await?;
+        check_granted(&body.permissions, granted)?;
+
+        let role_id = uuid::Uuid::new_v4().to_string();
+
+        role_repository::create_role(
+            &role_id,
+            &body.name,
+            body.description.as_deref(),
+            &body.permissions,
+            self.pool.clone(),
+        )
+        .await
+        .map_err(role_write_error)?;
+
+        self.get_role(&role_id).await
+    }
+
+    /// Updates a role definition. Built-in roles keep their name because
+    /// code and configuration refer to them by it. Like `create_role`, only
+    /// permissions in `granted` can be added.
+    pub async fn update_role(
+        &self,
+        role_id: &str,
+        body: &UpdateRoleSchema,
+        granted: &[String],
+    ) -> Result<RoleWithPermissions, AppError> {
+        let existing = self.get_role(role_id).await?;
+
+        if existing.role.is_system == 1
+            && body
+                .name
+                .as_deref()
+                .is_some_and(|name| name != existing.role.name)
+        {
+            return Err(AppError::SystemRoleImmutable);
+        }
+
+        if let Some(permissions) = &body.permissions {
+            self.check_permissions_exist(permissions).await?;
+
+            let added = permissions
+                .iter()
+                .filter(|name| !existing.permissions.contains(name))
+                .cloned()
+                .collect::<Vec<_>>();
+            check_granted(&added, granted)?;
+        }
+
+        role_repository::update_role(
+            role_id,
+            body.name.as_deref(),
+            body.description.as_deref(),
+            body.permissions.as_deref(),
+            self.pool.clone(),
+        )
+        .await
+        .map_err(role_write_error)?;
+
+        self.get_role(role_id).await
+    }
+
+    /// Deletes a custom role. Users holding it simply lose it.
+    pub async fn delete_role(&self, role_id: &str) -> Result<(), AppError> {
+        let existing = self.get_role(role_id).await?;
+
+        if existing.role.is_system == 1 {
+            return Err(AppError::SystemRoleImmutable);
+        }
+
+        role_repository::delete_role(role_id, self.pool.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn with_permissions(
+        &self,
+        roles: Vec<RoleModel>,
+    ) -> Result<Vec<RoleWithPermissions>, AppError> {
+        let role_ids: Vec<String> = roles.iter().map(|role| role.id.clone()).collect();
+        let grants = role_repository::get_role_permissions(&role_ids, self.pool.clone()).await?;
+
+        let mut permissions: HashMap<String, Vec<String>> = HashMap::new();
+        for grant in grants {
+            permissions
+                .entry(grant.owner_id)
+                .or_default()
+                .push(grant.name);
+        }
+
+        Ok(roles
+            .into_iter()
+            .map(|role| RoleWithPermissions {
+                permissions: permissions.remove(&role.id).unwrap_or_default(),
+                role,
+            })
+            .collect())
+    }
+
+    async fn check_permissions_exist(&self, names: &[String]) -> Result<(), AppError> {
+        let known = self.list_permissions().await?;
+
+        match names
+            .iter()
+            .find(|name| !known.iter().any(|permission| &permission.name == *name))
+        {
+            Some(unknown) => Err(AppError::UnknownPermission(unknown.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Refuses to hand out any of `permissions` the caller was not `granted`
+/// itself, so that a delegated role cannot escalate to a bigger one.
+pub fn check_granted(permissions: &[String], granted: &[String]) -> Result<(), AppError> {
+    match permissions
+        .iter()
+        .find(|permission| !granted.contains(permission))
+    {
+        Some(permission) => Err(AppError::ScopeNotGranted(permission.to_string())),
+        None => Ok(()),
+    }
+}
+
+fn role_write_error(e: sqlx::Error) -> AppError {
+    AppError::from(e).on_conflict(AppError::RoleExist)
+}
@@ -0,0 +1,110 @@
+use actix_web::{http, HttpRequest};
+use sqlx::MySqlPool;
+
+use crate::{
+    models::session::SessionModel,
+    repositories::{refresh_token_repository, session_repository},
+    utils::{client_ip::client_ip, config::Config},
+};
+
+/// Client details recorded against a session so users can tell their
+/// devices apart.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInfo {
+    pub device_name: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Records the same client address that rate limits and login throttles
+    /// key on, so a client cannot make up the one shown in its device list.
+    pub fn from_request(req: &HttpRequest, device_name: Option<String>, config: &Config) -> Self {
+        let ip_address =
+            client_ip(req, &config.trusted_proxies).map(|ip_address| ip_address.to_string());
+        let user_agent = req
+            .headers()
+            .get(http::header::USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|ua| ua.chars().take(255).collect());
+
+        DeviceInfo {
+            device_name: device_name.map(|name| name.chars().take(100).collect()),
+            ip_address,
+            user_agent,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SessionService {
+    pool: MySqlPool,
+}
+
+impl SessionService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the session if it is still active and records the activity.
+    pub async fn get_active_session(
+        &self,
+        session_id: &str,
+    ) -> Result<Option<SessionModel>, sqlx::Error> {
+        let session = session_repository::get_active_session(session_id, self.pool.clone()).await?;
+
+        if session.is_some() {
+            session_repository::touch_session(session_id, self.pool.clone()).await?;
+        }
+
+        Ok(session)
+    }
+
+    pub async fn get_sessions(&self, user_id: &str) -> Result<Vec<SessionModel>, sqlx::Error> {
+        let sessions = session_repository::get_active_sessions(user_id, self.pool.clone()).await?;
+        Ok(sessions)
+    }
+
+    /// Revokes one of the user's sessions together with its refresh tokens.
+    /// Returns `false` when the session does not exist or belongs to someone
+    /// else.
+    pub async fn revoke_session(
+        &self,
+        user_id: &str,
+        session_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let query_result =
+            session_repository::revoke_session(session_id, Some(user_id), self.pool.clone())
+                .await?;
+
+        if query_result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        refresh_token_repository::revoke_refresh_token_family(session_id, self.pool.clone())
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Revokes every session of the user except `except_session_id`, or all
+    /// of them when it is `None`. Returns the number of sessions revoked.
+    pub async fn revoke_user_sessions(
+        &self,
+        user_id: &str,
+        except_session_id: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let query_result =
+            session_repository::revoke_user_sessions(user_id, except_session_id, self.pool.clone())
+                .await?;
+
+        refresh_token_repository::revoke_user_refresh_tokens(
+            user_id,
+            except_session_id,
+            self.pool.clone(),
+        )
+        .await?;
+
+        Ok(query_result.rows_affected())
+    }
+}
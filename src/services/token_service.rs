@@ -0,0 +1,255 @@
+use chrono::{Duration, Utc};
+use sqlx::MySqlPool;
+
+use crate::{
+    models::{refresh_token::RefreshTokenModel, user::UserModel},
+    repositories::{refresh_token_repository, session_repository, user_repository},
+    utils::{config::Config, error::AppError, jwt_keys::JwtKeys, token},
+};
+
+use super::{role_service::RoleService, session_service::DeviceInfo, user_services::UserService};
+
+/// An access token and the refresh token that can renew it.
+#[derive(Debug)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug)]
+pub struct TokenService {
+    pool: MySqlPool,
+}
+
+impl TokenService {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Opens a new session for the user and issues its first token pair.
+    ///
+    /// Each session owns exactly one refresh-token family, so the session id
+    /// doubles as the family id and as the `jti` of every access token issued
+    /// for it.
+    pub async fn start_session(
+        &self,
+        user: &UserModel,
+        device: &DeviceInfo,
+        config: &Config,
+        keys: &JwtKeys,
+    ) -> Result<TokenPair, AppError> {
+        UserService::ensure_active(user)?;
+
+        // Signing in during the grace period is how a deletion is undone.
+        if user.deletion_scheduled_at.is_some() {
+            UserService::new(self.pool.clone())
+                .cancel_deletion(&user.id)
+                .await?;
+        }
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+
+        session_repository::create_session(
+            &session_id,
+            &user.id,
+            device.device_name.as_deref(),
+            device.ip_address.as_deref(),
+            device.user_agent.as_deref(),
+            Utc::now() + Duration::minutes(config.refresh_token_maxage),
+            self.pool.clone(),
+        )
+        .await?;
+
+        let refresh_token = self
+            .issue_refresh_token(&user.id, &session_id, config.refresh_token_maxage)
+            .await?;
+
+        let access_token = self
+            .create_access_token(user, &session_id, None, config, keys)
+            .await?;
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token,
+        })
+    }
+
+    /// Rotates the refresh token and issues a new access token for the same
+    /// session. The user and their roles are read again so role changes
+    /// reach the new token.
+    pub async fn refresh_session(
+        &self,
+        raw_token: &str,
+        config: &Config,
+        keys: &JwtKeys,
+    ) -> Result<TokenPair, AppError> {
+        let (stored, refresh_token) = self
+            .rotate_refresh_token(raw_token, config.refresh_token_maxage)
+            .await?;
+
+        session_repository::extend_session(
+            &stored.family_id,
+            Utc::now() + Duration::minutes(config.refresh_token_maxage),
+            self.pool.clone(),
+        )
+        .await?;
+
+        // Removing a member or deleting an organization clears it from the
+        // session, so whatever is still set here is safe to carry over.
+        let session = session_repository::get_active_session(&stored.family_id, self.pool.clone())
+            .await?
+            .ok_or(AppError::SessionRevoked)?;
+
+        let user = user_repository::get_user(Some(&stored.user_id), None, None, self.pool.clone())
+            .await?
+            .ok_or(AppError::UserNoLongerExist)?;
+        UserService::ensure_active(&user)?;
+
+        let access_token = self
+            .create_access_token(
+                &user,
+                &stored.family_id,
+                session.active_organization_id.as_deref(),
+                config,
+                keys,
+            )
+            .await?;
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token,
+        })
+    }
+
+    /// Issues an access token carrying the user's current roles and, as its
+    /// scope, every permission those roles grant. `organization_id` becomes
+    /// the `org` claim.
+    pub async fn create_access_token(
+        &self,
+        user: &UserModel,
+        session_id: &str,
+        organization_id: Option<&str>,
+        config: &Config,
+        keys: &JwtKeys,
+    ) -> Result<String, AppError> {
+        let role_service = RoleService::new(self.pool.clone());
+        let roles = role_service.get_user_roles(&user.id).await?;
+        let permissions = role_service.get_user_permissions(&user.id).await?;
+
+        token::create_token(
+            &user.id,
+            roles,
+            permissions,
+            session_id,
+            organization_id,
+            keys,
+            config.jwt_maxage,
+        )
+        .map_err(AppError::internal)
+    }
+
+    /// Stores a new refresh token in `family_id` and returns the raw value,
+    /// which is the only time it is ever available in clear text.
+    pub async fn issue_refresh_token(
+        &self,
+        user_id: &str,
+        family_id: &str,
+        expires_in_minutes: i64,
+    ) -> Result<String, sqlx::Error> {
+        let token_id = uuid::Uuid::new_v4().to_string();
+        let raw_token = token::generate_opaque_token();
+
+        refresh_token_repository::create_refresh_token(
+            &token_id,
+            user_id,
+            family_id,
+            &token::hash_opaque_token(&raw_token),
+            Utc::now() + Duration::minutes(expires_in_minutes),
+            self.pool.clone(),
+        )
+        .await?;
+
+        Ok(raw_token)
+    }
+
+    /// Exchanges a refresh token for a new one in the same family and returns
+    /// the consumed row alongside the new raw token.
+    ///
+    /// Presenting a token that has already been rotated means it was copied
+    /// somewhere, so the whole family is revoked and the user has to log in
+    /// again on every device that shared it.
+    pub async fn rotate_refresh_token(
+        &self,
+        raw_token: &str,
+        expires_in_minutes: i64,
+    ) -> Result<(RefreshTokenModel, String), AppError> {
+        let stored = refresh_token_repository::get_refresh_token_by_hash(
+            &token::hash_opaque_token(raw_token),
+            self.pool.clone(),
+        )
+        .await?
+        .ok_or(AppError::InvalidRefreshToken)?;
+
+        if stored.revoked_at.is_some() {
+            self.revoke_family(&stored.family_id).await?;
+            return Err(AppError::RefreshTokenReused);
+        }
+
+        if stored.expires_at < Utc::now() {
+            return Err(AppError::InvalidRefreshToken);
+        }
+
+        let token_id = uuid::Uuid::new_v4().to_string();
+        let raw_token = token::generate_opaque_token();
+
+        let rotated = refresh_token_repository::rotate_refresh_token(
+            &stored.id,
+            &token_id,
+            self.pool.clone(),
+        )
+        .await?;
+
+        if !rotated {
+            // Another request rotated this token between our read and write.
+            self.revoke_family(&stored.family_id).await?;
+            return Err(AppError::RefreshTokenReused);
+        }
+
+        refresh_token_repository::create_refresh_token(
+            &token_id,
+            &stored.user_id,
+            &stored.family_id,
+            &token::hash_opaque_token(&raw_token),
+            Utc::now() + Duration::minutes(expires_in_minutes),
+            self.pool.clone(),
+        )
+        .await?;
+
+        Ok((stored, raw_token))
+    }
+
+    /// Ends the session `raw_token` belongs to. Unknown tokens are ignored,
+    /// logging out has nothing left to do for them.
+    pub async fn revoke_refresh_token(&self, raw_token: &str) -> Result<(), AppError> {
+        let stored = refresh_token_repository::get_refresh_token_by_hash(
+            &token::hash_opaque_token(raw_token),
+            self.pool.clone(),
+        )
+        .await?;
+
+        if let Some(stored) = stored {
+            self.revoke_family(&stored.family_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Revokes a refresh-token family and the session it belongs to.
+    pub async fn revoke_family(&self, family_id: &str) -> Result<(), AppError> {
+        refresh_token_repository::revoke_refresh_token_family(family_id, self.pool.clone()).await?;
+
+        session_repository::revoke_session(family_id, None, self.pool.clone()).await?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,15 @@
+use actix_web::web;
+use chrono::{DateTime, Duration, Utc};
 use sqlx::MySqlPool;
 
-use crate::{models::user::UserModel, repositories::user_repository};
+use crate::{
+    models::user::{UserModel, UserStatus},
+    repositories::user_repository,
+    schemas::user::UpdateProfileSchema,
+    utils::{avatar, error::AppError, password, storage::Storage},
+};
+
+use super::session_service::SessionService;
 
 #[derive(Debug)]
 pub struct UserService {
@@ -12,6 +21,15 @@ impl UserService {
         Self { pool }
     }
 
+    /// Fails with 403 when the account is currently suspended or banned.
+    pub fn ensure_active(user: &UserModel) -> Result<(), AppError> {
+        match user.effective_status() {
+            UserStatus::Active => Ok(()),
+            UserStatus::Suspended => Err(AppError::AccountSuspended(user.status_expires_at)),
+            UserStatus::Banned => Err(AppError::AccountBanned),
+        }
+    }
+
     pub async fn get_user(
         &self,
         user_id: Option<&str>,
@@ -21,4 +39,141 @@ impl UserService {
         let user = user_repository::get_user(user_id, name, email, self.pool.clone()).await?;
         Ok(user)
     }
+
+    /// Applies a profile update and returns the user as stored afterwards.
+    pub async fn update_profile(
+        &self,
+        user_id: &str,
+        body: &UpdateProfileSchema,
+    ) -> Result<UserModel, AppError> {
+        user_repository::update_profile(
+            user_id,
+            body.name.as_deref(),
+            body.photo.as_deref(),
+            self.pool.clone(),
+        )
+        .await?;
+
+        self.get_user(Some(user_id), None, None)
+            .await?
+            .ok_or(AppError::UserNoLongerExist)
+    }
+
+    /// Processes an uploaded photo, stores it with its thumbnails and points
+    /// the user at it. The previous upload, if any, is deleted afterwards.
+    pub async fn update_photo(
+        &self,
+        user: &UserModel,
+        bytes: Vec<u8>,
+        storage: &dyn Storage,
+    ) -> Result<UserModel, AppError> {
+        let processed = web::block(move || avatar::process(&bytes))
+            .await
+            .map_err(AppError::internal)?
+            .map_err(|_| AppError::InvalidImage)?;
+
+        let photo_key = avatar::photo_key(&user.id);
+
+        for (size, bytes) in processed.thumbnails {
+            storage
+                .put(
+                    &avatar::thumbnail_key(&photo_key, size),
+                    bytes,
+                    avatar::CONTENT_TYPE,
+                )
+                .await
+                .map_err(AppError::internal)?;
+        }
+        storage
+            .put(&photo_key, processed.photo, avatar::CONTENT_TYPE)
+            .await
+            .map_err(AppError::internal)?;
+
+        user_repository::update_photo(&user.id, &photo_key, self.pool.clone()).await?;
+
+        if avatar::is_avatar_key(&user.photo) {
+            Self::delete_photo_files(&user.photo, storage).await;
+        }
+
+        self.get_user(Some(&user.id), None, None)
+            .await?
+            .ok_or(AppError::UserNoLongerExist)
+    }
+
+    /// Best effort: a leftover file is only wasted space, so failures are
+    /// logged rather than surfaced.
+    pub async fn delete_photo_files(photo_key: &str, storage: &dyn Storage) {
+        let keys = avatar::THUMBNAIL_SIZES
+            .iter()
+            .map(|&size| avatar::thumbnail_key(photo_key, size))
+            .chain(std::iter::once(photo_key.to_string()));
+
+        for key in keys {
+            if let Err(e) = storage.delete(&key).await {
+                eprintln!("🔥 Failed to delete {}: {}", key, e);
+            }
+        }
+    }
+
+    /// Replaces the password after checking the current one. When
+    /// `keep_session_id` is given, every other session of the user is
+    /// revoked.
+    pub async fn change_password(
+        &self,
+        user: &UserModel,
+        current_password: &str,
+        new_password: &str,
+        keep_session_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let password_matches = password::compare(current_password, &user.password)
+            .map_err(AppError::InvalidPassword)?;
+
+        if !password_matches {
+            return Err(AppError::WrongCurrentPassword);
+        }
+
+        let hashed_password = password::hash(new_password).map_err(AppError::InvalidPassword)?;
+
+        user_repository::update_password(&user.id, &hashed_password, self.pool.clone()).await?;
+
+        if let Some(keep_session_id) = keep_session_id {
+            SessionService::new(self.pool.clone())
+                .revoke_user_sessions(&user.id, Some(keep_session_id))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Schedules the account for purging after `grace_period` minutes and
+    /// signs it out everywhere. Returns when the purge becomes due.
+    pub async fn request_deletion(
+        &self,
+        user: &UserModel,
+        password: &str,
+        grace_period: i64,
+    ) -> Result<DateTime<Utc>, AppError> {
+        let password_matches =
+            password::compare(password, &user.password).map_err(AppError::InvalidPassword)?;
+
+        if !password_matches {
+            return Err(AppError::WrongCurrentPassword);
+        }
+
+        let scheduled_at = Utc::now() + Duration::minutes(grace_period);
+
+        user_repository::schedule_deletion(&user.id, scheduled_at, self.pool.clone()).await?;
+
+        SessionService::new(self.pool.clone())
+            .revoke_user_sessions(&user.id, None)
+            .await?;
+
+        Ok(scheduled_at)
+    }
+
+    pub async fn cancel_deletion(&self, user_id: &str) -> Result<(), AppError> {
+        user_repository::cancel_deletion(user_id, self.pool.clone()).await?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,123 @@
+use std::io::Cursor;
+
+use image::{
+    codecs::jpeg::JpegEncoder, imageops::FilterType, io::Limits, io::Reader, DynamicImage,
+    ImageFormat, Rgb, RgbImage,
+};
+
+/// Prefix of every key an uploaded photo is stored under.
+pub const KEY_PREFIX: &str = "avatars/";
+/// Longest edge of the stored photo.
+pub const MAX_DIMENSION: u32 = 1024;
+/// Square thumbnails generated next to every photo.
+pub const THUMBNAIL_SIZES: [u32; 2] = [256, 64];
+pub const CONTENT_TYPE: &str = "image/jpeg";
+
+const JPEG_QUALITY: u8 = 85;
+/// Refuse images that would decode into something huge, whatever their
+/// compressed size.
+const MAX_SOURCE_DIMENSION: u32 = 8000;
+
+pub struct ProcessedAvatar {
+    pub photo: Vec<u8>,
+    pub thumbnails: Vec<(u32, Vec<u8>)>,
+}
+
+/// Decodes an uploaded image and re-encodes it as JPEG, which drops EXIF and
+/// any other metadata. The format is sniffed from the bytes, never taken
+/// from the client, and orientation from EXIF is applied first so photos
+/// taken sideways stay upright.
+pub fn process(bytes: &[u8]) -> Result<ProcessedAvatar, String> {
+    let format = image::guess_format(bytes).map_err(|_| "unrecognised image".to_string())?;
+    if !matches!(
+        format,
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP | ImageFormat::Gif
+    ) {
+        return Err(format!("unsupported image format {:?}", format));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+
+    let mut reader = Reader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| e.to_string())?;
+    let image = apply_orientation(image, exif_orientation(bytes));
+
+    let photo = if image.width() > MAX_DIMENSION || image.height() > MAX_DIMENSION {
+        encode_jpeg(&image.resize(MAX_DIMENSION, MAX_DIMENSION, FilterType::Lanczos3))?
+    } else {
+        encode_jpeg(&image)?
+    };
+    let thumbnails = THUMBNAIL_SIZES
+        .iter()
+        .map(|&size| {
+            encode_jpeg(&image.resize_to_fill(size, size, FilterType::Lanczos3))
+                .map(|bytes| (size, bytes))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ProcessedAvatar { photo, thumbnails })
+}
+
+/// Key for a new photo of `user_id`. Every upload gets a fresh key so caches
+/// and signed URLs of the previous photo never serve the new one.
+pub fn photo_key(user_id: &str) -> String {
+    format!("{}{}/{}.jpg", KEY_PREFIX, user_id, uuid::Uuid::new_v4())
+}
+
+/// Whether a stored `photo` value points at an uploaded photo rather than
+/// the default or an external URL.
+pub fn is_avatar_key(photo: &str) -> bool {
+    photo.starts_with(KEY_PREFIX)
+}
+
+/// Key of the `size` thumbnail stored next to `photo_key`.
+pub fn thumbnail_key(photo_key: &str, size: u32) -> String {
+    match photo_key.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}_{}.{}", stem, size, extension),
+        None => format!("{}_{}", photo_key, size),
+    }
+}
+
+fn exif_orientation(bytes: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .unwrap_or(1)
+}
+
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// JPEG has no alpha channel, so transparent pixels are laid over white.
+fn encode_jpeg(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let rgba = image.to_rgba8();
+    let flattened = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let pixel = rgba.get_pixel(x, y);
+        let alpha = pixel[3] as u16;
+        Rgb([0, 1, 2].map(|i| ((pixel[i] as u16 * alpha + 255 * (255 - alpha)) / 255) as u8))
+    });
+
+    let mut bytes = Vec::new();
+    JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY)
+        .encode_image(&flattened)
+        .map_err(|e| e.to_string())?;
+
+    Ok(bytes)
+}
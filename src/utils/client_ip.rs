@@ -0,0 +1,233 @@
+use std::{net::IpAddr, str::FromStr};
+
+use actix_web::HttpRequest;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Address or CIDR range, such as `10.0.0.0/8`, of a proxy in front of the
+/// API whose `X-Forwarded-For` is believed.
+#[derive(Debug, Clone, Copy)]
+pub struct TrustedProxy {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = match value.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (value, None),
+        };
+
+        let network = address
+            .trim()
+            .parse::<IpAddr>()
+            .map_err(|_| format!("{} is not an IP address or CIDR range", value))?
+            .to_canonical();
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_len)
+                .ok_or(format!("{} has an invalid prefix length", value))?,
+            None => max_len,
+        };
+
+        Ok(TrustedProxy {
+            network,
+            prefix_len,
+        })
+    }
+}
+
+/// Address of the client that made the request. This is the peer, unless
+/// the peer is one of `trusted_proxies`: then `X-Forwarded-For` is read from
+/// the right, skipping the proxies, up to the first address they did not
+/// add. Anything further left is whatever the client claimed, and is never
+/// believed.
+///
+/// Unlike `connection_info().realip_remote_addr()`, a client cannot pick its
+/// own address by sending the header itself, so this is what limits and
+/// lockouts have to key on.
+pub fn client_ip(req: &HttpRequest, trusted_proxies: &[TrustedProxy]) -> Option<IpAddr> {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+
+    let mut client = req.peer_addr()?.ip().to_canonical();
+
+    let hops = req
+        .headers()
+        .get_all(X_FORWARDED_FOR)
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .collect::<Vec<_>>();
+
+    for hop in hops.into_iter().rev() {
+        if !is_trusted(client) {
+            break;
+        }
+
+        match hop.trim().parse::<IpAddr>() {
+            Ok(ip) => client = ip.to_canonical(),
+            // A proxy would not have added it, stop at the last one that did.
+            Err(_) => break,
+        }
+    }
+
+    Some(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn proxies(entries: &[&str]) -> Vec<TrustedProxy> {
+        entries.iter().map(|entry| entry.parse().unwrap()).collect()
+    }
+
+    fn request(peer: &str, forwarded_for: &[&str]) -> HttpRequest {
+        let mut req = TestRequest::default().peer_addr(peer.parse().unwrap());
+        for value in forwarded_for {
+            req = req.append_header((X_FORWARDED_FOR, *value));
+        }
+        req.to_http_request()
+    }
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn parses_addresses_and_ranges() {
+        let cases = [
+            ("10.0.0.1", "10.0.0.1", true),
+            ("10.0.0.1", "10.0.0.2", false),
+            ("10.0.0.0/8", "10.255.1.2", true),
+            ("10.0.0.0/8", "11.0.0.1", false),
+            ("0.0.0.0/0", "203.0.113.9", true),
+            ("fd00::/8", "fd12:3456::1", true),
+            ("fd00::/8", "fe80::1", false),
+            ("2001:db8::1", "2001:db8::1", true),
+            ("::/0", "2001:db8::1", true),
+            // IPv4-mapped peers match IPv4 ranges.
+            ("10.0.0.0/8", "::ffff:10.1.2.3", true),
+            ("10.0.0.0/8", "fd00::1", false),
+        ];
+
+        for (proxy, address, expected) in cases {
+            let proxy = proxy.parse::<TrustedProxy>().unwrap();
+            assert_eq!(
+                proxy.contains(address.parse().unwrap()),
+                expected,
+                "{:?} contains {}",
+                proxy,
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        for entry in [
+            "",
+            "proxy",
+            "10.0.0.0/33",
+            "fd00::/129",
+            "10.0.0.0/x",
+            "10.0.0/8",
+        ] {
+            assert!(entry.parse::<TrustedProxy>().is_err(), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn ignores_the_header_from_untrusted_peers() {
+        let req = request("203.0.113.9:4000", &["198.51.100.1"]);
+
+        assert_eq!(client_ip(&req, &[]), ip("203.0.113.9"));
+        assert_eq!(
+            client_ip(&req, &proxies(&["10.0.0.0/8"])),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn takes_the_address_a_trusted_proxy_saw() {
+        // The client forged the first entry, the proxy appended the second.
+        let req = request("10.0.0.2:4000", &["198.51.100.1, 203.0.113.9"]);
+
+        assert_eq!(
+            client_ip(&req, &proxies(&["10.0.0.0/8"])),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn skips_chains_of_trusted_proxies() {
+        let trusted = proxies(&["10.0.0.0/8", "192.168.1.1"]);
+
+        let req = request("10.0.0.2:4000", &["198.51.100.1, 203.0.113.9, 192.168.1.1"]);
+        assert_eq!(client_ip(&req, &trusted), ip("203.0.113.9"));
+
+        // Proxies may also send one header each.
+        let req = request(
+            "10.0.0.2:4000",
+            &["198.51.100.1, 203.0.113.9", "10.1.1.1", "192.168.1.1"],
+        );
+        assert_eq!(client_ip(&req, &trusted), ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn falls_back_to_the_last_proxy_when_every_hop_is_trusted() {
+        let req = request("10.0.0.2:4000", &["10.0.0.3"]);
+
+        assert_eq!(client_ip(&req, &proxies(&["10.0.0.0/8"])), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn stops_at_a_malformed_entry() {
+        let trusted = proxies(&["10.0.0.0/8"]);
+
+        let req = request("10.0.0.2:4000", &["203.0.113.9, not-an-ip"]);
+        assert_eq!(client_ip(&req, &trusted), ip("10.0.0.2"));
+
+        let req = request("10.0.0.2:4000", &["198.51.100.1, unknown, 10.0.0.3"]);
+        assert_eq!(client_ip(&req, &trusted), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn handles_ipv6_proxies_and_clients() {
+        let trusted = proxies(&["fd00::/8"]);
+
+        let req = request("[fd00::2]:4000", &["2001:db8::1, 2001:db8::9"]);
+        assert_eq!(client_ip(&req, &trusted), ip("2001:db8::9"));
+
+        let req = request("[2001:db8::5]:4000", &["2001:db8::9"]);
+        assert_eq!(client_ip(&req, &trusted), ip("2001:db8::5"));
+    }
+}
@@ -1,27 +1,296 @@
+use super::client_ip::TrustedProxy;
+
 fn get_env_var(var_name: &str) -> String {
     std::env::var(var_name).unwrap_or_else(|_| panic!("{} must be set", var_name))
 }
 
+fn get_optional_env_var(var_name: &str) -> Option<String> {
+    std::env::var(var_name)
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// An OpenID Connect provider users can sign in with.
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    /// Name used in the API paths, e.g. `google`.
+    pub name: String,
+    pub discovery_url: String,
+    /// Client ids the ID tokens may be issued to, one per app platform.
+    pub client_ids: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
     pub jwt_secret: String,
+    pub jwt_algorithm: String,
+    pub jwt_key_id: String,
+    pub jwt_private_key_path: Option<String>,
+    pub jwt_verification_keys: Vec<(String, String)>,
+    pub jwt_issuer: String,
+    pub jwt_audience: String,
+    pub jwt_leeway: u64,
     pub jwt_maxage: i64,
+    pub refresh_token_maxage: i64,
+    pub email_verification_maxage: i64,
+    pub password_reset_maxage: i64,
+    pub magic_link_maxage: i64,
+    pub login_free_attempts: i32,
+    pub login_backoff_base: i64,
+    pub login_backoff_max: i64,
+    pub login_lockout_threshold: i32,
+    pub login_ip_lockout_threshold: i32,
+    pub login_lockout_duration: i64,
+    pub login_failure_window: i64,
+    pub app_url: String,
+    pub api_url: String,
+    pub mailer: String,
+    pub mail_from: String,
+    pub mail_outbox_dir: String,
+    pub mail_template_dir: String,
+    pub default_locale: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_tls: bool,
+    pub mfa_issuer: String,
+    pub mfa_required_roles: Vec<String>,
+    pub storage: String,
+    pub storage_local_dir: String,
+    pub storage_public_url: String,
+    pub s3_bucket: Option<String>,
+    pub s3_region: String,
+    pub s3_endpoint: Option<String>,
+    pub s3_access_key: Option<String>,
+    pub s3_secret_key: Option<String>,
+    pub s3_public_url: Option<String>,
+    pub s3_url_expiry: u32,
+    pub avatar_max_size: usize,
+    pub status_sweep_interval: u64,
+    pub account_deletion_grace_period: i64,
+    pub account_purge_interval: u64,
+    pub data_export_maxage: i64,
+    pub data_export_interval: u64,
+    pub organization_invitation_maxage: i64,
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    pub rate_limit_store: String,
+    pub redis_url: Option<String>,
+    /// Proxies whose `X-Forwarded-For` is believed, see `client_ip`.
+    pub trusted_proxies: Vec<TrustedProxy>,
     pub port: u16,
 }
 
+/// Reads `OIDC_<NAME>_DISCOVERY_URL` and `OIDC_<NAME>_CLIENT_IDS`. Google and
+/// Apple fall back to their public discovery documents.
+fn oidc_provider(name: &str) -> OidcProviderConfig {
+    let prefix = format!("OIDC_{}", name.to_uppercase().replace('-', "_"));
+    let discovery_url = get_optional_env_var(&format!("{}_DISCOVERY_URL", prefix))
+        .or(match name {
+            "google" => {
+                Some("https://accounts.google.com/.well-known/openid-configuration".to_string())
+            }
+            "apple" => {
+                Some("https://appleid.apple.com/.well-known/openid-configuration".to_string())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("{}_DISCOVERY_URL must be set", prefix));
+    let client_ids: Vec<String> = get_optional_env_var(&format!("{}_CLIENT_IDS", prefix))
+        .unwrap_or_default()
+        .split(',')
+        .map(|client_id| client_id.trim().to_string())
+        .filter(|client_id| !client_id.is_empty())
+        .collect();
+    if client_ids.is_empty() {
+        panic!("{}_CLIENT_IDS must be set", prefix);
+    }
+
+    OidcProviderConfig {
+        name: name.to_string(),
+        discovery_url,
+        client_ids,
+    }
+}
+
 impl Config {
     pub fn init() -> Config {
         let database_url = get_env_var("DATABASE_URL");
         let jwt_secret = get_env_var("JWT_SECRET_KEY");
+        let jwt_algorithm = get_optional_env_var("JWT_ALGORITHM").unwrap_or("HS256".to_string());
+        let jwt_key_id = get_optional_env_var("JWT_KEY_ID").unwrap_or("default".to_string());
+        let jwt_private_key_path = get_optional_env_var("JWT_PRIVATE_KEY_PATH");
+        let jwt_verification_keys =
+            get_optional_env_var("JWT_VERIFICATION_KEYS").unwrap_or_default();
+        let jwt_issuer =
+            get_optional_env_var("JWT_ISSUER").unwrap_or("rust_flutter_application".to_string());
+        let jwt_audience =
+            get_optional_env_var("JWT_AUDIENCE").unwrap_or("rust_flutter_application".to_string());
+        let jwt_leeway = get_optional_env_var("JWT_LEEWAY").unwrap_or("60".to_string());
         let jwt_mexage = get_env_var("JWT_MAXAGE");
+        let refresh_token_maxage = get_env_var("REFRESH_TOKEN_MAXAGE");
+        let email_verification_maxage = get_env_var("EMAIL_VERIFICATION_MAXAGE");
+        let password_reset_maxage = get_env_var("PASSWORD_RESET_MAXAGE");
+        let login_free_attempts =
+            get_optional_env_var("LOGIN_FREE_ATTEMPTS").unwrap_or("3".to_string());
+        let login_backoff_base =
+            get_optional_env_var("LOGIN_BACKOFF_BASE").unwrap_or("1".to_string());
+        let login_backoff_max =
+            get_optional_env_var("LOGIN_BACKOFF_MAX").unwrap_or("300".to_string());
+        let login_lockout_threshold =
+            get_optional_env_var("LOGIN_LOCKOUT_THRESHOLD").unwrap_or("10".to_string());
+        let login_ip_lockout_threshold =
+            get_optional_env_var("LOGIN_IP_LOCKOUT_THRESHOLD").unwrap_or("100".to_string());
+        let login_lockout_duration =
+            get_optional_env_var("LOGIN_LOCKOUT_DURATION").unwrap_or("15".to_string());
+        let login_failure_window =
+            get_optional_env_var("LOGIN_FAILURE_WINDOW").unwrap_or("15".to_string());
+        let magic_link_maxage =
+            get_optional_env_var("MAGIC_LINK_MAXAGE").unwrap_or("15".to_string());
+        let app_url = get_env_var("APP_URL");
+        let mailer = get_env_var("MAILER");
+        let mail_from = get_env_var("MAIL_FROM");
+        let mail_outbox_dir =
+            get_optional_env_var("MAIL_OUTBOX_DIR").unwrap_or("outbox".to_string());
+        let mail_template_dir =
+            get_optional_env_var("MAIL_TEMPLATE_DIR").unwrap_or("templates/email".to_string());
+        let default_locale = get_optional_env_var("DEFAULT_LOCALE").unwrap_or("en".to_string());
+        let smtp_host = get_optional_env_var("SMTP_HOST");
+        let smtp_port = get_optional_env_var("SMTP_PORT");
+        let smtp_username = get_optional_env_var("SMTP_USERNAME");
+        let smtp_password = get_optional_env_var("SMTP_PASSWORD");
+        let smtp_tls = get_optional_env_var("SMTP_TLS");
+        let mfa_issuer =
+            get_optional_env_var("MFA_ISSUER").unwrap_or("Rust Flutter Application".to_string());
+        let mfa_required_roles = get_optional_env_var("MFA_REQUIRED_ROLES").unwrap_or_default();
         let port = get_env_var("PORT");
+        let storage = get_optional_env_var("STORAGE").unwrap_or("local".to_string());
+        let storage_local_dir =
+            get_optional_env_var("STORAGE_LOCAL_DIR").unwrap_or("uploads".to_string());
+        let storage_public_url = get_optional_env_var("STORAGE_PUBLIC_URL")
+            .unwrap_or(format!("http://localhost:{}/uploads", port));
+        let s3_bucket = get_optional_env_var("S3_BUCKET");
+        let s3_region = get_optional_env_var("S3_REGION").unwrap_or("us-east-1".to_string());
+        let s3_endpoint = get_optional_env_var("S3_ENDPOINT");
+        let s3_access_key = get_optional_env_var("S3_ACCESS_KEY");
+        let s3_secret_key = get_optional_env_var("S3_SECRET_KEY");
+        let s3_public_url = get_optional_env_var("S3_PUBLIC_URL");
+        let s3_url_expiry = get_optional_env_var("S3_URL_EXPIRY").unwrap_or("3600".to_string());
+        let status_sweep_interval =
+            get_optional_env_var("STATUS_SWEEP_INTERVAL").unwrap_or("60".to_string());
+        let account_deletion_grace_period =
+            get_optional_env_var("ACCOUNT_DELETION_GRACE_PERIOD").unwrap_or("43200".to_string());
+        let account_purge_interval =
+            get_optional_env_var("ACCOUNT_PURGE_INTERVAL").unwrap_or("3600".to_string());
+        let api_url =
+            get_optional_env_var("API_URL").unwrap_or(format!("http://localhost:{}", port));
+        let data_export_maxage =
+            get_optional_env_var("DATA_EXPORT_MAXAGE").unwrap_or("1440".to_string());
+        let data_export_interval =
+            get_optional_env_var("DATA_EXPORT_INTERVAL").unwrap_or("300".to_string());
+        let organization_invitation_maxage =
+            get_optional_env_var("ORGANIZATION_INVITATION_MAXAGE").unwrap_or("10080".to_string());
+        let oidc_providers = get_optional_env_var("OIDC_PROVIDERS").unwrap_or_default();
+        let rate_limit_store =
+            get_optional_env_var("RATE_LIMIT_STORE").unwrap_or("memory".to_string());
+        let redis_url = get_optional_env_var("REDIS_URL");
+        let trusted_proxies = get_optional_env_var("TRUSTED_PROXIES").unwrap_or_default();
+        let avatar_max_size =
+            get_optional_env_var("AVATAR_MAX_SIZE").unwrap_or("5242880".to_string());
 
         Config {
             database_url,
             jwt_secret,
+            jwt_algorithm,
+            jwt_key_id,
+            jwt_private_key_path,
+            jwt_verification_keys: jwt_verification_keys
+                .split(',')
+                .map(|entry| entry.trim())
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| match entry.split_once('=') {
+                    Some((kid, path)) => (kid.trim().to_string(), path.trim().to_string()),
+                    None => panic!("JWT_VERIFICATION_KEYS entry {} must be kid=path", entry),
+                })
+                .collect(),
+            jwt_issuer,
+            jwt_audience,
+            jwt_leeway: jwt_leeway.parse::<u64>().unwrap(),
             jwt_maxage: jwt_mexage.parse::<i64>().unwrap(),
+            refresh_token_maxage: refresh_token_maxage.parse::<i64>().unwrap(),
+            email_verification_maxage: email_verification_maxage.parse::<i64>().unwrap(),
+            password_reset_maxage: password_reset_maxage.parse::<i64>().unwrap(),
+            magic_link_maxage: magic_link_maxage.parse::<i64>().unwrap(),
+            login_free_attempts: login_free_attempts.parse::<i32>().unwrap(),
+            login_backoff_base: login_backoff_base.parse::<i64>().unwrap(),
+            login_backoff_max: login_backoff_max.parse::<i64>().unwrap(),
+            login_lockout_threshold: login_lockout_threshold.parse::<i32>().unwrap(),
+            login_ip_lockout_threshold: login_ip_lockout_threshold.parse::<i32>().unwrap(),
+            login_lockout_duration: login_lockout_duration.parse::<i64>().unwrap(),
+            login_failure_window: login_failure_window.parse::<i64>().unwrap(),
+            app_url,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            mailer,
+            mail_from,
+            mail_outbox_dir,
+            mail_template_dir,
+            default_locale,
+            smtp_host,
+            smtp_port: smtp_port.map(|port| port.parse::<u16>().unwrap()),
+            smtp_username,
+            smtp_password,
+            smtp_tls: smtp_tls.is_none_or(|tls| tls.parse::<bool>().unwrap()),
+            mfa_issuer,
+            mfa_required_roles: mfa_required_roles
+                .split(',')
+                .map(|role| role.trim())
+                .filter(|role| !role.is_empty())
+                .map(|role| role.to_lowercase())
+                .collect(),
+            storage,
+            storage_local_dir,
+            storage_public_url,
+            s3_bucket,
+            s3_region,
+            s3_endpoint,
+            s3_access_key,
+            s3_secret_key,
+            s3_public_url,
+            s3_url_expiry: s3_url_expiry.parse::<u32>().unwrap(),
+            avatar_max_size: avatar_max_size.parse::<usize>().unwrap(),
+            status_sweep_interval: status_sweep_interval.parse::<u64>().unwrap(),
+            account_deletion_grace_period: account_deletion_grace_period.parse::<i64>().unwrap(),
+            account_purge_interval: account_purge_interval.parse::<u64>().unwrap(),
+            data_export_maxage: data_export_maxage.parse::<i64>().unwrap(),
+            data_export_interval: data_export_interval.parse::<u64>().unwrap(),
+            organization_invitation_maxage: organization_invitation_maxage.parse::<i64>().unwrap(),
+            oidc_providers: oidc_providers
+                .split(',')
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map(|name| oidc_provider(&name.to_lowercase()))
+                .collect(),
+            rate_limit_store,
+            redis_url,
+            trusted_proxies: trusted_proxies
+                .split(',')
+                .map(|entry| entry.trim())
+                .filter(|entry| !entry.is_empty())
+                .map(|entry| {
+                    entry
+                        .parse::<TrustedProxy>()
+                        .unwrap_or_else(|e| panic!("TRUSTED_PROXIES: {}", e))
+                })
+                .collect(),
             port: port.parse::<u16>().unwrap(),
         }
     }
+
+    /// Whether holding any of `roles` requires two-factor authentication.
+    pub fn requires_mfa(&self, roles: &[String]) -> bool {
+        roles
+            .iter()
+            .any(|role| self.mfa_required_roles.contains(role))
+    }
 }
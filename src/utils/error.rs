@@ -1,176 +1,845 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{error::JsonPayloadError, http::StatusCode, HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
+use sqlx::mysql::MySqlDatabaseError;
+use utoipa::ToSchema;
+use validator::{ValidationErrors, ValidationErrorsKind};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// MySQL's `ER_DUP_ENTRY`, raised when a write violates a unique key.
+const ER_DUP_ENTRY: u16 = 1062;
+
+/// What clients are told about server errors, the details are only logged.
+pub const SERVER_ERROR_MESSAGE: &str = "Server Error. Please try again later";
+
+/// Body of every error response. `code` is stable and meant for clients to
+/// branch on, `message` is for people and may change. Clients that accept
+/// `application/problem+json` get a `ProblemDetails` instead.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorResponse {
     pub status: String,
+    pub code: String,
     pub message: String,
+    /// What is wrong with each field of the request body, keyed by its name
+    /// in the body such as `passwordConfirm` or `roles[0]`. Only set when the
+    /// body was rejected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<BTreeMap<String, Vec<FieldError>>>,
 }
 
-impl fmt::Display for ErrorResponse {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap())
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct Response {
-    pub status: &'static str,
+/// One problem with a field. `code` names the rule that failed, such as
+/// `length`, `email` or `required`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    pub code: String,
     pub message: String,
 }
 
-#[derive(Debug, PartialEq)]
-pub enum ErrorMessage {
-    EmptyPassword,
-    ExceededMaxPasswordLength(usize),
-    HashingError,
-    InvalidHashFormat,
+/// Every error a request can end in. Each variant has a fixed status code and
+/// machine-readable code, see `status_code` and `code`.
+#[derive(Debug)]
+pub enum AppError {
+    /// The request body failed validation.
+    Validation(ValidationErrors),
+    /// A field of the JSON body is missing or has the wrong type.
+    InvalidField {
+        field: String,
+        code: &'static str,
+        message: String,
+    },
+    /// The request body could not be read, such as malformed multipart data.
+    InvalidPayload(String),
+    /// A password `password::hash` or `password::compare` refused.
+    InvalidPassword(String),
     InvalidToken,
-    ServerError,
     WrongCredentials,
     EmailExist,
     UserNoLongerExist,
     TokenNotProvided,
     PermissionDenied,
+    InsufficientScope,
+    RefreshTokenNotProvided,
+    InvalidRefreshToken,
+    RefreshTokenReused,
+    SessionRevoked,
+    SessionNotFound,
+    InvalidVerificationToken,
+    EmailNotVerified,
+    InvalidResetToken,
+    WrongCurrentPassword,
+    MfaAlreadyEnabled,
+    MfaNotEnrolled,
+    MfaCodeNotProvided,
+    InvalidMfaCode,
+    /// A wrong code while confirming enrollment, where it is not a failed
+    /// sign-in.
+    InvalidEnrollmentCode,
+    /// A wrong code from a signed-in user, which must not read as a failed
+    /// sign-in either.
+    WrongCurrentMfaCode,
+    MfaEnrollmentRequired,
+    MfaRequiredForRole,
+    PhotoNotProvided,
+    InvalidImage,
+    PhotoTooLarge(usize),
+    UserNotFound,
+    CannotChangeOwnRole,
+    AccountSuspended(Option<chrono::DateTime<chrono::Utc>>),
+    AccountBanned,
+    CannotChangeOwnStatus,
+    InvalidStatusChange,
+    DataExportInProgress,
+    DataExportNotFound,
+    InvalidDownloadToken,
+    RoleNotFound,
+    RoleExist,
+    SystemRoleImmutable,
+    UnknownRole(String),
+    UnknownPermission(String),
+    OrganizationNotFound,
+    OrganizationSlugExist,
+    OrganizationMemberNotFound,
+    AlreadyOrganizationMember,
+    LastOrganizationOwner,
+    NoActiveOrganization,
+    OrganizationMismatch,
+    InvitationNotFound,
+    InvalidInvitationToken,
+    InvitationEmailMismatch,
+    InvalidApiKey,
+    ApiKeyNotAllowed,
+    ApiKeyNotFound,
+    ScopeNotGranted(String),
+    OidcProviderNotFound,
+    InvalidIdToken,
+    IdentityEmailNotVerified,
+    IdentityAlreadyLinked,
+    ProviderAlreadyLinked(String),
+    IdentityNotFound,
+    LastSignInMethod,
+    UnverifiedAccountExists,
+    InvalidMagicLink,
+    MagicLinkNotProvided,
+    TooManyRequests,
+    /// A write hit a unique key nobody mapped to a specific error, holds the
+    /// key name.
+    UniqueViolation(String),
+    /// Any other database failure. The details are logged, never sent.
+    Database(sqlx::Error),
+    /// Anything else that went wrong on our side. The details are logged,
+    /// never sent.
+    Internal(String),
 }
 
-impl ToString for ErrorMessage {
-    fn to_string(&self) -> String {
-        self.to_str().to_owned()
+impl AppError {
+    /// Wraps a failure that has no variant of its own, such as a storage or
+    /// mailer error.
+    pub fn internal(e: impl ToString) -> Self {
+        AppError::Internal(e.to_string())
+    }
+
+    /// Swaps a unique violation for the error the caller knows it means,
+    /// such as `EmailExist` for a duplicate email. Other errors pass through.
+    pub fn on_conflict(self, error: AppError) -> Self {
+        match self {
+            AppError::UniqueViolation(_) => error,
+            other => other,
+        }
     }
-}
 
-impl Into<String> for ErrorMessage {
-    fn into(self) -> String {
-        self.to_string()
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation(_) | AppError::InvalidField { .. } => "validation_failed",
+            AppError::InvalidPayload(_) => "invalid_payload",
+            AppError::InvalidPassword(_) => "invalid_password",
+            AppError::InvalidToken => "invalid_token",
+            AppError::WrongCredentials => "wrong_credentials",
+            AppError::EmailExist => "email_exists",
+            AppError::UserNoLongerExist => "user_no_longer_exists",
+            AppError::TokenNotProvided => "token_not_provided",
+            AppError::PermissionDenied => "permission_denied",
+            AppError::InsufficientScope => "insufficient_scope",
+            AppError::RefreshTokenNotProvided => "refresh_token_not_provided",
+            AppError::InvalidRefreshToken => "invalid_refresh_token",
+            AppError::RefreshTokenReused => "refresh_token_reused",
+            AppError::SessionRevoked => "session_revoked",
+            AppError::SessionNotFound => "session_not_found",
+            AppError::InvalidVerificationToken => "invalid_verification_token",
+            AppError::EmailNotVerified => "email_not_verified",
+            AppError::InvalidResetToken => "invalid_reset_token",
+            AppError::WrongCurrentPassword => "wrong_current_password",
+            AppError::MfaAlreadyEnabled => "mfa_already_enabled",
+            AppError::MfaNotEnrolled => "mfa_not_enrolled",
+            AppError::MfaCodeNotProvided => "mfa_code_not_provided",
+            AppError::InvalidMfaCode => "invalid_mfa_code",
+            AppError::InvalidEnrollmentCode => "invalid_enrollment_code",
+            AppError::WrongCurrentMfaCode => "wrong_current_mfa_code",
+            AppError::MfaEnrollmentRequired => "mfa_enrollment_required",
+            AppError::MfaRequiredForRole => "mfa_required_for_role",
+            AppError::PhotoNotProvided => "photo_not_provided",
+            AppError::InvalidImage => "invalid_image",
+            AppError::PhotoTooLarge(_) => "photo_too_large",
+            AppError::UserNotFound => "user_not_found",
+            AppError::CannotChangeOwnRole => "cannot_change_own_role",
+            AppError::AccountSuspended(_) => "account_suspended",
+            AppError::AccountBanned => "account_banned",
+            AppError::CannotChangeOwnStatus => "cannot_change_own_status",
+            AppError::InvalidStatusChange => "invalid_status_change",
+            AppError::DataExportInProgress => "data_export_in_progress",
+            AppError::DataExportNotFound => "data_export_not_found",
+            AppError::InvalidDownloadToken => "invalid_download_token",
+            AppError::RoleNotFound => "role_not_found",
+            AppError::RoleExist => "role_exists",
+            AppError::SystemRoleImmutable => "system_role_immutable",
+            AppError::UnknownRole(_) => "unknown_role",
+            AppError::UnknownPermission(_) => "unknown_permission",
+            AppError::OrganizationNotFound => "organization_not_found",
+            AppError::OrganizationSlugExist => "organization_slug_exists",
+            AppError::OrganizationMemberNotFound => "organization_member_not_found",
+            AppError::AlreadyOrganizationMember => "already_organization_member",
+            AppError::LastOrganizationOwner => "last_organization_owner",
+            AppError::NoActiveOrganization => "no_active_organization",
+            AppError::OrganizationMismatch => "organization_mismatch",
+            AppError::InvitationNotFound => "invitation_not_found",
+            AppError::InvalidInvitationToken => "invalid_invitation_token",
+            AppError::InvitationEmailMismatch => "invitation_email_mismatch",
+            AppError::InvalidApiKey => "invalid_api_key",
+            AppError::ApiKeyNotAllowed => "api_key_not_allowed",
+            AppError::ApiKeyNotFound => "api_key_not_found",
+            AppError::ScopeNotGranted(_) => "scope_not_granted",
+            AppError::OidcProviderNotFound => "oidc_provider_not_found",
+            AppError::InvalidIdToken => "invalid_id_token",
+            AppError::IdentityEmailNotVerified => "identity_email_not_verified",
+            AppError::IdentityAlreadyLinked => "identity_already_linked",
+            AppError::ProviderAlreadyLinked(_) => "provider_already_linked",
+            AppError::IdentityNotFound => "identity_not_found",
+            AppError::LastSignInMethod => "last_sign_in_method",
+            AppError::UnverifiedAccountExists => "unverified_account_exists",
+            AppError::InvalidMagicLink => "invalid_magic_link",
+            AppError::MagicLinkNotProvided => "magic_link_not_provided",
+            AppError::TooManyRequests => "too_many_requests",
+            AppError::UniqueViolation(_) => "conflict",
+            AppError::Database(_) => "database_error",
+            AppError::Internal(_) => "internal_error",
+        }
     }
-}
 
-impl ErrorMessage {
-    fn to_str(&self) -> String {
+    /// The message sent to the client. Server errors get a generic one.
+    pub fn message(&self) -> String {
         match self {
-            ErrorMessage::ServerError => "Server Error. Please try again later".to_string(),
-            ErrorMessage::WrongCredentials => "Email or password is wrong".to_string(),
-            ErrorMessage::EmailExist => "An User with this email already exists".to_string(),
-            ErrorMessage::UserNoLongerExist => {
-                "User belonging to this token no longer exists".to_string()
+            AppError::Validation(_) | AppError::InvalidField { .. } => {
+                "Some fields are invalid".to_string()
+            }
+            AppError::InvalidPayload(message) | AppError::InvalidPassword(message) => {
+                message.clone()
             }
-            ErrorMessage::EmptyPassword => "Password cannot be empty".to_string(),
-            ErrorMessage::HashingError => "Error while hashing password".to_string(),
-            ErrorMessage::InvalidHashFormat => "Invalid password hash format".to_string(),
-            ErrorMessage::ExceededMaxPasswordLength(max_length) => {
-                format!("Password must not be more than {} characters", max_length)
+            AppError::WrongCredentials => "Email or password is wrong".to_string(),
+            AppError::EmailExist => "An User with this email already exists".to_string(),
+            AppError::UserNoLongerExist => {
+                "User belonging to this token no longer exists".to_string()
             }
-            ErrorMessage::InvalidToken => "Authentication token is invalid or expired".to_string(),
-            ErrorMessage::TokenNotProvided => {
+            AppError::InvalidToken => "Authentication token is invalid or expired".to_string(),
+            AppError::TokenNotProvided => {
                 "You are not logged in, please provide token".to_string()
             }
-            ErrorMessage::PermissionDenied => {
+            AppError::PermissionDenied => {
                 "You are not allowed to perform this action".to_string()
             }
+            AppError::InsufficientScope => {
+                "Authentication token does not grant access to this resource".to_string()
+            }
+            AppError::RefreshTokenNotProvided => "Refresh token was not provided".to_string(),
+            AppError::InvalidRefreshToken => "Refresh token is invalid or expired".to_string(),
+            AppError::RefreshTokenReused => {
+                "Refresh token has already been used, please log in again".to_string()
+            }
+            AppError::SessionRevoked => {
+                "Session has been revoked or expired, please log in again".to_string()
+            }
+            AppError::SessionNotFound => "Session not found".to_string(),
+            AppError::InvalidVerificationToken => {
+                "Verification token is invalid or expired".to_string()
+            }
+            AppError::EmailNotVerified => {
+                "Please verify your email address to perform this action".to_string()
+            }
+            AppError::InvalidResetToken => {
+                "Password reset token is invalid or expired".to_string()
+            }
+            AppError::WrongCurrentPassword => "Current password is wrong".to_string(),
+            AppError::MfaAlreadyEnabled => {
+                "Two-factor authentication is already enabled".to_string()
+            }
+            AppError::MfaNotEnrolled => {
+                "Two-factor authentication has not been set up".to_string()
+            }
+            AppError::MfaCodeNotProvided => {
+                "Provide either an authentication code or a recovery code".to_string()
+            }
+            AppError::InvalidMfaCode
+            | AppError::InvalidEnrollmentCode
+            | AppError::WrongCurrentMfaCode => {
+                "Authentication code is invalid".to_string()
+            }
+            AppError::MfaEnrollmentRequired => {
+                "Two-factor authentication is required for your account, please enable it"
+                    .to_string()
+            }
+            AppError::MfaRequiredForRole => {
+                "Two-factor authentication cannot be disabled for your role".to_string()
+            }
+            AppError::PhotoNotProvided => {
+                "Upload the photo as multipart form data in a field named photo".to_string()
+            }
+            AppError::InvalidImage => {
+                "Photo must be a PNG, JPEG, WebP or GIF image".to_string()
+            }
+            AppError::PhotoTooLarge(max_size) => {
+                format!("Photo must not be larger than {} bytes", max_size)
+            }
+            AppError::UserNotFound => "User not found".to_string(),
+            AppError::CannotChangeOwnRole => "You cannot change your own role".to_string(),
+            AppError::AccountSuspended(Some(until)) => {
+                format!("Your account is suspended until {}", until.to_rfc3339())
+            }
+            AppError::AccountSuspended(None) => "Your account is suspended".to_string(),
+            AppError::AccountBanned => "Your account has been banned".to_string(),
+            AppError::CannotChangeOwnStatus => {
+                "You cannot suspend or ban your own account".to_string()
+            }
+            AppError::InvalidStatusChange => {
+                "Status must be suspended or banned, clear it to reactivate the account".to_string()
+            }
+            AppError::DataExportInProgress => {
+                "A data export is already being prepared, you will get an email when it is ready"
+                    .to_string()
+            }
+            AppError::DataExportNotFound => "Data export not found".to_string(),
+            AppError::InvalidDownloadToken => {
+                "Download link is invalid or has expired".to_string()
+            }
+            AppError::RoleNotFound => "Role not found".to_string(),
+            AppError::RoleExist => "A role with this name already exists".to_string(),
+            AppError::SystemRoleImmutable => {
+                "Built-in roles cannot be renamed or deleted".to_string()
+            }
+            AppError::UnknownRole(name) => format!("Role {} does not exist", name),
+            AppError::UnknownPermission(name) => {
+                format!("Permission {} does not exist", name)
+            }
+            AppError::OrganizationNotFound => "Organization not found".to_string(),
+            AppError::OrganizationSlugExist => {
+                "An organization with this slug already exists".to_string()
+            }
+            AppError::OrganizationMemberNotFound => "Member not found".to_string(),
+            AppError::AlreadyOrganizationMember => {
+                "This user is already a member of the organization".to_string()
+            }
+            AppError::LastOrganizationOwner => {
+                "An organization must keep at least one owner".to_string()
+            }
+            AppError::NoActiveOrganization => {
+                "Switch to an organization before accessing it".to_string()
+            }
+            AppError::OrganizationMismatch => {
+                "Authentication token is not valid for this organization".to_string()
+            }
+            AppError::InvitationNotFound => "Invitation not found".to_string(),
+            AppError::InvalidInvitationToken => {
+                "Invitation is invalid, expired or has already been accepted".to_string()
+            }
+            AppError::InvitationEmailMismatch => {
+                "This invitation was sent to a different email address".to_string()
+            }
+            AppError::InvalidApiKey => "API key is invalid or expired".to_string(),
+            AppError::ApiKeyNotAllowed => {
+                "This action requires signing in, API keys cannot be used for it".to_string()
+            }
+            AppError::ApiKeyNotFound => "API key not found".to_string(),
+            AppError::ScopeNotGranted(name) => {
+                format!("You cannot grant {} because you do not have it", name)
+            }
+            AppError::OidcProviderNotFound => "Sign-in provider not found".to_string(),
+            AppError::InvalidIdToken => "ID token is invalid or expired".to_string(),
+            AppError::IdentityEmailNotVerified => {
+                "The provider did not confirm that this email address is verified".to_string()
+            }
+            AppError::IdentityAlreadyLinked => {
+                "This provider account is already linked to another user".to_string()
+            }
+            AppError::ProviderAlreadyLinked(provider) => {
+                format!("A {} account is already linked, unlink it first", provider)
+            }
+            AppError::IdentityNotFound => "No account from this provider is linked".to_string(),
+            AppError::LastSignInMethod => {
+                "Set a password before unlinking your last sign-in provider".to_string()
+            }
+            AppError::UnverifiedAccountExists => {
+                "An unverified account uses this email, verify it or sign in with its password to link this provider"
+                    .to_string()
+            }
+            AppError::InvalidMagicLink => {
+                "Sign-in link or code is invalid or has expired".to_string()
+            }
+            AppError::MagicLinkNotProvided => {
+                "Provide either the token from the link or the email and code".to_string()
+            }
+            AppError::TooManyRequests => {
+                "Too many requests, please try again later".to_string()
+            }
+            AppError::UniqueViolation(_) => {
+                "This conflicts with an existing record".to_string()
+            }
+            AppError::Database(_) | AppError::Internal(_) => SERVER_ERROR_MESSAGE.to_string(),
         }
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct HttpError {
-    pub message: String,
-    pub status: u16,
+    /// The problems per field for a rejected request body, `None` for any
+    /// other error.
+    pub fn field_errors(&self) -> Option<BTreeMap<String, Vec<FieldError>>> {
+        match self {
+            AppError::Validation(errors) => {
+                let mut fields = BTreeMap::new();
+                collect_field_errors(errors, "", &mut fields);
+                Some(fields)
+            }
+            AppError::InvalidField {
+                field,
+                code,
+                message,
+            } => Some(BTreeMap::from([(
+                field.clone(),
+                vec![FieldError {
+                    code: code.to_string(),
+                    message: message.clone(),
+                }],
+            )])),
+            _ => None,
+        }
+    }
 }
 
-impl HttpError {
-    pub fn new(message: impl Into<String>, status: u16) -> Self {
-        HttpError {
-            message: message.into(),
-            status,
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::InvalidPayload(_)
+            | AppError::InvalidVerificationToken
+            | AppError::InvalidResetToken
+            | AppError::WrongCurrentPassword
+            | AppError::MfaNotEnrolled
+            | AppError::MfaCodeNotProvided
+            | AppError::PhotoNotProvided
+            | AppError::InvalidInvitationToken
+            | AppError::MagicLinkNotProvided => StatusCode::BAD_REQUEST,
+            AppError::InvalidToken
+            | AppError::WrongCredentials
+            | AppError::UserNoLongerExist
+            | AppError::TokenNotProvided
+            | AppError::RefreshTokenNotProvided
+            | AppError::InvalidRefreshToken
+            | AppError::RefreshTokenReused
+            | AppError::SessionRevoked
+            | AppError::InvalidMfaCode
+            | AppError::InvalidApiKey
+            | AppError::InvalidIdToken
+            | AppError::InvalidMagicLink => StatusCode::UNAUTHORIZED,
+            AppError::PermissionDenied
+            | AppError::InsufficientScope
+            | AppError::EmailNotVerified
+            | AppError::MfaEnrollmentRequired
+            | AppError::MfaRequiredForRole
+            | AppError::CannotChangeOwnRole
+            | AppError::AccountSuspended(_)
+            | AppError::AccountBanned
+            | AppError::CannotChangeOwnStatus
+            | AppError::SystemRoleImmutable
+            | AppError::NoActiveOrganization
+            | AppError::OrganizationMismatch
+            | AppError::InvitationEmailMismatch
+            | AppError::ApiKeyNotAllowed
+            | AppError::ScopeNotGranted(_) => StatusCode::FORBIDDEN,
+            AppError::SessionNotFound
+            | AppError::UserNotFound
+            | AppError::DataExportNotFound
+            | AppError::InvalidDownloadToken
+            | AppError::RoleNotFound
+            | AppError::OrganizationNotFound
+            | AppError::OrganizationMemberNotFound
+            | AppError::InvitationNotFound
+            | AppError::ApiKeyNotFound
+            | AppError::OidcProviderNotFound
+            | AppError::IdentityNotFound => StatusCode::NOT_FOUND,
+            AppError::EmailExist
+            | AppError::MfaAlreadyEnabled
+            | AppError::DataExportInProgress
+            | AppError::RoleExist
+            | AppError::OrganizationSlugExist
+            | AppError::AlreadyOrganizationMember
+            | AppError::LastOrganizationOwner
+            | AppError::IdentityAlreadyLinked
+            | AppError::ProviderAlreadyLinked(_)
+            | AppError::LastSignInMethod
+            | AppError::UnverifiedAccountExists
+            | AppError::UniqueViolation(_) => StatusCode::CONFLICT,
+            AppError::PhotoTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::Validation(_)
+            | AppError::InvalidField { .. }
+            | AppError::InvalidPassword(_)
+            | AppError::InvalidEnrollmentCode
+            | AppError::WrongCurrentMfaCode
+            | AppError::InvalidImage
+            | AppError::InvalidStatusChange
+            | AppError::UnknownRole(_)
+            | AppError::UnknownPermission(_)
+            | AppError::IdentityEmailNotVerified => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
-    pub fn server_error(message: impl Into<String>) -> Self {
-        HttpError {
-            message: message.into(),
-            status: 500,
-        }
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+
+        HttpResponse::build(status).json(ErrorResponse {
+            status: if status.is_server_error() {
+                "error"
+            } else {
+                "fail"
+            }
+            .to_string(),
+            code: self.code().to_string(),
+            message: self.message(),
+            errors: self.field_errors(),
+        })
     }
+}
 
-    pub fn bad_request(message: impl Into<String>) -> Self {
-        HttpError {
-            message: message.into(),
-            status: 400,
+/// Shows the details, also of server errors, for logs.
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::UniqueViolation(key) => write!(f, "Duplicate entry for key {}", key),
+            AppError::Database(e) => write!(f, "Database error: {}", e),
+            AppError::Internal(message) => write!(f, "{}", message),
+            _ => write!(f, "{}", self.message()),
         }
     }
+}
 
-    pub fn unique_constraint_voilation(message: impl Into<String>) -> Self {
-        HttpError {
-            message: message.into(),
-            status: 409,
+impl std::error::Error for AppError {}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(db_error) = &e {
+            if let Some(mysql_error) = db_error.try_downcast_ref::<MySqlDatabaseError>() {
+                if mysql_error.number() == ER_DUP_ENTRY {
+                    return AppError::UniqueViolation(duplicate_key(mysql_error.message()));
+                }
+            }
         }
+
+        AppError::Database(e)
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::Validation(errors)
     }
+}
 
-    pub fn unauthorized(message: impl Into<String>) -> Self {
-        HttpError {
-            message: message.into(),
-            status: 401,
+/// Bodies `web::Json` could not read. The extractor is configured in `main`
+/// to answer with these instead of actix's plain text errors.
+impl From<JsonPayloadError> for AppError {
+    fn from(e: JsonPayloadError) -> Self {
+        match e {
+            JsonPayloadError::Deserialize(e) => json_error(e, None),
+            e => AppError::InvalidPayload(e.to_string()),
         }
     }
+}
 
-    // pub fn not_found(message: impl Into<String>) -> Self {
-    //     HttpError {
-    //         message: message.into(),
-    //         status: 404,
-    //     }
-    // }
-
-    pub fn into_http_response(self) -> HttpResponse {
-        match self.status {
-            400 => HttpResponse::BadRequest().json(Response {
-                status: "fail",
-                message: self.message.into(),
-            }),
-            401 => HttpResponse::Unauthorized().json(Response {
-                status: "fail",
-                message: self.message.into(),
-            }),
-            409 => HttpResponse::Conflict().json(Response {
-                status: "fail",
-                message: self.message.into(),
-            }),
-            500 => HttpResponse::InternalServerError().json(Response {
-                status: "error",
-                message: self.message.into(),
-            }),
-            _ => {
-                eprintln!(
-                    "Warning: Missing pattern match. Converted status code {} to 500.",
-                    self.status
-                );
-
-                HttpResponse::InternalServerError().json(Response {
-                    status: "error",
-                    message: ErrorMessage::ServerError.into(),
-                })
+impl From<serde_path_to_error::Error<serde_json::Error>> for AppError {
+    fn from(e: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = e.path().to_string();
+        // The path of an error in the top-level value itself is ".".
+        let path = (path != ".").then_some(path);
+
+        json_error(e.into_inner(), path)
+    }
+}
+
+/// Blames the field at `path` for a JSON body that has the wrong shape.
+/// Malformed JSON, and errors no field can be blamed for, are a bad payload.
+fn json_error(e: serde_json::Error, path: Option<String>) -> AppError {
+    if !e.is_data() {
+        return AppError::InvalidPayload(format!("Request body is not valid JSON: {}", e));
+    }
+
+    // serde_json appends the position, which means nothing to a form.
+    let message = e.to_string();
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(message, _)| message);
+
+    // A missing field is reported on the struct that lacks it.
+    if let Some(name) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+    {
+        let field = match path {
+            Some(path) => format!("{}.{}", path, name),
+            None => name.to_string(),
+        };
+
+        return AppError::InvalidField {
+            field,
+            code: "required",
+            message: "This field is required".to_string(),
+        };
+    }
+
+    match path {
+        Some(field) => AppError::InvalidField {
+            field,
+            code: "invalid_type",
+            message: message.to_string(),
+        },
+        None => AppError::InvalidPayload(message.to_string()),
+    }
+}
+
+/// Flattens nested validation errors into `fields`, naming nested fields
+/// `address.street` and list items `roles[0]`.
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    prefix: &str,
+    fields: &mut BTreeMap<String, Vec<FieldError>>,
+) {
+    for (name, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                fields
+                    .entry(path)
+                    .or_default()
+                    .extend(errors.iter().map(|error| {
+                        FieldError {
+                            code: error.code.to_string(),
+                            message: error
+                                .message
+                                .as_ref()
+                                .map_or_else(|| "Invalid value".to_string(), |m| m.to_string()),
+                        }
+                    }))
+            }
+            ValidationErrorsKind::Struct(errors) => collect_field_errors(errors, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(errors, &format!("{}[{}]", path, index), fields);
+                }
             }
         }
     }
 }
 
-impl fmt::Display for HttpError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "HttpError: message: {}, status: {}",
-            self.message, self.status
-        )
-    }
+/// The key named in "Duplicate entry 'x' for key 'users.email'", without the
+/// table MySQL 8 puts in front.
+fn duplicate_key(message: &str) -> String {
+    message
+        .rsplit_once("for key '")
+        .map(|(_, key)| key.trim_end_matches('\''))
+        .map(|key| key.rsplit('.').next().unwrap_or(key))
+        .unwrap_or_default()
+        .to_string()
 }
 
-impl std::error::Error for HttpError {}
+#[cfg(test)]
+mod tests {
+    use validator::Validate;
+
+    use super::*;
+
+    /// `(field, code)` of the error a body is rejected with, the field is
+    /// empty for a bad payload.
+    fn blame(error: AppError) -> (String, &'static str) {
+        match error {
+            AppError::InvalidField {
+                field,
+                code,
+                message,
+            } => {
+                assert!(!message.contains(" at line "), "{}", message);
+                (field, code)
+            }
+            AppError::InvalidPayload(_) => (String::new(), "invalid_payload"),
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn names_the_violated_key() {
+        let cases = [
+            ("Duplicate entry 'a@b.c' for key 'users.email'", "email"),
+            ("Duplicate entry 'a@b.c' for key 'email'", "email"),
+            (
+                "Duplicate entry 'u-r' for key 'user_roles.PRIMARY'",
+                "PRIMARY",
+            ),
+            ("Duplicate entry 'for key 'x'' for key 'roles.name'", "name"),
+            ("Something else went wrong", ""),
+        ];
+
+        for (message, key) in cases {
+            assert_eq!(duplicate_key(message), key, "{}", message);
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Address {
+        street: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Body {
+        name: String,
+        age: u8,
+        address: Option<Address>,
+        tags: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn blames_the_field_at_the_path() {
+        let cases = [
+            (r#"{"age": 1}"#, "name", "required"),
+            (r#"{"name": "a", "age": "x"}"#, "age", "invalid_type"),
+            (r#"{"name": "a", "age": 300}"#, "age", "invalid_type"),
+            (
+                r#"{"name": "a", "age": 1, "address": {}}"#,
+                "address.street",
+                "required",
+            ),
+            (
+                r#"{"name": "a", "age": 1, "address": {"street": 1}}"#,
+                "address.street",
+                "invalid_type",
+            ),
+            (
+                r#"{"name": "a", "age": 1, "tags": ["a", 2]}"#,
+                "tags[1]",
+                "invalid_type",
+            ),
+            (r#"[]"#, "", "invalid_payload"),
+            (r#"{"name": "#, "", "invalid_payload"),
+            (r#"{"name": "a" "age": 1}"#, "", "invalid_payload"),
+        ];
+
+        for (json, field, code) in cases {
+            let deserializer = &mut serde_json::Deserializer::from_str(json);
+            let error = serde_path_to_error::deserialize::<_, Body>(deserializer).unwrap_err();
+
+            assert_eq!(
+                blame(AppError::from(error)),
+                (field.to_string(), code),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn blames_only_missing_fields_without_a_path() {
+        let cases = [
+            (r#"{"age": 1}"#, "name", "required"),
+            (r#"{"name": "a", "age": "x"}"#, "", "invalid_payload"),
+        ];
+
+        for (json, field, code) in cases {
+            let error = serde_json::from_str::<Body>(json).unwrap_err();
+
+            assert_eq!(
+                blame(AppError::from(JsonPayloadError::Deserialize(error))),
+                (field.to_string(), code),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[derive(Validate)]
+    struct Street {
+        #[validate(length(min = 1, message = "Street is required"))]
+        street: String,
+    }
+
+    #[derive(Validate)]
+    struct Form {
+        #[validate(email(message = "Email is invalid"), length(max = 5))]
+        email: String,
+        #[validate]
+        address: Street,
+        #[validate]
+        items: Vec<Street>,
+    }
+
+    #[test]
+    fn flattens_nested_validation_errors() {
+        let form = Form {
+            email: "not-an-email".to_string(),
+            address: Street {
+                street: String::new(),
+            },
+            items: vec![
+                Street {
+                    street: "Main St".to_string(),
+                },
+                Street {
+                    street: String::new(),
+                },
+            ],
+        };
+
+        let fields = AppError::from(form.validate().unwrap_err())
+            .field_errors()
+            .unwrap()
+            .into_iter()
+            .map(|(field, errors)| {
+                let mut errors = errors
+                    .into_iter()
+                    .map(|error| (error.code, error.message))
+                    .collect::<Vec<_>>();
+                errors.sort();
+                (field, errors)
+            })
+            .collect::<Vec<_>>();
 
-impl ResponseError for HttpError {
-    fn error_response(&self) -> HttpResponse<actix_web::body::BoxBody> {
-        let cloned = self.clone();
-        cloned.into_http_response()
+        let error = |code: &str, message: &str| (code.to_string(), message.to_string());
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    "address.street".to_string(),
+                    vec![error("length", "Street is required")]
+                ),
+                (
+                    "email".to_string(),
+                    vec![
+                        error("email", "Email is invalid"),
+                        error("length", "Invalid value")
+                    ]
+                ),
+                (
+                    "items[1].street".to_string(),
+                    vec![error("length", "Street is required")]
+                ),
+            ]
+        );
     }
 }
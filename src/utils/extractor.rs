@@ -5,7 +5,6 @@ use std::{
 
 use actix_web::{
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
-    error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized},
     http, web, FromRequest, HttpMessage,
 };
 use futures_util::{
@@ -14,16 +13,31 @@ use futures_util::{
 };
 
 use crate::{
-    models::user::{UserModel, UserRole},
-    services::user_services::UserService,
+    models::{
+        api_key::ApiKeyModel,
+        organization::{OrganizationMemberModel, OrganizationRole},
+        session::SessionModel,
+        user::UserModel,
+    },
+    services::{
+        api_key_service::{ApiKeyService, ApiKeyWithScopes},
+        organization_service::OrganizationService,
+        role_service::RoleService,
+        session_service::SessionService,
+        user_services::UserService,
+    },
     AppState,
 };
 
 use super::{
-    error::{ErrorMessage, ErrorResponse, HttpError},
-    token,
+    error::AppError,
+    token::{self, TokenClaims},
 };
 
+/// Header integrations may send an API key in, as an alternative to
+/// `Authorization: Bearer <key>`. Lowercase so it can be a `HeaderName`.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
 pub struct Authenticated(UserModel);
 
 impl FromRequest for Authenticated {
@@ -37,9 +51,7 @@ impl FromRequest for Authenticated {
         let value = req.extensions().get::<UserModel>().cloned();
         let result = match value {
             Some(user) => Ok(Authenticated(user)),
-            None => Err(ErrorInternalServerError(HttpError::server_error(
-                "Authentication Error",
-            ))),
+            None => Err(AppError::internal("Authentication Error").into()),
         };
         ready(result)
     }
@@ -53,16 +65,217 @@ impl std::ops::Deref for Authenticated {
     }
 }
 
+/// The session the current access token was issued for.
+pub struct CurrentSession(SessionModel);
+
+impl FromRequest for CurrentSession {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        _payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let value = req.extensions().get::<SessionModel>().cloned();
+        let result = match value {
+            Some(session) => Ok(CurrentSession(session)),
+            None => Err(AppError::internal("Authentication Error").into()),
+        };
+        ready(result)
+    }
+}
+
+impl std::ops::Deref for CurrentSession {
+    type Target = SessionModel;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Claims of the access token the request was authenticated with. Available
+/// on every route behind `RequireAuth`, including stateless ones.
+pub struct AuthClaims(TokenClaims);
+
+impl FromRequest for AuthClaims {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        _payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let value = req.extensions().get::<TokenClaims>().cloned();
+        let result = match value {
+            Some(claims) => Ok(AuthClaims(claims)),
+            None => Err(AppError::internal("Authentication Error").into()),
+        };
+        ready(result)
+    }
+}
+
+impl AuthClaims {
+    /// For handlers that need a permission beyond the ones their route
+    /// requires.
+    pub fn require_scope(&self, scope: &str) -> Result<(), AppError> {
+        if self.0.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(AppError::InsufficientScope)
+        }
+    }
+}
+
+impl std::ops::Deref for AuthClaims {
+    type Target = TokenClaims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Membership of the current user in the organization named by the
+/// `{org_id}` path segment. Only resolves when that organization is the one
+/// in the token's `org` claim, so a token switched to one tenant cannot reach
+/// another. The membership is read fresh, a removed member is refused even
+/// while their token is still valid.
+pub struct CurrentMembership(OrganizationMemberModel);
+
+impl FromRequest for CurrentMembership {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        _payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let claims = req.extensions().get::<TokenClaims>().cloned();
+        let organization_id = req.match_info().get("org_id").map(|id| id.to_string());
+        let app_state = req.app_data::<web::Data<AppState>>().cloned();
+
+        async move {
+            let (Some(claims), Some(organization_id), Some(app_state)) =
+                (claims, organization_id, app_state)
+            else {
+                return Err(AppError::internal("Authentication Error").into());
+            };
+
+            let active_organization = claims
+                .org
+                .as_deref()
+                .ok_or(AppError::NoActiveOrganization)?;
+            if active_organization != organization_id {
+                return Err(AppError::OrganizationMismatch.into());
+            }
+
+            let membership = OrganizationService::new(app_state.db.clone())
+                .get_membership(&organization_id, &claims.sub)
+                .await
+                .map_err(AppError::from)?
+                .ok_or(AppError::OrganizationMismatch)?;
+
+            Ok(CurrentMembership(membership))
+        }
+        .boxed_local()
+    }
+}
+
+impl CurrentMembership {
+    /// Refuses members whose role grants less than `role`.
+    pub fn require(&self, role: OrganizationRole) -> Result<(), AppError> {
+        if self.0.role.includes(role) {
+            Ok(())
+        } else {
+            Err(AppError::PermissionDenied)
+        }
+    }
+}
+
+impl std::ops::Deref for CurrentMembership {
+    type Target = OrganizationMemberModel;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 pub struct RequireAuth {
-    pub allowed_roles: Rc<Vec<UserRole>>,
+    /// `None` lets users with any role through.
+    pub allowed_roles: Option<Rc<Vec<String>>>,
+    pub required_permissions: Rc<Vec<String>>,
+    pub require_verified: bool,
+    pub allow_without_mfa: bool,
+    pub allow_api_keys: bool,
+    pub stateless: bool,
 }
 
 impl RequireAuth {
-    pub fn allowed_roles(allowed_roles: Vec<UserRole>) -> Self {
+    /// Lets any signed-in user through.
+    pub fn authenticated() -> Self {
         RequireAuth {
-            allowed_roles: Rc::new(allowed_roles),
+            allowed_roles: None,
+            required_permissions: Rc::new(Vec::new()),
+            require_verified: false,
+            allow_without_mfa: false,
+            allow_api_keys: true,
+            stateless: false,
         }
     }
+
+    /// Requires at least one of the roles named.
+    pub fn allowed_roles(allowed_roles: &[&str]) -> Self {
+        RequireAuth {
+            allowed_roles: Some(Rc::new(
+                allowed_roles.iter().map(|role| role.to_string()).collect(),
+            )),
+            ..Self::authenticated()
+        }
+    }
+
+    /// Requires every one of `permissions`, both granted to the user through
+    /// their roles and present in the token's `scope` claim.
+    pub fn permissions(permissions: &[&str]) -> Self {
+        RequireAuth {
+            required_permissions: Rc::new(
+                permissions
+                    .iter()
+                    .map(|permission| permission.to_string())
+                    .collect(),
+            ),
+            ..Self::authenticated()
+        }
+    }
+
+    /// Authorizes on the token's `roles` and `scope` claims alone, without
+    /// reading the session or the user. A revoked session, a changed role or
+    /// a suspension is only noticed once the token expires, and handlers can only use
+    /// `AuthClaims`. Checks that need the user, such as `verified()` or the
+    /// two-factor requirement for the token's roles, still read it. API keys
+    /// are always looked up.
+    pub fn stateless(mut self) -> Self {
+        self.stateless = true;
+        self
+    }
+
+    /// Also refuses accounts that have not verified their email address.
+    pub fn verified(mut self) -> Self {
+        self.require_verified = true;
+        self
+    }
+
+    /// Lets accounts whose role requires two-factor authentication through
+    /// even before they enabled it. Meant for the routes they need to enroll.
+    pub fn allow_without_mfa(mut self) -> Self {
+        self.allow_without_mfa = true;
+        self
+    }
+
+    /// Refuses API keys. Meant for routes that act on the signed-in session
+    /// or secure the account itself, such as passwords, sessions and keys.
+    pub fn without_api_keys(mut self) -> Self {
+        self.allow_api_keys = false;
+        self
+    }
 }
 
 impl<S> Transform<S, ServiceRequest> for RequireAuth
@@ -83,13 +296,23 @@ where
         ready(Ok(AuthMiddleware {
             service: Rc::new(service),
             allowed_roles: self.allowed_roles.clone(),
+            required_permissions: self.required_permissions.clone(),
+            require_verified: self.require_verified,
+            allow_without_mfa: self.allow_without_mfa,
+            allow_api_keys: self.allow_api_keys,
+            stateless: self.stateless,
         }))
     }
 }
 
 pub struct AuthMiddleware<S> {
     service: Rc<S>,
-    allowed_roles: Rc<Vec<UserRole>>,
+    allowed_roles: Option<Rc<Vec<String>>>,
+    required_permissions: Rc<Vec<String>>,
+    require_verified: bool,
+    allow_without_mfa: bool,
+    allow_api_keys: bool,
+    stateless: bool,
 }
 
 impl<S> Service<ServiceRequest> for AuthMiddleware<S>
@@ -109,65 +332,244 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let token = req
-            .cookie("token")
-            .map(|c| c.value().to_string())
-            .or_else(|| {
-                req.headers()
-                    .get(http::header::AUTHORIZATION)
-                    .map(|h| h.to_str().unwrap().split_at(7).1.to_string())
-            });
+        let cookie = req.cookie("token").map(|c| c.value().to_string());
+        // `Some(None)` is an `Authorization` header that holds no bearer token.
+        let bearer = req.headers().get(http::header::AUTHORIZATION).map(|h| {
+            h.to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::to_string)
+        });
+
+        if cookie.is_none() && matches!(bearer, Some(None)) {
+            return Box::pin(ready(Err(AppError::InvalidToken.into())));
+        }
+
+        let token = cookie.or(bearer.flatten()).or_else(|| {
+            req.headers()
+                .get(API_KEY_HEADER)
+                .and_then(|h| h.to_str().ok())
+                .map(|key| key.to_string())
+        });
 
         if token.is_none() {
-            let json_error = ErrorResponse {
-                status: "fail".to_string(),
-                message: ErrorMessage::TokenNotProvided.to_string(),
-            };
-            return Box::pin(ready(Err(ErrorUnauthorized(json_error))));
+            return Box::pin(ready(Err(AppError::TokenNotProvided.into())));
+        }
+
+        let token = token.unwrap();
+        if token::is_api_key(&token) {
+            return self.call_with_api_key(req, token);
         }
 
         let app_state = req.app_data::<web::Data<AppState>>().unwrap();
-        let user_id =
-            match token::decode_token(&token.unwrap(), app_state.config.jwt_secret.as_bytes()) {
-                Ok(id) => id,
-                Err(e) => {
-                    return Box::pin(ready(Err(ErrorUnauthorized(ErrorResponse {
-                        status: "fail".to_string(),
-                        message: e.message,
-                    }))))
-                }
-            };
+        let claims = match token::decode_token(&token, &app_state.jwt_keys) {
+            Ok(claims) => claims,
+            Err(e) => return Box::pin(ready(Err(e.into()))),
+        };
+
+        if !self
+            .required_permissions
+            .iter()
+            .all(|permission| claims.has_scope(permission))
+        {
+            return Box::pin(ready(Err(AppError::InsufficientScope.into())));
+        }
+
+        let needs_user = self.require_verified
+            || (!self.allow_without_mfa && app_state.config.requires_mfa(&claims.roles));
+
+        if self.stateless && !needs_user {
+            if !has_allowed_role(self.allowed_roles.as_deref(), &claims.roles) {
+                return Box::pin(ready(Err(AppError::PermissionDenied.into())));
+            }
+
+            req.extensions_mut().insert::<TokenClaims>(claims);
+            let srv = Rc::clone(&self.service);
+            return async move { srv.call(req).await }.boxed_local();
+        }
 
         let cloned_app_state = app_state.clone();
         let allowed_roles = self.allowed_roles.clone();
+        let required_permissions = self.required_permissions.clone();
+        let require_verified = self.require_verified;
+        let allow_without_mfa = self.allow_without_mfa;
         let srv = Rc::clone(&self.service);
 
         async move {
-            let user_id = uuid::Uuid::parse_str(user_id.as_str()).unwrap();
+            let mut claims = claims;
 
-            let result = UserService::new(cloned_app_state.db.clone())
-                .get_user(Some(&user_id.to_string()), None, None)
+            let session = SessionService::new(cloned_app_state.db.clone())
+                .get_active_session(&claims.jti)
                 .await
-                .map_err(|e| ErrorInternalServerError(HttpError::server_error(e.to_string())))?;
-
-            let user = result.ok_or(ErrorUnauthorized(ErrorResponse {
-                status: "fail".to_string(),
-                message: ErrorMessage::UserNoLongerExist.to_string(),
-            }))?;
-
-            // Check if user's role matches the required role
-            if allowed_roles.contains(&user.role) {
-                req.extensions_mut().insert::<UserModel>(user);
-                let res = srv.call(req).await?;
-                Ok(res)
-            } else {
-                let json_error = ErrorResponse {
-                    status: "fail".to_string(),
-                    message: ErrorMessage::PermissionDenied.to_string(),
-                };
-                Err(ErrorForbidden(json_error))
+                .map_err(AppError::from)?
+                .filter(|session| session.user_id == claims.sub)
+                .ok_or(AppError::SessionRevoked)?;
+
+            let user = authorize_user(
+                &cloned_app_state,
+                &mut claims,
+                allowed_roles.as_deref(),
+                &required_permissions,
+                require_verified,
+                allow_without_mfa,
+            )
+            .await?;
+
+            req.extensions_mut().insert::<UserModel>(user);
+            req.extensions_mut().insert::<SessionModel>(session);
+            req.extensions_mut().insert::<TokenClaims>(claims);
+            let res = srv.call(req).await?;
+            Ok(res)
+        }
+        .boxed_local()
+    }
+}
+
+impl<S> AuthMiddleware<S>
+where
+    S: Service<
+            ServiceRequest,
+            Response = ServiceResponse<actix_web::body::BoxBody>,
+            Error = actix_web::Error,
+        > + 'static,
+{
+    /// Authenticates with an API key instead of an access token. The key
+    /// has no session, and stands in for a token whose `scope` is the
+    /// permissions granted to the key.
+    fn call_with_api_key(
+        &self,
+        req: ServiceRequest,
+        raw_key: String,
+    ) -> <Self as Service<ServiceRequest>>::Future {
+        if !self.allow_api_keys {
+            return Box::pin(ready(Err(AppError::ApiKeyNotAllowed.into())));
+        }
+
+        let cloned_app_state = req.app_data::<web::Data<AppState>>().unwrap().clone();
+        let allowed_roles = self.allowed_roles.clone();
+        let required_permissions = self.required_permissions.clone();
+        let require_verified = self.require_verified;
+        let allow_without_mfa = self.allow_without_mfa;
+        let srv = Rc::clone(&self.service);
+
+        async move {
+            let ApiKeyWithScopes { api_key, scopes } =
+                ApiKeyService::new(cloned_app_state.db.clone())
+                    .authenticate(&raw_key)
+                    .await
+                    .map_err(AppError::from)?
+                    .ok_or(AppError::InvalidApiKey)?;
+
+            let keys = &cloned_app_state.jwt_keys;
+            let mut claims = TokenClaims {
+                iss: keys.issuer().to_string(),
+                aud: keys.audience().to_string(),
+                sub: api_key.user_id.clone(),
+                jti: api_key.id.clone(),
+                roles: Vec::new(),
+                scope: scopes,
+                org: None,
+                iat: api_key
+                    .created_at
+                    .map_or(0, |created_at| created_at.timestamp() as usize),
+                // Keys without an expiry have no meaningful `exp`.
+                exp: api_key
+                    .expires_at
+                    .map_or(0, |expires_at| expires_at.timestamp() as usize),
+            };
+
+            if !required_permissions
+                .iter()
+                .all(|permission| claims.has_scope(permission))
+            {
+                return Err(AppError::InsufficientScope.into());
             }
+
+            let user = authorize_user(
+                &cloned_app_state,
+                &mut claims,
+                allowed_roles.as_deref(),
+                &required_permissions,
+                require_verified,
+                allow_without_mfa,
+            )
+            .await?;
+
+            // Requesting deletion signs the user out everywhere, its keys
+            // stop working until signing in again cancels the deletion.
+            if user.deletion_scheduled_at.is_some() {
+                return Err(AppError::InvalidApiKey.into());
+            }
+
+            req.extensions_mut().insert::<UserModel>(user);
+            req.extensions_mut().insert::<ApiKeyModel>(api_key);
+            req.extensions_mut().insert::<TokenClaims>(claims);
+            let res = srv.call(req).await?;
+            Ok(res)
         }
         .boxed_local()
     }
 }
+
+/// Loads the user behind `claims` and applies every check that needs it:
+/// account status, email verification, two-factor enrollment, roles and
+/// permissions. Narrows `claims.scope` to what the user may still do.
+async fn authorize_user(
+    app_state: &web::Data<AppState>,
+    claims: &mut TokenClaims,
+    allowed_roles: Option<&Vec<String>>,
+    required_permissions: &[String],
+    require_verified: bool,
+    allow_without_mfa: bool,
+) -> Result<UserModel, AppError> {
+    let user_id = uuid::Uuid::parse_str(claims.sub.as_str()).unwrap();
+
+    let result = UserService::new(app_state.db.clone())
+        .get_user(Some(&user_id.to_string()), None, None)
+        .await?;
+
+    let user = result.ok_or(AppError::UserNoLongerExist)?;
+
+    UserService::ensure_active(&user)?;
+
+    if require_verified && user.verified == 0 {
+        return Err(AppError::EmailNotVerified);
+    }
+
+    // Roles and permissions are read fresh, so changes apply to
+    // tokens that were issued before them.
+    let role_service = RoleService::new(app_state.db.clone());
+
+    let check_mfa = !allow_without_mfa
+        && user.mfa_enabled == 0
+        && !app_state.config.mfa_required_roles.is_empty();
+    if check_mfa || allowed_roles.is_some() {
+        claims.roles = role_service.get_user_roles(&user.id).await?;
+    }
+
+    if check_mfa && app_state.config.requires_mfa(&claims.roles) {
+        return Err(AppError::MfaEnrollmentRequired);
+    }
+
+    if !has_allowed_role(allowed_roles, &claims.roles) {
+        return Err(AppError::PermissionDenied);
+    }
+
+    // Narrow the token's scope to what the user may still do, which
+    // is also what `AuthClaims::require_scope` checks against.
+    let permissions = role_service.get_user_permissions(&user.id).await?;
+    claims.scope.retain(|scope| permissions.contains(scope));
+
+    if !required_permissions
+        .iter()
+        .all(|permission| claims.has_scope(permission))
+    {
+        return Err(AppError::PermissionDenied);
+    }
+
+    Ok(user)
+}
+
+fn has_allowed_role(allowed_roles: Option<&Vec<String>>, roles: &[String]) -> bool {
+    allowed_roles.is_none_or(|allowed| roles.iter().any(|role| allowed.contains(role)))
+}
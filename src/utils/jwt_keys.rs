@@ -0,0 +1,257 @@
+use std::{collections::HashMap, fs};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    traits::PublicKeyParts,
+    RsaPrivateKey, RsaPublicKey,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use super::config::Config;
+
+struct VerificationKey {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Signing key plus every key tokens are still accepted from.
+///
+/// With `HS256` the shared secret does both jobs and nothing is published.
+/// With `RS256` or `EdDSA` the private key signs, and its public half is
+/// published in the JWKS together with the retired keys listed in
+/// `JWT_VERIFICATION_KEYS`, so tokens signed before a rotation stay valid
+/// until they expire.
+pub struct JwtKeys {
+    kid: String,
+    issuer: String,
+    audience: String,
+    leeway: u64,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    verification_keys: HashMap<String, VerificationKey>,
+    jwks: Value,
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let kid = config.jwt_key_id.to_owned();
+        let mut verification_keys = HashMap::new();
+        let mut jwks = Vec::new();
+
+        let (algorithm, encoding_key) = match config.jwt_algorithm.as_str() {
+            "HS256" => {
+                verification_keys.insert(
+                    kid.clone(),
+                    VerificationKey {
+                        algorithm: Algorithm::HS256,
+                        key: DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+                    },
+                );
+                (
+                    Algorithm::HS256,
+                    EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+                )
+            }
+            "RS256" | "EdDSA" => {
+                let path = config
+                    .jwt_private_key_path
+                    .as_deref()
+                    .ok_or("JWT_PRIVATE_KEY_PATH must be set for RS256 and EdDSA")?;
+                let pem = read_pem(path)?;
+
+                let (algorithm, encoding_key, public_jwk) = if config.jwt_algorithm == "RS256" {
+                    let private_key = RsaPrivateKey::from_pkcs8_pem(&pem)
+                        .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    let encoding_key =
+                        EncodingKey::from_rsa_pem(pem.as_bytes()).map_err(|e| e.to_string())?;
+                    (
+                        Algorithm::RS256,
+                        encoding_key,
+                        rsa_components(&private_key.to_public_key()),
+                    )
+                } else {
+                    let signing_key = ed25519_dalek::SigningKey::from_pkcs8_pem(&pem)
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                    let encoding_key =
+                        EncodingKey::from_ed_pem(pem.as_bytes()).map_err(|e| e.to_string())?;
+                    (
+                        Algorithm::EdDSA,
+                        encoding_key,
+                        ed_components(&signing_key.verifying_key()),
+                    )
+                };
+
+                add_public_key(&mut verification_keys, &mut jwks, &kid, public_jwk)?;
+                (algorithm, encoding_key)
+            }
+            other => {
+                return Err(format!(
+                    "Unsupported JWT_ALGORITHM \"{}\", expected HS256, RS256 or EdDSA",
+                    other
+                ))
+            }
+        };
+
+        for (retired_kid, path) in &config.jwt_verification_keys {
+            if *retired_kid == kid {
+                return Err(format!(
+                    "JWT_VERIFICATION_KEYS reuses the active key id \"{}\"",
+                    kid
+                ));
+            }
+            let public_jwk =
+                parse_public_key(&read_pem(path)?).map_err(|e| format!("{}: {}", path, e))?;
+            add_public_key(&mut verification_keys, &mut jwks, retired_kid, public_jwk)?;
+        }
+
+        Ok(JwtKeys {
+            kid,
+            issuer: config.jwt_issuer.to_owned(),
+            audience: config.jwt_audience.to_owned(),
+            leeway: config.jwt_leeway,
+            algorithm,
+            encoding_key,
+            verification_keys,
+            jwks: json!({ "keys": jwks }),
+        })
+    }
+
+    /// Signs `claims` with the active key and stamps its `kid` in the header.
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, jsonwebtoken::errors::Error> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.to_owned());
+
+        encode(&header, claims, &self.encoding_key)
+    }
+
+    /// Verifies a token against the key named by its `kid` and checks its
+    /// expiry, issuer and that its audience is `audience`. Tokens without a
+    /// `kid` predate key ids and are checked against the active key.
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        audience: &str,
+    ) -> Result<T, jsonwebtoken::errors::Error> {
+        let header = decode_header(token)?;
+        let kid = header.kid.unwrap_or(self.kid.to_owned());
+
+        let verification_key = self
+            .verification_keys
+            .get(&kid)
+            .ok_or(jsonwebtoken::errors::ErrorKind::InvalidToken)?;
+
+        let mut validation = Validation::new(verification_key.algorithm);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[audience]);
+        validation.leeway = self.leeway;
+
+        let decoded = decode::<T>(token, &verification_key.key, &validation)?;
+
+        Ok(decoded.claims)
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn audience(&self) -> &str {
+        &self.audience
+    }
+
+    /// Public keys in JWK Set format. Empty for `HS256`.
+    pub fn jwks(&self) -> &Value {
+        &self.jwks
+    }
+}
+
+fn read_pem(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Cannot read key {}: {}", path, e))
+}
+
+/// A public key as JWK members, without `kid`, `use` and `alg`.
+struct PublicJwk {
+    algorithm: Algorithm,
+    members: Value,
+}
+
+fn rsa_components(public_key: &RsaPublicKey) -> PublicJwk {
+    PublicJwk {
+        algorithm: Algorithm::RS256,
+        members: json!({
+            "kty": "RSA",
+            "n": URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            "e": URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }),
+    }
+}
+
+fn ed_components(public_key: &ed25519_dalek::VerifyingKey) -> PublicJwk {
+    PublicJwk {
+        algorithm: Algorithm::EdDSA,
+        members: json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": URL_SAFE_NO_PAD.encode(public_key.as_bytes()),
+        }),
+    }
+}
+
+/// Accepts RSA keys in SPKI or PKCS#1 form and Ed25519 keys in SPKI form.
+fn parse_public_key(pem: &str) -> Result<PublicJwk, String> {
+    if let Ok(public_key) = RsaPublicKey::from_public_key_pem(pem) {
+        return Ok(rsa_components(&public_key));
+    }
+    if let Ok(public_key) = RsaPublicKey::from_pkcs1_pem(pem) {
+        return Ok(rsa_components(&public_key));
+    }
+    if let Ok(public_key) = ed25519_dalek::VerifyingKey::from_public_key_pem(pem) {
+        return Ok(ed_components(&public_key));
+    }
+
+    Err("not an RSA or Ed25519 public key".to_string())
+}
+
+fn add_public_key(
+    verification_keys: &mut HashMap<String, VerificationKey>,
+    jwks: &mut Vec<Value>,
+    kid: &str,
+    public_jwk: PublicJwk,
+) -> Result<(), String> {
+    let members = &public_jwk.members;
+    let key = match public_jwk.algorithm {
+        Algorithm::RS256 => DecodingKey::from_rsa_components(
+            members["n"].as_str().unwrap_or_default(),
+            members["e"].as_str().unwrap_or_default(),
+        )
+        .map_err(|e| e.to_string())?,
+        _ => DecodingKey::from_ed_components(members["x"].as_str().unwrap_or_default())
+            .map_err(|e| e.to_string())?,
+    };
+
+    let mut jwk = public_jwk.members.clone();
+    jwk["kid"] = json!(kid);
+    jwk["use"] = json!("sig");
+    jwk["alg"] = json!(match public_jwk.algorithm {
+        Algorithm::RS256 => "RS256",
+        _ => "EdDSA",
+    });
+    jwks.push(jwk);
+
+    verification_keys.insert(
+        kid.to_string(),
+        VerificationKey {
+            algorithm: public_jwk.algorithm,
+            key,
+        },
+    );
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use lettre::{AsyncFileTransport, AsyncTransport, Tokio1Executor};
+
+use super::{build_message, Email, Mailer};
+
+/// Writes every message as an `.eml` file into a directory instead of
+/// delivering it. Handy for local development without an SMTP server.
+pub struct FileMailer {
+    from: String,
+    transport: AsyncFileTransport<Tokio1Executor>,
+}
+
+impl FileMailer {
+    pub fn new(from: &str, outbox_dir: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(outbox_dir)
+            .map_err(|e| format!("Cannot create mail outbox {}: {}", outbox_dir, e))?;
+
+        Ok(FileMailer {
+            from: from.to_owned(),
+            transport: AsyncFileTransport::<Tokio1Executor>::new(outbox_dir),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        let message = build_message(&self.from, email)?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
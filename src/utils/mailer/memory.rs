@@ -0,0 +1,41 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use super::{Email, Mailer};
+
+/// Keeps sent messages in memory so integration tests can assert on them.
+#[derive(Debug, Default)]
+pub struct MemoryMailer {
+    outbox: Mutex<Vec<Email>>,
+}
+
+impl MemoryMailer {
+    /// Every message sent so far, oldest first.
+    pub fn messages(&self) -> Vec<Email> {
+        self.outbox.lock().unwrap().clone()
+    }
+
+    /// The most recent message sent to `to`, if any.
+    pub fn last_message_to(&self, to: &str) -> Option<Email> {
+        self.outbox
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|email| email.to == to)
+            .cloned()
+    }
+
+    pub fn clear(&self) {
+        self.outbox.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        self.outbox.lock().unwrap().push(email);
+        Ok(())
+    }
+}
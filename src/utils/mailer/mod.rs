@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use actix_web::{http, HttpRequest};
+use async_trait::async_trait;
+
+use super::config::Config;
+
+pub mod file;
+pub mod memory;
+pub mod smtp;
+pub mod templates;
+
+pub use file::FileMailer;
+pub use memory::MemoryMailer;
+pub use smtp::SmtpMailer;
+pub use templates::EmailTemplates;
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outbound email transport. Kept behind a trait so the flows that send mail
+/// do not care where it ends up.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<(), String>;
+}
+
+/// Builds the backend selected by `MAILER`.
+pub fn from_config(config: &Config) -> Result<Arc<dyn Mailer>, String> {
+    match config.mailer.as_str() {
+        "smtp" => Ok(Arc::new(SmtpMailer::new(config)?)),
+        "file" => Ok(Arc::new(FileMailer::new(
+            &config.mail_from,
+            &config.mail_outbox_dir,
+        )?)),
+        "memory" => Ok(Arc::new(MemoryMailer::default())),
+        other => Err(format!(
+            "Unknown mailer backend \"{}\", expected smtp, file or memory",
+            other
+        )),
+    }
+}
+
+/// Picks the preferred language from `Accept-Language`, ignoring quality
+/// values. Returns `None` when the header is missing or unreadable.
+pub fn locale_from_request(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(http::header::ACCEPT_LANGUAGE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.split(',').next())
+        .map(|tag| tag.split(';').next().unwrap_or("").trim().to_string())
+        .filter(|tag| !tag.is_empty() && tag != "*")
+}
+
+/// Converts an [`Email`] into a lettre message, shared by the SMTP and file
+/// backends so both produce byte-identical output.
+fn build_message(from: &str, email: Email) -> Result<lettre::Message, String> {
+    lettre::Message::builder()
+        .from(from.parse().map_err(|e| format!("Invalid sender: {}", e))?)
+        .to(email
+            .to
+            .parse()
+            .map_err(|e| format!("Invalid recipient: {}", e))?)
+        .subject(email.subject)
+        .header(lettre::message::header::ContentType::TEXT_PLAIN)
+        .body(email.body)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use lettre::{
+    transport::smtp::authentication::Credentials, AsyncSmtpTransport, AsyncTransport,
+    Tokio1Executor,
+};
+
+use crate::utils::config::Config;
+
+use super::{build_message, Email, Mailer};
+
+/// Delivers mail through an SMTP relay. With `SMTP_TLS=false` it talks plain
+/// SMTP, which is what local catch-all servers such as MailHog expect.
+pub struct SmtpMailer {
+    from: String,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let host = config
+            .smtp_host
+            .as_deref()
+            .ok_or("SMTP_HOST must be set when MAILER=smtp")?;
+
+        let mut builder = if config.smtp_tls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host).map_err(|e| e.to_string())?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        };
+
+        if let Some(port) = config.smtp_port {
+            builder = builder.port(port);
+        }
+
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder =
+                builder.credentials(Credentials::new(username.to_owned(), password.to_owned()));
+        }
+
+        Ok(SmtpMailer {
+            from: config.mail_from.to_owned(),
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), String> {
+        let message = build_message(&self.from, email)?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
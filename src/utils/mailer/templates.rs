@@ -0,0 +1,115 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use super::Email;
+
+#[derive(Debug, Clone)]
+struct Template {
+    subject: String,
+    body: String,
+}
+
+/// Subject and body templates per locale, loaded once at startup from
+/// `<dir>/<locale>/<name>.subject.txt` and `<dir>/<locale>/<name>.body.txt`.
+///
+/// Placeholders are written as `{{ key }}`. Lookups fall back from `pt-BR`
+/// to `pt` and finally to the default locale.
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    default_locale: String,
+    templates: HashMap<(String, String), Template>,
+}
+
+impl EmailTemplates {
+    pub fn load(dir: &str, default_locale: &str) -> Result<Self, String> {
+        let mut templates = HashMap::new();
+
+        let locales =
+            fs::read_dir(dir).map_err(|e| format!("Cannot read templates {}: {}", dir, e))?;
+
+        for locale_dir in locales.flatten() {
+            if !locale_dir.path().is_dir() {
+                continue;
+            }
+            let locale = locale_dir.file_name().to_string_lossy().to_lowercase();
+
+            let files = fs::read_dir(locale_dir.path()).map_err(|e| e.to_string())?;
+            for file in files.flatten() {
+                let file_name = file.file_name().to_string_lossy().to_string();
+                let Some(name) = file_name.strip_suffix(".subject.txt") else {
+                    continue;
+                };
+
+                let subject = fs::read_to_string(file.path()).map_err(|e| e.to_string())?;
+                let body_path = locale_dir.path().join(format!("{}.body.txt", name));
+                let body = fs::read_to_string(&body_path)
+                    .map_err(|e| format!("{}: {}", body_path.display(), e))?;
+
+                templates.insert(
+                    (locale.clone(), name.to_string()),
+                    Template {
+                        subject: subject.trim().to_string(),
+                        body,
+                    },
+                );
+            }
+        }
+
+        let default_locale = default_locale.to_lowercase();
+        if !templates
+            .keys()
+            .any(|(locale, _)| *locale == default_locale)
+        {
+            return Err(format!(
+                "No email templates found for default locale \"{}\" in {}",
+                default_locale,
+                Path::new(dir).display()
+            ));
+        }
+
+        Ok(EmailTemplates {
+            default_locale,
+            templates,
+        })
+    }
+
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
+    /// Renders template `name` for `to` in the closest available locale.
+    pub fn render(
+        &self,
+        name: &str,
+        locale: &str,
+        to: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<Email, String> {
+        let template = self
+            .lookup(name, locale)
+            .ok_or(format!("Email template \"{}\" does not exist", name))?;
+
+        Ok(Email {
+            to: to.to_string(),
+            subject: substitute(&template.subject, vars),
+            body: substitute(&template.body, vars),
+        })
+    }
+
+    fn lookup(&self, name: &str, locale: &str) -> Option<&Template> {
+        let locale = locale.to_lowercase();
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+
+        [locale.as_str(), language, self.default_locale.as_str()]
+            .iter()
+            .find_map(|l| self.templates.get(&(l.to_string(), name.to_string())))
+    }
+}
+
+fn substitute(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |rendered, (key, value)| {
+            rendered
+                .replace(&format!("{{{{ {} }}}}", key), value)
+                .replace(&format!("{{{{{}}}}}", key), value)
+        })
+}
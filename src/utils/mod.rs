@@ -1,5 +1,16 @@
+pub mod avatar;
+pub mod client_ip;
 pub mod config;
 pub mod error;
 pub mod extractor;
+pub mod jwt_keys;
+pub mod mailer;
+pub mod oidc;
 pub mod password;
+pub mod problem;
+pub mod rate_limit;
+pub mod scope;
+pub mod storage;
 pub mod token;
+pub mod totp;
+pub mod validated_json;
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{
+    config::{Config, OidcProviderConfig},
+    error::AppError,
+};
+
+/// How long a fetched discovery document and key set are trusted.
+const KEYS_MAXAGE: Duration = Duration::from_secs(60 * 60);
+/// Least time between two fetches caused by an unknown `kid`, so tokens with
+/// made-up key ids cannot make us hammer the provider.
+const REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    jwks_uri: String,
+}
+
+struct ProviderKeys {
+    issuer: String,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+struct Provider {
+    config: OidcProviderConfig,
+    keys: RwLock<Option<Arc<ProviderKeys>>>,
+}
+
+#[derive(Deserialize)]
+struct RawIdTokenClaims {
+    sub: String,
+    email: Option<String>,
+    /// A boolean, except from Apple, which sends `"true"` or `"false"`.
+    email_verified: Option<Value>,
+    name: Option<String>,
+    nonce: Option<String>,
+}
+
+/// The identity asserted by a verified ID token.
+#[derive(Debug, Clone)]
+pub struct IdTokenClaims {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+/// Verifies ID tokens issued by the providers in `OIDC_PROVIDERS`. Discovery
+/// documents and signing keys are fetched on first use and cached, which also
+/// makes a local mock server work as a provider.
+pub struct OidcVerifier {
+    client: reqwest::Client,
+    leeway: u64,
+    providers: HashMap<String, Provider>,
+}
+
+impl OidcVerifier {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let providers = config
+            .oidc_providers
+            .iter()
+            .map(|provider| {
+                (
+                    provider.name.clone(),
+                    Provider {
+                        config: provider.clone(),
+                        keys: RwLock::new(None),
+                    },
+                )
+            })
+            .collect();
+
+        Ok(OidcVerifier {
+            client,
+            leeway: config.jwt_leeway,
+            providers,
+        })
+    }
+
+    /// Checks the signature, issuer, audience and expiry of `id_token`, and
+    /// its nonce when the client sent one.
+    pub async fn verify(
+        &self,
+        provider_name: &str,
+        id_token: &str,
+        nonce: Option<&str>,
+    ) -> Result<IdTokenClaims, AppError> {
+        let provider = self
+            .providers
+            .get(provider_name)
+            .ok_or(AppError::OidcProviderNotFound)?;
+
+        let header = decode_header(id_token).map_err(|_| AppError::InvalidIdToken)?;
+
+        // With an HMAC algorithm the published key would become the secret.
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(AppError::InvalidIdToken);
+        }
+
+        let kid = header.kid.ok_or(AppError::InvalidIdToken)?;
+
+        let mut keys = self.keys(provider, false).await?;
+        if keys.jwks.find(&kid).is_none() && keys.fetched_at.elapsed() >= REFETCH_INTERVAL {
+            // The provider may have rotated its keys since we cached them.
+            keys = self.keys(provider, true).await?;
+        }
+
+        let jwk = keys.jwks.find(&kid).ok_or(AppError::InvalidIdToken)?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| AppError::InvalidIdToken)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = self.leeway;
+        validation.set_audience(&provider.config.client_ids);
+        // Google issues tokens both with and without the scheme.
+        validation.set_issuer(&[
+            keys.issuer.as_str(),
+            keys.issuer.trim_start_matches("https://"),
+        ]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<RawIdTokenClaims>(id_token, &key, &validation)
+            .map_err(|_| AppError::InvalidIdToken)?
+            .claims;
+
+        if let Some(nonce) = nonce {
+            if claims.nonce.as_deref() != Some(nonce) {
+                return Err(AppError::InvalidIdToken);
+            }
+        }
+
+        let email_verified = match claims.email_verified {
+            Some(Value::Bool(verified)) => verified,
+            Some(Value::String(verified)) => verified == "true",
+            _ => false,
+        };
+
+        Ok(IdTokenClaims {
+            subject: claims.sub,
+            email: claims.email.map(|email| email.to_lowercase()),
+            email_verified,
+            name: claims.name,
+        })
+    }
+
+    async fn keys(
+        &self,
+        provider: &Provider,
+        refresh: bool,
+    ) -> Result<Arc<ProviderKeys>, AppError> {
+        if !refresh {
+            if let Some(keys) = provider.keys.read().unwrap().as_ref() {
+                if keys.fetched_at.elapsed() < KEYS_MAXAGE {
+                    return Ok(keys.clone());
+                }
+            }
+        }
+
+        let keys = self.fetch_keys(&provider.config).await.map_err(|e| {
+            AppError::internal(format!(
+                "Failed to fetch the signing keys of {}: {}",
+                provider.config.name, e
+            ))
+        })?;
+        let keys = Arc::new(keys);
+        *provider.keys.write().unwrap() = Some(keys.clone());
+
+        Ok(keys)
+    }
+
+    async fn fetch_keys(
+        &self,
+        config: &OidcProviderConfig,
+    ) -> Result<ProviderKeys, reqwest::Error> {
+        let discovery: DiscoveryDocument = self
+            .client
+            .get(&config.discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let jwks: JwkSet = self
+            .client
+            .get(&discovery.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(ProviderKeys {
+            issuer: discovery.issuer,
+            jwks,
+            fetched_at: Instant::now(),
+        })
+    }
+}
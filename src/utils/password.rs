@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
@@ -28,6 +30,13 @@ pub fn hash(password: impl Into<String>) -> Result<String, String> {
     Ok(hashed_password)
 }
 
+/// Hash of a throwaway password. Comparing against it when there is no
+/// account makes unknown emails take as long as wrong passwords.
+pub fn dummy_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    DUMMY_HASH.get_or_init(|| hash("not-a-real-password").expect("hashing a constant works"))
+}
+
 pub fn compare(password: &str, hashed_password: &str) -> Result<bool, String> {
     if password.is_empty() {
         return Err("Password cannot be empty".to_string());
@@ -45,7 +54,7 @@ pub fn compare(password: &str, hashed_password: &str) -> Result<bool, String> {
 
     let password_matches = Argon2::default()
         .verify_password(password.as_bytes(), &parsed_hash)
-        .map_or(false, |_| true);
+        .is_ok();
 
     Ok(password_matches)
 }
@@ -0,0 +1,532 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    body::BoxBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::{
+        header::{self, HeaderMap, HeaderValue},
+        StatusCode,
+    },
+    HttpResponse, ResponseError,
+};
+use futures_util::{
+    future::{ready, LocalBoxFuture, Ready},
+    FutureExt,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::error::{AppError, FieldError, SERVER_ERROR_MESSAGE};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Carries the id that ties an error response to its log line. Taken from
+/// the request when a gateway already assigned one.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Error body in the Problem Details format of RFC 7807, sent in place of
+/// `ErrorResponse` to clients that accept `application/problem+json`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    /// `{API_URL}/problems/{code}`, or `about:blank` for errors that did not
+    /// come from the API itself.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Path of the request that failed.
+    pub instance: String,
+    /// Same as `ErrorResponse::code`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Same as `ErrorResponse::errors`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<BTreeMap<String, Vec<FieldError>>>,
+    /// Also sent as `X-Request-Id` and logged with server errors.
+    #[serde(rename = "traceId")]
+    pub trace_id: String,
+}
+
+/// Negotiates the format of error responses. Clients that accept
+/// `application/problem+json` get `ProblemDetails`, everyone else keeps the
+/// `ErrorResponse` older app versions understand.
+///
+/// Wrapped on the whole app so that it also sees the errors other
+/// middleware, such as `RequireAuth`, end requests with. Every response gets
+/// an `X-Request-Id`, and server errors are logged with it.
+pub struct ProblemJson {
+    type_base: Rc<String>,
+}
+
+impl ProblemJson {
+    /// `api_url` is where the `type` links of problems point to.
+    pub fn new(api_url: &str) -> Self {
+        ProblemJson {
+            type_base: Rc::new(format!("{}/problems/", api_url)),
+        }
+    }
+}
+
+impl<S> Transform<S, ServiceRequest> for ProblemJson
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>
+        + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Transform = ProblemJsonMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ProblemJsonMiddleware {
+            service: Rc::new(service),
+            type_base: self.type_base.clone(),
+        }))
+    }
+}
+
+pub struct ProblemJsonMiddleware<S> {
+    service: Rc<S>,
+    type_base: Rc<String>,
+}
+
+impl<S> Service<ServiceRequest> for ProblemJsonMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>
+        + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, actix_web::Error>>;
+
+    fn poll_ready(&self, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let negotiation = Negotiation {
+            wants_problem: accepts_problem_json(req.headers()),
+            trace_id: request_id(req.headers()),
+            type_base: self.type_base.clone(),
+            instance: req.path().to_string(),
+        };
+        let srv = Rc::clone(&self.service);
+
+        async move {
+            match srv.call(req).await {
+                Ok(res) => {
+                    let (http_req, response) = res.into_parts();
+                    let problem = match response.error() {
+                        Some(error) => negotiation.problem(error, response.status())?,
+                        None => None,
+                    };
+                    let is_error = response.error().is_some();
+
+                    Ok(ServiceResponse::new(
+                        http_req,
+                        negotiation.apply(response, is_error, problem),
+                    ))
+                }
+                // Middleware ends requests with an error rather than a
+                // response. The request is gone by now, so leave the error
+                // to actix and negotiate once it renders it.
+                Err(error) => {
+                    let status = error.as_response_error().status_code();
+                    let problem = negotiation.problem(&error, status)?;
+
+                    Err(NegotiatedError {
+                        error,
+                        problem,
+                        negotiation,
+                    }
+                    .into())
+                }
+            }
+        }
+        .boxed_local()
+    }
+}
+
+/// What `ProblemJson` needs to know about the request to answer it.
+#[derive(Debug)]
+struct Negotiation {
+    wants_problem: bool,
+    trace_id: String,
+    type_base: Rc<String>,
+    instance: String,
+}
+
+impl Negotiation {
+    /// Logs server errors, and returns the `ProblemDetails` body to send in
+    /// place of the `ErrorResponse` when the client wants one.
+    fn problem(
+        &self,
+        error: &actix_web::Error,
+        status: StatusCode,
+    ) -> Result<Option<String>, AppError> {
+        if status.is_server_error() {
+            eprintln!("🔥 [{}] {}", self.trace_id, error);
+        }
+
+        if !self.wants_problem {
+            return Ok(None);
+        }
+
+        let problem = problem_details(
+            error,
+            status,
+            &self.type_base,
+            self.instance.clone(),
+            self.trace_id.clone(),
+        );
+        serde_json::to_string(&problem)
+            .map(Some)
+            .map_err(AppError::internal)
+    }
+
+    /// Tags `response` with the request id and, when it is an error,
+    /// swaps in the `problem` body.
+    fn apply(
+        &self,
+        mut response: HttpResponse,
+        is_error: bool,
+        problem: Option<String>,
+    ) -> HttpResponse {
+        if let Ok(value) = HeaderValue::from_str(&self.trace_id) {
+            response
+                .headers_mut()
+                .insert(header::HeaderName::from_static(REQUEST_ID_HEADER), value);
+        }
+
+        if !is_error {
+            return response;
+        }
+
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+
+        match problem {
+            Some(body) => {
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+                response.set_body(BoxBody::new(body))
+            }
+            None => response,
+        }
+    }
+}
+
+/// An error middleware ended the request with, which renders negotiated
+/// like every other error response.
+#[derive(Debug)]
+struct NegotiatedError {
+    error: actix_web::Error,
+    problem: Option<String>,
+    negotiation: Negotiation,
+}
+
+impl fmt::Display for NegotiatedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl ResponseError for NegotiatedError {
+    fn status_code(&self) -> StatusCode {
+        self.error.as_response_error().status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.negotiation
+            .apply(self.error.error_response(), true, self.problem.clone())
+    }
+}
+
+/// The problem `error` stands for, with `instance` and `trace_id` filled in.
+fn problem_details(
+    error: &actix_web::Error,
+    status: StatusCode,
+    type_base: &str,
+    instance: String,
+    trace_id: String,
+) -> ProblemDetails {
+    let (problem_type, detail, code, errors) = match error.as_error::<AppError>() {
+        Some(error) => (
+            format!("{}{}", type_base, error.code()),
+            error.message(),
+            Some(error.code().to_string()),
+            error.field_errors(),
+        ),
+        // Errors of actix itself, such as an unparsable path segment.
+        None if status.is_server_error() => (
+            "about:blank".to_string(),
+            SERVER_ERROR_MESSAGE.to_string(),
+            None,
+            None,
+        ),
+        None => ("about:blank".to_string(), error.to_string(), None, None),
+    };
+
+    ProblemDetails {
+        problem_type,
+        title: status.canonical_reason().unwrap_or("Error").to_string(),
+        status: status.as_u16(),
+        detail,
+        instance,
+        code,
+        errors,
+        trace_id,
+    }
+}
+
+/// Whether `Accept` lists `application/problem+json` with a quality above
+/// zero. Wildcards do not count, they are what older clients send.
+fn accepts_problem_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .any(|range| {
+            let mut params = range.split(';').map(str::trim);
+            params
+                .next()
+                .is_some_and(|media_type| media_type.eq_ignore_ascii_case(PROBLEM_JSON))
+                && params
+                    .filter_map(|param| param.strip_prefix("q="))
+                    .all(|q| q.parse::<f32>().is_ok_and(|q| q > 0.0))
+        })
+}
+
+/// The `X-Request-Id` the request came with, or a new one.
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        http::header::HeaderName,
+        test::{self, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    use super::*;
+
+    const API_URL: &str = "https://api.example.com";
+
+    fn headers(accept: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in accept {
+            headers.append(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn negotiates_on_accept() {
+        let cases: [(&[&str], bool); 10] = [
+            (&[], false),
+            (&["*/*"], false),
+            (&["application/*"], false),
+            (&["application/json"], false),
+            (&["application/problem+json"], true),
+            (&["Application/Problem+JSON"], true),
+            (&["application/json, application/problem+json;q=0.5"], true),
+            (&["application/problem+json; q=0"], false),
+            (&["application/problem+json;q=nope"], false),
+            (&["application/json", "application/problem+json"], true),
+        ];
+
+        for (accept, expected) in cases {
+            assert_eq!(
+                accepts_problem_json(&headers(accept)),
+                expected,
+                "{:?}",
+                accept
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_the_request_id_it_was_given() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderValue::from_static(" gateway-42 "),
+        );
+
+        assert_eq!(request_id(&headers), "gateway-42");
+    }
+
+    #[test]
+    fn replaces_missing_or_unusable_request_ids() {
+        for value in [None, Some(""), Some("   "), Some(&*"x".repeat(129))] {
+            let mut headers = HeaderMap::new();
+            if let Some(value) = value {
+                headers.insert(
+                    HeaderName::from_static(REQUEST_ID_HEADER),
+                    HeaderValue::from_str(value).unwrap(),
+                );
+            }
+
+            let id = request_id(&headers);
+            assert!(
+                uuid::Uuid::parse_str(&id).is_ok(),
+                "{:?} gave {}",
+                value,
+                id
+            );
+        }
+    }
+
+    async fn call(req: TestRequest) -> ServiceResponse {
+        let app = test::init_service(
+            App::new()
+                .wrap(ProblemJson::new(API_URL))
+                .route(
+                    "/missing",
+                    web::get().to(|| async { Err::<HttpResponse, _>(AppError::UserNotFound) }),
+                )
+                .route("/ok", web::get().to(HttpResponse::Ok))
+                .service(
+                    // Ends requests the way `RequireAuth` does.
+                    web::scope("/guarded")
+                        .wrap_fn(|_, _| {
+                            ready(Err::<ServiceResponse, _>(AppError::TokenNotProvided.into()))
+                        })
+                        .route("", web::get().to(HttpResponse::Ok)),
+                ),
+        )
+        .await;
+
+        // Errors are turned into responses the way actix does it past the
+        // last middleware.
+        match test::try_call_service(&app, req.to_request()).await {
+            Ok(res) => res,
+            Err(e) => ServiceResponse::from_err(e, TestRequest::default().to_http_request()),
+        }
+    }
+
+    #[actix_web::test]
+    async fn sends_problem_details_to_clients_that_accept_them() {
+        let res = call(
+            TestRequest::get()
+                .uri("/missing")
+                .insert_header((header::ACCEPT, PROBLEM_JSON))
+                .insert_header((REQUEST_ID_HEADER, "gateway-42")),
+        )
+        .await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "gateway-42");
+
+        let problem: ProblemDetails = test::read_body_json(res).await;
+        assert_eq!(
+            problem.problem_type,
+            format!("{}/problems/{}", API_URL, AppError::UserNotFound.code())
+        );
+        assert_eq!(problem.status, 404);
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.detail, AppError::UserNotFound.message());
+        assert_eq!(problem.instance, "/missing");
+        assert_eq!(problem.code.as_deref(), Some(AppError::UserNotFound.code()));
+        assert_eq!(problem.trace_id, "gateway-42");
+    }
+
+    #[actix_web::test]
+    async fn keeps_the_error_response_for_other_clients() {
+        let res = call(
+            TestRequest::get()
+                .uri("/missing")
+                .insert_header((header::ACCEPT, "application/json")),
+        )
+        .await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_ne!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["code"], AppError::UserNotFound.code());
+        assert!(body.get("traceId").is_none());
+    }
+
+    #[actix_web::test]
+    async fn negotiates_errors_of_middleware() {
+        let res = call(
+            TestRequest::get()
+                .uri("/guarded")
+                .insert_header((header::ACCEPT, PROBLEM_JSON))
+                .insert_header((REQUEST_ID_HEADER, "gateway-42")),
+        )
+        .await;
+
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "gateway-42");
+
+        let problem: ProblemDetails = test::read_body_json(res).await;
+        assert_eq!(
+            problem.code.as_deref(),
+            Some(AppError::TokenNotProvided.code())
+        );
+        assert_eq!(problem.instance, "/guarded");
+
+        let res = call(TestRequest::get().uri("/guarded")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_ne!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "accept");
+    }
+
+    #[actix_web::test]
+    async fn tags_successful_responses_with_a_request_id() {
+        let res = call(
+            TestRequest::get()
+                .uri("/ok")
+                .insert_header((REQUEST_ID_HEADER, "abc")),
+        )
+        .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "abc");
+        assert!(res.headers().get(header::VARY).is_none());
+
+        let res = call(TestRequest::get().uri("/ok")).await;
+        let id = res
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok());
+    }
+}
@@ -0,0 +1,40 @@
+use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+
+use async_trait::async_trait;
+
+use super::{gcra, Quota, RateLimitDecision, RateLimitStore};
+
+/// Number of stored keys above which expired ones are swept out.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Keeps counters in process memory. Every instance counts on its own, so
+/// only suited to running a single one.
+#[derive(Default)]
+pub struct MemoryStore {
+    /// Theoretical arrival time per key, in milliseconds since the epoch.
+    buckets: Mutex<HashMap<String, u64>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, String> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_millis() as u64;
+
+        let mut buckets = self.buckets.lock().map_err(|e| e.to_string())?;
+
+        if buckets.len() >= SWEEP_THRESHOLD {
+            // A full bucket is the same as none at all.
+            buckets.retain(|_, tat| *tat > now);
+        }
+
+        let (decision, new_tat) = gcra(buckets.get(key).copied(), now, quota);
+        if let Some(new_tat) = new_tat {
+            buckets.insert(key.to_string(), new_tat);
+        }
+
+        Ok(decision)
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue, RETRY_AFTER},
+    web, HttpMessage, HttpResponse,
+};
+use async_trait::async_trait;
+use futures_util::{
+    future::{ready, LocalBoxFuture, Ready},
+    FutureExt,
+};
+
+use crate::{
+    models::{api_key::ApiKeyModel, user::UserModel},
+    AppState,
+};
+
+use super::{client_ip::client_ip, config::Config, error::AppError};
+
+pub mod memory;
+pub mod redis;
+
+pub use self::redis::RedisStore;
+pub use memory::MemoryStore;
+
+/// How many requests a client may make per `period`. The whole allowance can
+/// be spent at once, after that it refills evenly over the period.
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub limit: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    /// Milliseconds it takes to earn back one request.
+    fn interval_ms(&self) -> u64 {
+        (self.period.as_millis() as u64 / self.limit.max(1) as u64).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Requests left right after this one.
+    pub remaining: u32,
+    /// Until the full allowance is back.
+    pub reset_after: Duration,
+    /// Until the next request will be allowed, set when this one was not.
+    pub retry_after: Option<Duration>,
+}
+
+/// Generic cell rate algorithm, a token bucket that only needs to remember
+/// one timestamp per key: the theoretical arrival time `tat` at which the
+/// bucket would be full again. Returns the decision and the `tat` to store,
+/// or `None` when nothing changes.
+fn gcra(tat: Option<u64>, now: u64, quota: &Quota) -> (RateLimitDecision, Option<u64>) {
+    let interval = quota.interval_ms();
+    let period = quota.period.as_millis() as u64;
+    let tat = tat.unwrap_or(now).max(now);
+    let new_tat = tat + interval;
+    let allow_at = new_tat.saturating_sub(period);
+
+    if now < allow_at {
+        let decision = RateLimitDecision {
+            allowed: false,
+            remaining: 0,
+            reset_after: Duration::from_millis(tat - now),
+            retry_after: Some(Duration::from_millis(allow_at - now)),
+        };
+        return (decision, None);
+    }
+
+    let decision = RateLimitDecision {
+        allowed: true,
+        remaining: ((now - allow_at) / interval) as u32,
+        reset_after: Duration::from_millis(new_tat - now),
+        retry_after: None,
+    };
+    (decision, Some(new_tat))
+}
+
+/// Keeps the rate limit counters. The in-process store is enough for one
+/// instance; instances behind a load balancer have to share Redis.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Counts one request against `key` and decides whether it may pass.
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, String>;
+}
+
+/// Builds the backend selected by `RATE_LIMIT_STORE`.
+pub async fn from_config(config: &Config) -> Result<Arc<dyn RateLimitStore>, String> {
+    match config.rate_limit_store.as_str() {
+        "memory" => Ok(Arc::new(MemoryStore::default())),
+        "redis" => {
+            let url = config
+                .redis_url
+                .as_deref()
+                .ok_or("REDIS_URL must be set for the redis rate limit store")?;
+            Ok(Arc::new(RedisStore::new(url).await?))
+        }
+        other => Err(format!(
+            "Unknown rate limit store \"{}\", expected memory or redis",
+            other
+        )),
+    }
+}
+
+/// Who a limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitKey {
+    /// The client address, as reported by proxies.
+    Ip,
+    /// The signed-in user, or the address for anonymous requests.
+    User,
+    /// The API key, or the user for requests made with a token.
+    ApiKey,
+}
+
+/// Limits how often a client may call the routes it wraps, and reports the
+/// allowance in `RateLimit-Limit`, `RateLimit-Remaining` and
+/// `RateLimit-Reset`. Over the limit, requests get a 429 with `Retry-After`.
+///
+/// Keying by user or API key needs the identity `RequireAuth` stores, so
+/// `RateLimit` has to be wrapped before it, which makes it run after.
+/// Counters are kept per `name`, routes sharing a name share the allowance.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    pub name: Rc<String>,
+    pub quota: Quota,
+    pub key: RateLimitKey,
+}
+
+impl RateLimit {
+    pub fn per_ip(name: &str, limit: u32, period: Duration) -> Self {
+        RateLimit {
+            name: Rc::new(name.to_string()),
+            quota: Quota { limit, period },
+            key: RateLimitKey::Ip,
+        }
+    }
+
+    pub fn per_user(name: &str, limit: u32, period: Duration) -> Self {
+        RateLimit {
+            key: RateLimitKey::User,
+            ..Self::per_ip(name, limit, period)
+        }
+    }
+
+    pub fn per_api_key(name: &str, limit: u32, period: Duration) -> Self {
+        RateLimit {
+            key: RateLimitKey::ApiKey,
+            ..Self::per_ip(name, limit, period)
+        }
+    }
+}
+
+impl<S> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<
+            ServiceRequest,
+            Response = ServiceResponse<actix_web::body::BoxBody>,
+            Error = actix_web::Error,
+        > + 'static,
+{
+    type Response = ServiceResponse<actix_web::body::BoxBody>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            name: self.name.clone(),
+            quota: self.quota,
+            key: self.key,
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    name: Rc<String>,
+    quota: Quota,
+    key: RateLimitKey,
+}
+
+impl<S> RateLimitMiddleware<S> {
+    fn client_key(&self, req: &ServiceRequest, config: &Config) -> String {
+        let extensions = req.extensions();
+
+        if self.key == RateLimitKey::ApiKey {
+            if let Some(api_key) = extensions.get::<ApiKeyModel>() {
+                return format!("key:{}", api_key.id);
+            }
+        }
+
+        if self.key != RateLimitKey::Ip {
+            if let Some(user) = extensions.get::<UserModel>() {
+                return format!("user:{}", user.id);
+            }
+        }
+
+        match client_ip(req.request(), &config.trusted_proxies) {
+            Some(ip_address) => format!("ip:{}", ip_address),
+            None => "ip:unknown".to_string(),
+        }
+    }
+}
+
+impl<S> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<
+            ServiceRequest,
+            Response = ServiceResponse<actix_web::body::BoxBody>,
+            Error = actix_web::Error,
+        > + 'static,
+{
+    type Response = ServiceResponse<actix_web::body::BoxBody>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, actix_web::Error>>;
+
+    fn poll_ready(&self, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let app_state = req.app_data::<web::Data<AppState>>().unwrap().clone();
+        let store = app_state.rate_limit_store.clone();
+        let key = format!(
+            "rate_limit:{}:{}",
+            self.name,
+            self.client_key(&req, &app_state.config)
+        );
+        let quota = self.quota;
+        let srv = Rc::clone(&self.service);
+
+        async move {
+            let decision = match store.hit(&key, &quota).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    // A broken store must not take the API down with it.
+                    eprintln!(
+                        "🔥 Rate limit store failed, letting the request through: {}",
+                        e
+                    );
+                    return srv.call(req).await;
+                }
+            };
+
+            if let Some(retry_after) = decision.retry_after {
+                let mut response = HttpResponse::from_error(AppError::TooManyRequests);
+                let headers = response.headers_mut();
+                insert_rate_limit_headers(headers, &quota, &decision);
+                headers.insert(RETRY_AFTER, HeaderValue::from(ceil_secs(retry_after)));
+                return Ok(req.into_response(response));
+            }
+
+            let mut res = srv.call(req).await?;
+            insert_rate_limit_headers(res.headers_mut(), &quota, &decision);
+            Ok(res)
+        }
+        .boxed_local()
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_millis().div_ceil(1000) as u64
+}
+
+fn insert_rate_limit_headers(
+    headers: &mut actix_web::http::header::HeaderMap,
+    quota: &Quota,
+    decision: &RateLimitDecision,
+) {
+    headers.insert(
+        HeaderName::from_static("ratelimit-limit"),
+        HeaderValue::from(quota.limit),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-remaining"),
+        HeaderValue::from(decision.remaining),
+    );
+    headers.insert(
+        HeaderName::from_static("ratelimit-reset"),
+        HeaderValue::from(ceil_secs(decision.reset_after)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Far enough from the epoch that `allow_at` never saturates.
+    const NOW: u64 = 1_700_000_000_000;
+
+    fn quota() -> Quota {
+        // One request back every 12 seconds.
+        Quota {
+            limit: 5,
+            period: Duration::from_secs(60),
+        }
+    }
+
+    /// Hits the bucket `times` times at `now`, keeping the stored `tat`.
+    fn hit(tat: &mut Option<u64>, now: u64, times: u32) -> Vec<RateLimitDecision> {
+        (0..times)
+            .map(|_| {
+                let (decision, new_tat) = gcra(*tat, now, &quota());
+                if new_tat.is_some() {
+                    *tat = new_tat;
+                }
+                decision
+            })
+            .collect()
+    }
+
+    #[test]
+    fn allows_the_full_burst_then_denies() {
+        let mut tat = None;
+        let decisions = hit(&mut tat, NOW, 6);
+
+        assert!(decisions[..5].iter().all(|d| d.allowed));
+        assert!(!decisions[5].allowed);
+    }
+
+    #[test]
+    fn counts_remaining_down() {
+        let mut tat = None;
+        let remaining = hit(&mut tat, NOW, 5)
+            .iter()
+            .map(|d| d.remaining)
+            .collect::<Vec<_>>();
+
+        assert_eq!(remaining, vec![4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn denied_request_waits_one_interval() {
+        let mut tat = None;
+        hit(&mut tat, NOW, 5);
+        let stored = tat;
+
+        let (decision, new_tat) = gcra(tat, NOW, &quota());
+        assert!(!decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert_eq!(decision.retry_after, Some(Duration::from_secs(12)));
+        assert_eq!(decision.reset_after, Duration::from_secs(60));
+        // Denied requests do not push the bucket further out.
+        assert_eq!(new_tat, None);
+        assert_eq!(tat, stored);
+    }
+
+    #[test]
+    fn refills_one_request_per_interval() {
+        let mut tat = None;
+        hit(&mut tat, NOW, 5);
+
+        let early = hit(&mut tat, NOW + 11_999, 1);
+        assert!(!early[0].allowed);
+        assert_eq!(early[0].retry_after, Some(Duration::from_millis(1)));
+
+        let refilled = hit(&mut tat, NOW + 12_000, 2);
+        assert!(refilled[0].allowed);
+        assert_eq!(refilled[0].remaining, 0);
+        assert!(!refilled[1].allowed);
+    }
+
+    #[test]
+    fn refills_the_full_burst_after_the_period() {
+        let mut tat = None;
+        hit(&mut tat, NOW, 5);
+
+        let decisions = hit(&mut tat, NOW + 60_000, 1);
+        assert!(decisions[0].allowed);
+        assert_eq!(decisions[0].remaining, 4);
+        assert_eq!(decisions[0].reset_after, Duration::from_secs(12));
+    }
+
+    #[test]
+    fn rounds_seconds_up() {
+        assert_eq!(ceil_secs(Duration::ZERO), 0);
+        assert_eq!(ceil_secs(Duration::from_millis(1)), 1);
+        assert_eq!(ceil_secs(Duration::from_millis(1000)), 1);
+        assert_eq!(ceil_secs(Duration::from_millis(1001)), 2);
+    }
+
+    #[test]
+    fn sets_rate_limit_headers() {
+        let mut tat = None;
+        let decision = hit(&mut tat, NOW, 2)[1];
+        let mut headers = actix_web::http::header::HeaderMap::new();
+        insert_rate_limit_headers(&mut headers, &quota(), &decision);
+
+        assert_eq!(headers.get("ratelimit-limit").unwrap(), "5");
+        assert_eq!(headers.get("ratelimit-remaining").unwrap(), "3");
+        assert_eq!(headers.get("ratelimit-reset").unwrap(), "24");
+    }
+}
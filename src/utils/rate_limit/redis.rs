@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use ::redis::{aio::ConnectionManager, Client, Script};
+use async_trait::async_trait;
+
+use super::{Quota, RateLimitDecision, RateLimitStore};
+
+/// The same algorithm as `gcra`, run inside Redis so that concurrent
+/// requests on different instances cannot both take the last token. Uses the
+/// Redis clock, instances may disagree about the time.
+const GCRA_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local interval = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local tat = tonumber(redis.call('GET', KEYS[1])) or now
+if tat < now then
+    tat = now
+end
+local new_tat = tat + interval
+local allow_at = new_tat - period
+if now < allow_at then
+    return {0, 0, tat - now, allow_at - now}
+end
+redis.call('SET', KEYS[1], new_tat, 'PX', new_tat - now)
+return {1, math.floor((now - allow_at) / interval), new_tat - now, 0}
+"#;
+
+/// Keeps counters in Redis, shared by every instance.
+pub struct RedisStore {
+    connection: ConnectionManager,
+    script: Script,
+}
+
+impl RedisStore {
+    pub async fn new(url: &str) -> Result<Self, String> {
+        let client = Client::open(url).map_err(|e| e.to_string())?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(RedisStore {
+            connection,
+            script: Script::new(GCRA_SCRIPT),
+        })
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn hit(&self, key: &str, quota: &Quota) -> Result<RateLimitDecision, String> {
+        let mut connection = self.connection.clone();
+
+        let (allowed, remaining, reset_after, retry_after): (i64, i64, i64, i64) = self
+            .script
+            .key(key)
+            .arg(quota.interval_ms())
+            .arg(quota.period.as_millis() as u64)
+            .invoke_async(&mut connection)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(RateLimitDecision {
+            allowed: allowed == 1,
+            remaining: remaining.max(0) as u32,
+            reset_after: Duration::from_millis(reset_after.max(0) as u64),
+            retry_after: (allowed != 1).then(|| Duration::from_millis(retry_after.max(0) as u64)),
+        })
+    }
+}
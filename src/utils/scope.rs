@@ -0,0 +1,10 @@
+// Permission names, as stored in the `permissions` table. Roles grant them
+// to users, and access tokens carry the user's permissions in their `scope`
+// claim. A token may only be used on routes whose `RequireAuth::permissions`
+// are all present in that claim.
+pub const PROFILE_READ: &str = "profile:read";
+pub const PROFILE_WRITE: &str = "profile:write";
+pub const USERS_READ: &str = "users:read";
+pub const USERS_WRITE: &str = "users:write";
+pub const ROLES_READ: &str = "roles:read";
+pub const ROLES_WRITE: &str = "roles:write";
@@ -0,0 +1,71 @@
+use std::{fs, path::PathBuf};
+
+use actix_web::web;
+use async_trait::async_trait;
+
+use super::{check_key, Storage};
+
+/// Stores objects under a directory on disk. `main` serves that directory at
+/// `/uploads`, so `public_url` normally points there.
+pub struct LocalStorage {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: &str, public_url: &str) -> Result<Self, String> {
+        fs::create_dir_all(root)
+            .map_err(|e| format!("Cannot create storage directory {}: {}", root, e))?;
+
+        Ok(Self {
+            root: PathBuf::from(root),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<(), String> {
+        check_key(key)?;
+        let path = self.root.join(key);
+
+        web::block(move || {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, bytes)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        check_key(key)?;
+        let path = self.root.join(key);
+
+        web::block(move || fs::read(path))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        check_key(key)?;
+        let path = self.root.join(key);
+
+        web::block(move || match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+    }
+
+    fn url(&self, key: &str) -> Result<String, String> {
+        check_key(key)?;
+        Ok(format!("{}/{}", self.public_url, key))
+    }
+}
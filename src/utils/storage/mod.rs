@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::config::Config;
+
+pub mod local;
+pub mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Blob store for user uploads. Objects are addressed by a `/`-separated key
+/// such as `avatars/<user id>/<file>`, which is what gets stored in the
+/// database instead of a URL.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), String>;
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String>;
+
+    async fn delete(&self, key: &str) -> Result<(), String>;
+
+    /// URL a client can fetch `key` from. Backends without public access
+    /// return a signed URL that expires.
+    fn url(&self, key: &str) -> Result<String, String>;
+}
+
+/// Builds the backend selected by `STORAGE`.
+pub fn from_config(config: &Config) -> Result<Arc<dyn Storage>, String> {
+    match config.storage.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::new(
+            &config.storage_local_dir,
+            &config.storage_public_url,
+        )?)),
+        "s3" => Ok(Arc::new(S3Storage::new(config)?)),
+        other => Err(format!(
+            "Unknown storage backend \"{}\", expected local or s3",
+            other
+        )),
+    }
+}
+
+/// Resolves a stored `photo` value to a URL. Values that already are URLs,
+/// e.g. set through `PATCH /api/users/me`, are returned untouched.
+pub fn resolve_url(storage: &dyn Storage, value: &str) -> String {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        return value.to_string();
+    }
+
+    storage.url(value).unwrap_or_else(|e| {
+        eprintln!("🔥 Failed to resolve URL for {}: {}", value, e);
+        value.to_string()
+    })
+}
+
+/// Rejects keys that could escape the storage root.
+fn check_key(key: &str) -> Result<(), String> {
+    if key.is_empty()
+        || key.starts_with('/')
+        || key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(format!("Invalid storage key \"{}\"", key));
+    }
+
+    Ok(())
+}
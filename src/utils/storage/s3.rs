@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use s3::{creds::Credentials, Bucket, Region};
+
+use crate::utils::config::Config;
+
+use super::{check_key, Storage};
+
+/// Stores objects in an S3-compatible bucket, e.g. AWS S3 or a local MinIO.
+///
+/// With `S3_PUBLIC_URL` set, URLs point at that public base. Otherwise every
+/// URL is presigned and valid for `S3_URL_EXPIRY` seconds.
+pub struct S3Storage {
+    bucket: Bucket,
+    public_url: Option<String>,
+    url_expiry: u32,
+}
+
+impl S3Storage {
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let bucket_name = config
+            .s3_bucket
+            .as_deref()
+            .ok_or("S3_BUCKET must be set for the s3 storage")?;
+
+        let region = match &config.s3_endpoint {
+            Some(endpoint) => Region::Custom {
+                region: config.s3_region.to_owned(),
+                endpoint: endpoint.to_owned(),
+            },
+            None => config
+                .s3_region
+                .parse::<Region>()
+                .map_err(|e| e.to_string())?,
+        };
+
+        let credentials = Credentials::new(
+            config.s3_access_key.as_deref(),
+            config.s3_secret_key.as_deref(),
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut bucket =
+            Bucket::new(bucket_name, region, credentials).map_err(|e| e.to_string())?;
+        // MinIO and most self-hosted servers do not resolve bucket subdomains.
+        if config.s3_endpoint.is_some() {
+            bucket = bucket.with_path_style();
+        }
+
+        Ok(Self {
+            bucket,
+            public_url: config
+                .s3_public_url
+                .as_ref()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            url_expiry: config.s3_url_expiry,
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), String> {
+        check_key(key)?;
+
+        let response = self
+            .bucket
+            .put_object_with_content_type(key, &bytes, content_type)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match response.status_code() {
+            200..=299 => Ok(()),
+            status => Err(format!("Upload of {} failed with status {}", key, status)),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        check_key(key)?;
+
+        let response = self
+            .bucket
+            .get_object(key)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match response.status_code() {
+            200..=299 => Ok(response.bytes().to_vec()),
+            status => Err(format!("Download of {} failed with status {}", key, status)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        check_key(key)?;
+
+        let response = self
+            .bucket
+            .delete_object(key)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match response.status_code() {
+            200..=299 | 404 => Ok(()),
+            status => Err(format!("Delete of {} failed with status {}", key, status)),
+        }
+    }
+
+    fn url(&self, key: &str) -> Result<String, String> {
+        check_key(key)?;
+
+        match &self.public_url {
+            Some(public_url) => Ok(format!("{}/{}", public_url, key)),
+            None => self
+                .bucket
+                .presign_get(key, self.url_expiry, None)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
@@ -1,19 +1,43 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use super::error::{ErrorMessage, HttpError};
+use super::{error::AppError, jwt_keys::JwtKeys};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TokenClaims {
+    pub iss: String,
+    pub aud: String,
     pub sub: String,
+    /// Id of the server-side session the token was issued for, or of the API
+    /// key the request was made with.
+    pub jti: String,
+    /// Roles of the user when the token was issued.
+    pub roles: Vec<String>,
+    /// Permissions the token grants.
+    pub scope: Vec<String>,
+    /// Organization the session acts in. Tokens issued before one was
+    /// chosen do not carry it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
     pub iat: usize,
     pub exp: usize,
 }
 
+impl TokenClaims {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope.iter().any(|granted| granted == scope)
+    }
+}
+
 pub fn create_token(
     user_id: &str,
-    secret: &[u8],
+    roles: Vec<String>,
+    scope: Vec<String>,
+    session_id: &str,
+    organization_id: Option<&str>,
+    keys: &JwtKeys,
     expires_in_seconds: i64,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     if user_id.is_empty() {
@@ -24,27 +48,136 @@ pub fn create_token(
     let iat = now.timestamp() as usize;
     let exp = (now + Duration::minutes(expires_in_seconds)).timestamp() as usize;
     let claims: TokenClaims = TokenClaims {
+        iss: keys.issuer().to_string(),
+        aud: keys.audience().to_string(),
         sub: user_id.to_string(),
+        jti: session_id.to_string(),
+        roles,
+        scope,
+        org: organization_id.map(|id| id.to_string()),
         exp,
         iat,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
+    keys.encode(&claims)
 }
 
-pub fn decode_token<T: Into<String>>(token: T, secret: &[u8]) -> Result<String, HttpError> {
-    let decoded = decode::<TokenClaims>(
-        &token.into(),
-        &DecodingKey::from_secret(secret),
-        &Validation::new(Algorithm::HS256),
-    );
+pub fn decode_token<T: Into<String>>(token: T, keys: &JwtKeys) -> Result<TokenClaims, AppError> {
+    let decoded = keys.decode::<TokenClaims>(&token.into(), keys.audience());
 
     match decoded {
-        Ok(token) => Ok(token.claims.sub),
-        Err(_) => Err(HttpError::new(ErrorMessage::InvalidToken.to_string(), 401)),
+        Ok(claims) => Ok(claims),
+        Err(_) => Err(AppError::InvalidToken),
+    }
+}
+
+/// What a single-purpose token may be used for. Stored in the `purpose` claim
+/// so a token minted for one flow is rejected by every other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenPurpose {
+    EmailVerification,
+    MfaPending,
+}
+
+impl TokenPurpose {
+    pub fn to_str(&self) -> &str {
+        match self {
+            TokenPurpose::EmailVerification => "email_verification",
+            TokenPurpose::MfaPending => "mfa_pending",
+        }
     }
+
+    /// `<audience>:<purpose>`. Purpose tokens are signed with the keys
+    /// published in the JWKS, so without their own audience other services
+    /// would take a password-only `mfa_pending` token for an access token.
+    fn audience(&self, keys: &JwtKeys) -> String {
+        format!("{}:{}", keys.audience(), self.to_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurposeTokenClaims {
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub purpose: String,
+    /// Set on tokens that are only good once, such as `mfa_pending`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    pub iat: usize,
+    pub exp: usize,
+}
+
+pub fn create_purpose_token(
+    user_id: &str,
+    purpose: TokenPurpose,
+    token_id: Option<&str>,
+    keys: &JwtKeys,
+    expires_in_minutes: i64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    if user_id.is_empty() {
+        return Err(jsonwebtoken::errors::ErrorKind::InvalidSubject.into());
+    }
+
+    let now = Utc::now();
+    let claims = PurposeTokenClaims {
+        iss: keys.issuer().to_string(),
+        aud: purpose.audience(keys),
+        sub: user_id.to_string(),
+        purpose: purpose.to_str().to_string(),
+        jti: token_id.map(|id| id.to_string()),
+        iat: now.timestamp() as usize,
+        exp: (now + Duration::minutes(expires_in_minutes)).timestamp() as usize,
+    };
+
+    keys.encode(&claims)
+}
+
+/// Decodes a single-purpose token, failing when it was minted for a
+/// different purpose.
+pub fn decode_purpose_token<T: Into<String>>(
+    token: T,
+    purpose: TokenPurpose,
+    keys: &JwtKeys,
+) -> Result<PurposeTokenClaims, AppError> {
+    let decoded = keys.decode::<PurposeTokenClaims>(&token.into(), &purpose.audience(keys));
+
+    match decoded {
+        Ok(claims) if claims.purpose == purpose.to_str() => Ok(claims),
+        _ => Err(AppError::InvalidToken),
+    }
+}
+
+/// Generates a random, URL-safe token that carries no claims of its own and
+/// only has meaning through the row it is stored against.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Opaque tokens are high-entropy, so a fast digest is enough to keep them
+/// useless if the table leaks.
+pub fn hash_opaque_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// A 6-digit code for people to type in. It only resists guessing together
+/// with an attempt limit and a short lifetime.
+pub fn generate_login_code() -> String {
+    format!("{:06}", OsRng.next_u32() % 1_000_000)
+}
+
+/// Start of every API key. Tells them apart from JWTs and makes leaked keys
+/// easy to find with secret scanners.
+pub const API_KEY_PREFIX: &str = "rfa_";
+/// Leading characters of an API key that are stored and shown in clear text.
+pub const API_KEY_DISPLAY_LENGTH: usize = 12;
+
+pub fn generate_api_key() -> String {
+    format!("{}{}", API_KEY_PREFIX, generate_opaque_token())
+}
+
+pub fn is_api_key(credential: &str) -> bool {
+    credential.starts_with(API_KEY_PREFIX)
 }
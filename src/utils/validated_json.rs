@@ -0,0 +1,70 @@
+use std::ops::Deref;
+
+use actix_web::{
+    dev::Payload, error::JsonPayloadError, web, FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use super::error::AppError;
+
+/// A JSON body that passed its `Validate` rules. Use it in place of
+/// `web::Json` so handlers never see unchecked input.
+///
+/// A body that cannot be read as `T`, or breaks a rule, ends the request
+/// with `AppError`, whose `errors` name the offending fields.
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        // Same content types `web::Json` accepts.
+        let content_type = req.content_type();
+        let is_json = content_type == "application/json" || content_type.ends_with("+json");
+        let body = web::Bytes::from_request(req, payload);
+
+        async move {
+            if !is_json {
+                return Err(AppError::from(JsonPayloadError::ContentType).into());
+            }
+
+            let body = body
+                .await
+                .map_err(|e| AppError::InvalidPayload(e.to_string()))?;
+
+            // Unlike `serde_json::from_slice`, this tells which field a type
+            // error is in.
+            let deserializer = &mut serde_json::Deserializer::from_slice(&body);
+            let value: T =
+                serde_path_to_error::deserialize(&mut *deserializer).map_err(AppError::from)?;
+            deserializer
+                .end()
+                .map_err(|e| AppError::from(JsonPayloadError::Deserialize(e)))?;
+
+            value.validate().map_err(AppError::from)?;
+
+            Ok(ValidatedJson(value))
+        }
+        .boxed_local()
+    }
+}